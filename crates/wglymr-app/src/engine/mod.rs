@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-use wglymr_document::DocumentAdapter;
+use wglymr_document::{
+    CommandOutcome, DocumentAdapter, DocumentError, DocumentSubscriber, EditorCommand,
+    SubscriptionId,
+};
 use wglymr_interaction::{InputDispatcher, KeyModifiers, MouseEvent};
 use wglymr_view::{
     build_render_model, DepthLayer, EditorView, GlobalInteractionState, NodeLayoutConstants,
@@ -37,6 +40,29 @@ impl EditorEngine {
         }
     }
 
+    pub fn document(&self) -> &dyn DocumentAdapter {
+        self.document.as_ref()
+    }
+
+    pub fn apply_command(
+        &mut self,
+        command: EditorCommand,
+    ) -> Result<CommandOutcome, DocumentError> {
+        self.document.apply_command(command)
+    }
+
+    pub fn refresh_diagnostics(&mut self) {
+        self.document.refresh_diagnostics();
+    }
+
+    pub fn subscribe(&mut self, subscriber: DocumentSubscriber) -> SubscriptionId {
+        self.document.subscribe(subscriber)
+    }
+
+    pub fn unsubscribe(&mut self, subscription: SubscriptionId) -> bool {
+        self.document.unsubscribe(subscription)
+    }
+
     pub fn create_view(&mut self, view_id: ViewId) {
         self.views.insert(view_id, EditorView::new());
     }
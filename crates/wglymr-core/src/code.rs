@@ -0,0 +1,218 @@
+//! Custom WGSL code nodes.
+//!
+//! A `NodeKind::Code` node carries a WGSL snippet. Its input sockets become
+//! function parameters and its output sockets become results, so the snippet
+//! is wrapped in a generated helper function:
+//!
+//! - a single output with an expression body (`a * b + 1.0`) returns the
+//!   expression directly;
+//! - otherwise the body is a list of statements assigning one variable per
+//!   output socket, named after the socket.
+//!
+//! Nodes with several outputs return a generated struct and get one accessor
+//! function per output, so the IR only ever calls functions returning plain
+//! values. The generated source is validated with naga when the `naga`
+//! feature is enabled.
+
+use thiserror::Error;
+
+use crate::wgsl::type_to_wgsl;
+use crate::{IrHelper, IrType, NodeId};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CodeError {
+    #[error("code node body is empty")]
+    EmptyBody,
+
+    #[error("code node must declare at least one output")]
+    NoOutputs,
+
+    #[error("socket name {0:?} is not a valid WGSL identifier")]
+    InvalidIdentifier(String),
+
+    #[error("socket name {0:?} is used more than once")]
+    DuplicateName(String),
+
+    #[error("syntax error: {0}")]
+    Syntax(String),
+
+    #[error("type error: {0}")]
+    Type(String),
+}
+
+/// Helper functions generated for one code node
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeFunctions {
+    /// Helper source, emitted once per program
+    pub helper: IrHelper,
+    /// Function to call for each output, in output order
+    pub outputs: Vec<String>,
+}
+
+/// Wrap a code node body in helper functions
+///
+/// `inputs` and `outputs` are the node's socket names and types in order.
+pub fn generate_code_functions(
+    node: NodeId,
+    body: &str,
+    inputs: &[(String, IrType)],
+    outputs: &[(String, IrType)],
+) -> Result<CodeFunctions, CodeError> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(CodeError::EmptyBody);
+    }
+    if outputs.is_empty() {
+        return Err(CodeError::NoOutputs);
+    }
+
+    let mut seen: Vec<&str> = Vec::new();
+    for (name, _) in inputs.iter().chain(outputs) {
+        if !is_identifier(name) {
+            return Err(CodeError::InvalidIdentifier(name.clone()));
+        }
+        if seen.contains(&name.as_str()) {
+            return Err(CodeError::DuplicateName(name.clone()));
+        }
+        seen.push(name);
+    }
+
+    let name = format!("code_node_{}", node.0);
+    let params: Vec<String> = inputs
+        .iter()
+        .map(|(param, ty)| format!("{}: {}", param, type_to_wgsl(*ty)))
+        .collect();
+    let params = params.join(", ");
+    let arg_names: Vec<&str> = inputs.iter().map(|(param, _)| param.as_str()).collect();
+    let args = arg_names.join(", ");
+
+    let declarations: String = outputs
+        .iter()
+        .map(|(output, ty)| format!("    var {}: {};\n", output, type_to_wgsl(*ty)))
+        .collect();
+
+    let mut source = String::new();
+    let mut functions = Vec::new();
+
+    if let [(output, ty)] = outputs {
+        source.push_str(&format!(
+            "fn {}({}) -> {} {{\n",
+            name,
+            params,
+            type_to_wgsl(*ty)
+        ));
+        if is_expression(body) {
+            source.push_str(&format!("    return ({});\n", body));
+        } else {
+            source.push_str(&declarations);
+            source.push_str(&format!("    {}\n", body));
+            source.push_str(&format!("    return {};\n", output));
+        }
+        source.push_str("}\n");
+        functions.push(name.clone());
+    } else {
+        let struct_name = format!("CodeNode{}", node.0);
+
+        source.push_str(&format!("struct {} {{\n", struct_name));
+        for (output, ty) in outputs {
+            source.push_str(&format!("    {}: {},\n", output, type_to_wgsl(*ty)));
+        }
+        source.push_str("}\n\n");
+
+        let output_names: Vec<&str> = outputs.iter().map(|(output, _)| output.as_str()).collect();
+        source.push_str(&format!("fn {}({}) -> {} {{\n", name, params, struct_name));
+        source.push_str(&declarations);
+        source.push_str(&format!("    {}\n", body));
+        source.push_str(&format!(
+            "    return {}({});\n",
+            struct_name,
+            output_names.join(", ")
+        ));
+        source.push_str("}\n");
+
+        for (output, ty) in outputs {
+            let accessor = format!("{}_{}", name, output);
+            source.push_str(&format!(
+                "\nfn {}({}) -> {} {{\n    return {}({}).{};\n}}\n",
+                accessor,
+                params,
+                type_to_wgsl(*ty),
+                name,
+                args,
+                output
+            ));
+            functions.push(accessor);
+        }
+    }
+
+    validate_wgsl(&source)?;
+
+    Ok(CodeFunctions {
+        helper: IrHelper { name, source },
+        outputs: functions,
+    })
+}
+
+/// A body without statements is treated as a single expression
+fn is_expression(body: &str) -> bool {
+    !body.contains(';') && !body.contains('{')
+}
+
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    name != "_" && !name.starts_with("__") && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Parse and validate a standalone WGSL module
+#[cfg(feature = "naga")]
+pub fn validate_wgsl(source: &str) -> Result<(), CodeError> {
+    use naga::valid::{Capabilities, ValidationFlags, Validator};
+
+    let module = naga::front::wgsl::parse_str(source)
+        .map_err(|err| CodeError::Syntax(err.message().to_string()))?;
+
+    Validator::new(ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .map_err(|err| {
+            let mut message = err.as_inner().to_string();
+            let mut source = std::error::Error::source(err.as_inner());
+            while let Some(cause) = source {
+                message.push_str(": ");
+                message.push_str(&cause.to_string());
+                source = cause.source();
+            }
+            CodeError::Type(message)
+        })?;
+
+    Ok(())
+}
+
+/// Check bracket balance when naga is unavailable
+#[cfg(not(feature = "naga"))]
+pub fn validate_wgsl(source: &str) -> Result<(), CodeError> {
+    let mut stack = Vec::new();
+    for c in source.chars() {
+        match c {
+            '(' | '[' | '{' => stack.push(c),
+            ')' | ']' | '}' => {
+                let open = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+                if stack.pop() != Some(open) {
+                    return Err(CodeError::Syntax(format!("unexpected {:?}", c)));
+                }
+            }
+            _ => {}
+        }
+    }
+    match stack.pop() {
+        Some(open) => Err(CodeError::Syntax(format!("unclosed {:?}", open))),
+        None => Ok(()),
+    }
+}
@@ -6,7 +6,10 @@
 
 use std::collections::HashSet;
 
-use crate::{ConversionError, GraphView, IrLoweringError, IrType, NodeId, SocketId, TypeError};
+use crate::{
+    CompileError, ConversionError, GraphCycle, GraphError, GraphView, GroupError, IrLoweringError,
+    IrType, LinkId, NodeId, SocketId, TypeError,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DiagnosticSeverity {
@@ -20,65 +23,189 @@ pub struct Diagnostic {
     pub message: String,
     pub node: Option<NodeId>,
     pub socket: Option<SocketId>,
+    pub link: Option<LinkId>,
 }
 
 #[allow(dead_code)]
 impl Diagnostic {
-    fn error(message: String) -> Self {
+    pub(crate) fn error(message: String) -> Self {
         Self {
             severity: DiagnosticSeverity::Error,
             message,
             node: None,
             socket: None,
+            link: None,
         }
     }
 
-    fn error_at_socket(socket: SocketId, message: String) -> Self {
+    pub(crate) fn error_at_socket(socket: SocketId, message: String) -> Self {
         Self {
             severity: DiagnosticSeverity::Error,
             message,
             node: None,
             socket: Some(socket),
+            link: None,
         }
     }
 
-    fn error_at_node(node: NodeId, message: String) -> Self {
+    pub(crate) fn error_at_node(node: NodeId, message: String) -> Self {
         Self {
             severity: DiagnosticSeverity::Error,
             message,
             node: Some(node),
             socket: None,
+            link: None,
         }
     }
 
-    fn warning(message: String) -> Self {
+    pub(crate) fn error_at_link(link: LinkId, message: String) -> Self {
+        Self {
+            severity: DiagnosticSeverity::Error,
+            message,
+            node: None,
+            socket: None,
+            link: Some(link),
+        }
+    }
+
+    pub(crate) fn warning(message: String) -> Self {
         Self {
             severity: DiagnosticSeverity::Warning,
             message,
             node: None,
             socket: None,
+            link: None,
         }
     }
 
-    fn warning_at_socket(socket: SocketId, message: String) -> Self {
+    pub(crate) fn warning_at_socket(socket: SocketId, message: String) -> Self {
         Self {
             severity: DiagnosticSeverity::Warning,
             message,
             node: None,
             socket: Some(socket),
+            link: None,
         }
     }
 
-    fn warning_at_node(node: NodeId, message: String) -> Self {
+    pub(crate) fn warning_at_node(node: NodeId, message: String) -> Self {
         Self {
             severity: DiagnosticSeverity::Warning,
             message,
             node: Some(node),
             socket: None,
+            link: None,
         }
     }
 }
 
+/// Convert a GraphError into user-facing diagnostics
+pub fn diagnostics_from_graph_error(err: &GraphError) -> Vec<Diagnostic> {
+    match err {
+        GraphError::SocketNotFound { socket } => {
+            vec![Diagnostic::error_at_socket(
+                *socket,
+                "Socket not found".to_string(),
+            )]
+        }
+
+        GraphError::NodeNotFound { node } => {
+            vec![Diagnostic::error_at_node(
+                *node,
+                "Node not found".to_string(),
+            )]
+        }
+
+        GraphError::CycleDetected { cycles } => cycles.iter().flat_map(cycle_diagnostics).collect(),
+
+        GraphError::LinkClosesCycle { nodes, path } => {
+            let mut diagnostics: Vec<Diagnostic> = path
+                .iter()
+                .map(|link| {
+                    Diagnostic::error_at_link(
+                        *link,
+                        "Link is part of the cycle the new link would close".to_string(),
+                    )
+                })
+                .collect();
+            diagnostics.push(Diagnostic::error(format!(
+                "Link would close a cycle through nodes {}",
+                format_nodes(nodes)
+            )));
+            diagnostics
+        }
+
+        GraphError::RequiredInput { socket } => {
+            vec![Diagnostic::error_at_socket(
+                *socket,
+                "Required input takes no default value".to_string(),
+            )]
+        }
+
+        GraphError::OptionalInputMissingDefault { socket } => {
+            vec![Diagnostic::error_at_socket(
+                *socket,
+                "Optional input missing default value".to_string(),
+            )]
+        }
+
+        GraphError::DefaultLiteralTypeMismatch {
+            socket,
+            expected,
+            found,
+        } => {
+            vec![Diagnostic::error_at_socket(
+                *socket,
+                format!(
+                    "Default value type mismatch: expected {:?}, found {:?}",
+                    expected, found
+                ),
+            )]
+        }
+
+        GraphError::DefaultOutOfRange { socket, min, max } => {
+            let range = match (min, max) {
+                (Some(min), Some(max)) => format!("between {} and {}", min, max),
+                (Some(min), None) => format!("at least {}", min),
+                (None, Some(max)) => format!("at most {}", max),
+                (None, None) => "within range".to_string(),
+            };
+            vec![Diagnostic::error_at_socket(
+                *socket,
+                format!("Default value must be {}", range),
+            )]
+        }
+
+        GraphError::WrongDirection { .. }
+        | GraphError::TypeMismatch { .. }
+        | GraphError::InputAlreadyConnected => vec![Diagnostic::error(err.to_string())],
+    }
+}
+
+/// Errors on every node and link of a cycle
+pub fn cycle_diagnostics(cycle: &GraphCycle) -> Vec<Diagnostic> {
+    let message = format!(
+        "Part of a cycle through nodes {}",
+        format_nodes(&cycle.nodes)
+    );
+
+    let nodes = cycle
+        .nodes
+        .iter()
+        .map(|node| Diagnostic::error_at_node(*node, message.clone()));
+    let links = cycle
+        .links
+        .iter()
+        .map(|link| Diagnostic::error_at_link(*link, message.clone()));
+
+    nodes.chain(links).collect()
+}
+
+fn format_nodes(nodes: &[NodeId]) -> String {
+    let names: Vec<String> = nodes.iter().map(|node| node.0.to_string()).collect();
+    names.join(", ")
+}
+
 /// Convert a TypeError into user-facing diagnostics
 pub fn diagnostics_from_type_error(err: &TypeError) -> Vec<Diagnostic> {
     match err {
@@ -120,6 +247,30 @@ pub fn diagnostics_from_type_error(err: &TypeError) -> Vec<Diagnostic> {
                 "Required input is not connected".to_string(),
             )]
         }
+
+        TypeError::GroupNotInlined => {
+            vec![Diagnostic::error(
+                "Node groups must be inlined before compilation".to_string(),
+            )]
+        }
+
+        TypeError::ArityMismatch { expected, found } => {
+            vec![Diagnostic::error(format!(
+                "Wrong number of inputs: expected {}, found {}",
+                expected, found
+            ))]
+        }
+
+        TypeError::UnknownNodeDefinition(id) => {
+            vec![Diagnostic::error(format!("Unknown node type {:?}", id))]
+        }
+
+        TypeError::UnsupportedOperands { op, lhs, rhs } => {
+            vec![Diagnostic::error(format!(
+                "Cannot apply {:?} to {:?} and {:?}",
+                op, lhs, rhs
+            ))]
+        }
     }
 }
 
@@ -140,10 +291,25 @@ pub fn diagnostics_from_lowering_error(err: &IrLoweringError) -> Vec<Diagnostic>
             )]
         }
 
+        IrLoweringError::MissingOutput(socket) => {
+            vec![Diagnostic::error_at_socket(
+                *socket,
+                "Output value was not computed".to_string(),
+            )]
+        }
+
         IrLoweringError::UnsupportedNode => {
             vec![Diagnostic::error("Unsupported node type".to_string())]
         }
 
+        IrLoweringError::InvalidCode { node, error } => {
+            vec![Diagnostic::error_at_node(*node, error.to_string())]
+        }
+
+        IrLoweringError::UnknownNodeDefinition(id) => {
+            vec![Diagnostic::error(format!("Unknown node type {:?}", id))]
+        }
+
         IrLoweringError::OptionalInputMissingDefault(socket) => {
             vec![Diagnostic::error_at_socket(
                 *socket,
@@ -165,6 +331,56 @@ pub fn diagnostics_from_conversion_error(err: &ConversionError) -> Vec<Diagnosti
     }
 }
 
+/// Convert a CompileError into user-facing diagnostics
+pub fn diagnostics_from_compile_error(err: &CompileError) -> Vec<Diagnostic> {
+    match err {
+        CompileError::Graph(err) => diagnostics_from_graph_error(err),
+        CompileError::Type(err) => diagnostics_from_type_error(err),
+        CompileError::Lowering(err) => diagnostics_from_lowering_error(err),
+        CompileError::Conversion(err) => diagnostics_from_conversion_error(err),
+    }
+}
+
+/// Convert a GroupError into user-facing diagnostics
+pub fn diagnostics_from_group_error(err: &GroupError) -> Vec<Diagnostic> {
+    match err {
+        GroupError::GroupNotFound { group } => {
+            vec![Diagnostic::error(format!(
+                "Node group {} not found",
+                group.0
+            ))]
+        }
+
+        GroupError::RecursiveGroup { group, path } => {
+            let chain: Vec<String> = path.iter().map(|g| g.0.to_string()).collect();
+            vec![Diagnostic::error(format!(
+                "Node group {} contains itself ({})",
+                group.0,
+                chain.join(" > ")
+            ))]
+        }
+
+        GroupError::DuplicateInterfaceNode { group, kind } => {
+            vec![Diagnostic::error(format!(
+                "Node group {} has more than one {:?} node",
+                group.0, kind
+            ))]
+        }
+
+        GroupError::InterfaceMismatch { node, group } => {
+            vec![Diagnostic::error_at_node(
+                *node,
+                format!(
+                    "Sockets do not match the interface of node group {}",
+                    group.0
+                ),
+            )]
+        }
+
+        GroupError::Graph(err) => diagnostics_from_graph_error(err),
+    }
+}
+
 /// Create a warning diagnostic for an inserted conversion
 pub fn warning_conversion_inserted(socket: SocketId, from: IrType, to: IrType) -> Diagnostic {
     Diagnostic::warning_at_socket(
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::{NodeId, SocketDirection, SocketId, ValueType};
+use crate::{GraphCycle, LinkId, NodeId, SocketDirection, SocketId, ValueType};
 
 #[derive(Debug, Error)]
 pub enum GraphError {
@@ -22,12 +22,33 @@ pub enum GraphError {
     #[error("Input socket already has an incoming connection")]
     InputAlreadyConnected,
 
-    #[error("Graph contains cycles, cannot compute topological order")]
-    CycleDetected,
+    #[error(
+        "Graph contains {} cycle(s), cannot compute topological order",
+        .cycles.len()
+    )]
+    CycleDetected { cycles: Vec<GraphCycle> },
+
+    /// `nodes` run downstream from the target node to the source node,
+    /// joined by the existing links in `path`
+    #[error("Link would close a cycle through nodes {nodes:?}")]
+    LinkClosesCycle {
+        nodes: Vec<NodeId>,
+        path: Vec<LinkId>,
+    },
+
+    #[error("Input socket {socket:?} is required and takes no default value")]
+    RequiredInput { socket: SocketId },
 
     #[error("Optional input socket {socket:?} missing default value")]
     OptionalInputMissingDefault { socket: SocketId },
 
+    #[error("Default value for socket {socket:?} is outside {min:?}..{max:?}")]
+    DefaultOutOfRange {
+        socket: SocketId,
+        min: Option<f32>,
+        max: Option<f32>,
+    },
+
     #[error("Default literal type mismatch for socket {socket:?}: expected {expected:?}, found {found:?}")]
     DefaultLiteralTypeMismatch {
         socket: SocketId,
@@ -1,11 +1,13 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use glam::Vec2;
 use serde::{Deserialize, Serialize};
 
+use crate::passes::downstream_path;
 use crate::{
-    GraphError, InputSocketConfig, Link, LinkId, Node, NodeId, NodeKind, Socket, SocketDirection,
-    SocketId, ValueType,
+    GraphError, InputSocketConfig, Link, LinkId, Literal, Node, NodeId, NodeKind, NodeProperties,
+    OutOfRange, PropertyValue, Socket, SocketDirection, SocketId, SocketMetadata, ValueType,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +19,38 @@ pub struct Graph {
     next_node_id: u64,
     next_socket_id: u64,
     next_link_id: u64,
+
+    /// Bumped by every mutation
+    #[serde(default)]
+    revision: u64,
+
+    /// Refuse links that would close a cycle
+    #[serde(default)]
+    reject_cycles: bool,
+
+    /// Tells graphs at the same revision apart
+    #[serde(skip)]
+    instance: GraphInstance,
+}
+
+/// Process-wide unique id of one graph value
+///
+/// Clones and deserialized graphs get a new id, since their revision no
+/// longer identifies their contents once they are edited independently.
+#[derive(Debug)]
+struct GraphInstance(u64);
+
+impl Default for GraphInstance {
+    fn default() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Clone for GraphInstance {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
 }
 
 pub struct InputDef {
@@ -45,6 +79,11 @@ impl InputDef {
             config: InputSocketConfig::optional_with_default(default),
         }
     }
+
+    pub fn with_metadata(mut self, metadata: SocketMetadata) -> Self {
+        self.config.metadata = metadata;
+        self
+    }
 }
 
 impl Graph {
@@ -56,9 +95,34 @@ impl Graph {
             next_node_id: 0,
             next_socket_id: 0,
             next_link_id: 0,
+            revision: 0,
+            reject_cycles: false,
+            instance: GraphInstance::default(),
         }
     }
 
+    /// Counter incremented by every mutation of the graph
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Id distinguishing this graph value from every other, including its
+    /// clones
+    pub(crate) fn instance_id(&self) -> u64 {
+        self.instance.0
+    }
+
+    /// Make `connect` refuse links that would close a cycle
+    ///
+    /// Links already in the graph are left alone.
+    pub fn set_reject_cycles(&mut self, reject: bool) {
+        self.reject_cycles = reject;
+    }
+
+    pub fn rejects_cycles(&self) -> bool {
+        self.reject_cycles
+    }
+
     pub fn add_node(
         &mut self,
         kind: NodeKind,
@@ -128,9 +192,11 @@ impl Graph {
             inputs: input_ids,
             outputs: output_ids,
             position,
+            properties: NodeProperties::new(),
         };
 
         self.nodes.insert(node_id, node);
+        self.revision += 1;
         node_id
     }
 
@@ -168,6 +234,12 @@ impl Graph {
             return Err(GraphError::InputAlreadyConnected);
         }
 
+        if self.reject_cycles {
+            if let Some((nodes, path)) = downstream_path(self, to_socket.node, from_socket.node) {
+                return Err(GraphError::LinkClosesCycle { nodes, path });
+            }
+        }
+
         let link_id = LinkId(self.next_link_id);
         self.next_link_id += 1;
 
@@ -178,11 +250,130 @@ impl Graph {
         };
 
         self.links.insert(link_id, link);
+        self.revision += 1;
         Ok(link_id)
     }
 
     pub fn disconnect(&mut self, link: LinkId) -> bool {
-        self.links.remove(&link).is_some()
+        let removed = self.links.remove(&link).is_some();
+        if removed {
+            self.revision += 1;
+        }
+        removed
+    }
+
+    /// Replace the default value of an optional input socket
+    ///
+    /// Values outside the socket's hard range are clamped or rejected as
+    /// its metadata specifies.
+    pub fn set_input_default(
+        &mut self,
+        socket: SocketId,
+        value: Literal,
+    ) -> Result<(), GraphError> {
+        let target = self
+            .sockets
+            .get_mut(&socket)
+            .ok_or(GraphError::SocketNotFound { socket })?;
+
+        let config = match (&target.direction, target.input_config.as_mut()) {
+            (SocketDirection::Input, Some(config)) if config.optional => config,
+            (SocketDirection::Input, _) => return Err(GraphError::RequiredInput { socket }),
+            (found, _) => {
+                return Err(GraphError::WrongDirection {
+                    expected: SocketDirection::Input,
+                    found: *found,
+                })
+            }
+        };
+
+        if value.value_type() != target.value_type {
+            return Err(GraphError::DefaultLiteralTypeMismatch {
+                socket,
+                expected: target.value_type,
+                found: value.value_type(),
+            });
+        }
+
+        let metadata = &config.metadata;
+        let value = match metadata.out_of_range {
+            OutOfRange::Clamp => metadata.clamp(&value),
+            OutOfRange::Reject if !metadata.in_range(&value) => {
+                return Err(GraphError::DefaultOutOfRange {
+                    socket,
+                    min: metadata.hard_min,
+                    max: metadata.hard_max,
+                });
+            }
+            OutOfRange::Reject => value,
+        };
+
+        config.default = Some(value);
+        self.revision += 1;
+        Ok(())
+    }
+
+    pub fn set_node_position(&mut self, node: NodeId, position: Vec2) -> Result<(), GraphError> {
+        let target = self
+            .nodes
+            .get_mut(&node)
+            .ok_or(GraphError::NodeNotFound { node })?;
+        target.position = position;
+        self.revision += 1;
+        Ok(())
+    }
+
+    /// Set a property value on a node, returning the previous value
+    ///
+    /// Values are not checked here; see `NodeDefinition::check_property`.
+    pub fn set_node_property(
+        &mut self,
+        node: NodeId,
+        name: impl Into<String>,
+        value: PropertyValue,
+    ) -> Result<Option<PropertyValue>, GraphError> {
+        let target = self
+            .nodes
+            .get_mut(&node)
+            .ok_or(GraphError::NodeNotFound { node })?;
+        let previous = target.properties.insert(name.into(), value);
+        self.revision += 1;
+        Ok(previous)
+    }
+
+    /// Remove a property value from a node, returning the removed value
+    ///
+    /// The property falls back to its declared default.
+    pub fn remove_node_property(
+        &mut self,
+        node: NodeId,
+        name: &str,
+    ) -> Result<Option<PropertyValue>, GraphError> {
+        let target = self
+            .nodes
+            .get_mut(&node)
+            .ok_or(GraphError::NodeNotFound { node })?;
+        let removed = target.properties.remove(name);
+        if removed.is_some() {
+            self.revision += 1;
+        }
+        Ok(removed)
+    }
+
+    /// Remove a node together with its sockets and every link touching them
+    pub fn remove_node(&mut self, id: NodeId) -> Option<Node> {
+        let node = self.nodes.remove(&id)?;
+
+        for socket_id in node.inputs.iter().chain(node.outputs.iter()) {
+            self.sockets.remove(socket_id);
+        }
+
+        self.links.retain(|_, link| {
+            self.sockets.contains_key(&link.from) && self.sockets.contains_key(&link.to)
+        });
+
+        self.revision += 1;
+        Some(node)
     }
 
     pub fn node(&self, id: NodeId) -> Option<&Node> {
@@ -205,6 +396,10 @@ impl Graph {
         self.links.values().filter(move |link| link.from == socket)
     }
 
+    pub fn links(&self) -> impl Iterator<Item = &Link> {
+        self.links.values()
+    }
+
     pub fn nodes(&self) -> impl Iterator<Item = &Node> {
         self.nodes.values()
     }
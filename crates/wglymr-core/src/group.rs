@@ -0,0 +1,557 @@
+//! Node groups - reusable subgraphs instanced through `NodeKind::Group`.
+//!
+//! A group is a `Graph` stored in a `GroupLibrary`. Its interface is defined by
+//! an optional `GroupInput` node (whose outputs become the group's inputs) and an
+//! optional `GroupOutput` node (whose inputs become the group's outputs).
+//!
+//! `inline_groups` splices every group instance into its parent before
+//! `build_graph_view`, leaving identity `GroupInput`/`GroupOutput` boundary nodes
+//! in place of the instance so diagnostics can be mapped back through group
+//! boundaries.
+
+use std::collections::HashMap;
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{Diagnostic, Graph, GraphError, InputDef, Node, NodeId, NodeKind, SocketId, ValueType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GroupId(pub u64);
+
+#[derive(Debug, Error)]
+pub enum GroupError {
+    #[error("Group {group:?} not found")]
+    GroupNotFound { group: GroupId },
+
+    #[error("Group {group:?} references itself through {path:?}")]
+    RecursiveGroup { group: GroupId, path: Vec<GroupId> },
+
+    #[error("Group {group:?} has more than one {kind:?} node")]
+    DuplicateInterfaceNode { group: GroupId, kind: NodeKind },
+
+    #[error("Group node {node:?} sockets do not match the interface of group {group:?}")]
+    InterfaceMismatch { node: NodeId, group: GroupId },
+
+    #[error(transparent)]
+    Graph(#[from] GraphError),
+}
+
+/// A named subgraph that can be instanced by group nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupDef {
+    pub name: String,
+    pub graph: Graph,
+}
+
+/// Input and output sockets exposed by a group
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupInterface {
+    pub inputs: Vec<(String, ValueType)>,
+    pub outputs: Vec<(String, ValueType)>,
+    pub input_node: Option<NodeId>,
+    pub output_node: Option<NodeId>,
+}
+
+impl GroupInterface {
+    /// Derive the interface from the `GroupInput`/`GroupOutput` nodes of a group graph
+    pub fn from_graph(group: GroupId, graph: &Graph) -> Result<Self, GroupError> {
+        let mut input_node = None;
+        let mut output_node = None;
+
+        for node in sorted_nodes(graph) {
+            let slot = match node.kind {
+                NodeKind::GroupInput => &mut input_node,
+                NodeKind::GroupOutput => &mut output_node,
+                _ => continue,
+            };
+            if slot.is_some() {
+                return Err(GroupError::DuplicateInterfaceNode {
+                    group,
+                    kind: node.kind.clone(),
+                });
+            }
+            *slot = Some(node);
+        }
+
+        let signature = |sockets: &[SocketId]| -> Vec<(String, ValueType)> {
+            sockets
+                .iter()
+                .filter_map(|id| graph.socket(*id))
+                .map(|socket| (socket.name.clone(), socket.value_type))
+                .collect()
+        };
+
+        Ok(Self {
+            inputs: input_node
+                .map(|n| signature(&n.outputs))
+                .unwrap_or_default(),
+            outputs: output_node
+                .map(|n| signature(&n.inputs))
+                .unwrap_or_default(),
+            input_node: input_node.map(|n| n.id),
+            output_node: output_node.map(|n| n.id),
+        })
+    }
+
+    fn matches_node(&self, graph: &Graph, node: &Node) -> bool {
+        let types = |sockets: &[SocketId]| -> Vec<ValueType> {
+            sockets
+                .iter()
+                .filter_map(|id| graph.socket(*id))
+                .map(|socket| socket.value_type)
+                .collect()
+        };
+
+        let expected_inputs: Vec<ValueType> = self.inputs.iter().map(|(_, ty)| *ty).collect();
+        let expected_outputs: Vec<ValueType> = self.outputs.iter().map(|(_, ty)| *ty).collect();
+
+        types(&node.inputs) == expected_inputs && types(&node.outputs) == expected_outputs
+    }
+}
+
+/// Storage for group definitions referenced by `NodeKind::Group`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GroupLibrary {
+    groups: HashMap<GroupId, GroupDef>,
+    next_group_id: u64,
+}
+
+impl GroupLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_group(&mut self, name: impl Into<String>, graph: Graph) -> GroupId {
+        let id = GroupId(self.next_group_id);
+        self.next_group_id += 1;
+        self.groups.insert(
+            id,
+            GroupDef {
+                name: name.into(),
+                graph,
+            },
+        );
+        id
+    }
+
+    pub fn group(&self, id: GroupId) -> Option<&GroupDef> {
+        self.groups.get(&id)
+    }
+
+    pub fn group_mut(&mut self, id: GroupId) -> Option<&mut GroupDef> {
+        self.groups.get_mut(&id)
+    }
+
+    pub fn interface(&self, id: GroupId) -> Result<GroupInterface, GroupError> {
+        let def = self
+            .group(id)
+            .ok_or(GroupError::GroupNotFound { group: id })?;
+        GroupInterface::from_graph(id, &def.graph)
+    }
+
+    /// Add a group node to `graph` with sockets matching the group interface
+    pub fn instantiate(
+        &self,
+        graph: &mut Graph,
+        id: GroupId,
+        position: Vec2,
+    ) -> Result<NodeId, GroupError> {
+        let interface = self.interface(id)?;
+        Ok(graph.add_node(
+            NodeKind::Group(id),
+            position,
+            interface.inputs,
+            interface.outputs,
+        ))
+    }
+}
+
+/// Where a node of an inlined graph came from
+///
+/// `group_path` lists the group nodes that were entered, outermost first;
+/// each id lives in the graph of the previous group. `node` lives in the
+/// innermost graph. An empty path means the node is visible in the
+/// top-level graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeOrigin {
+    pub group_path: Vec<NodeId>,
+    pub node: NodeId,
+}
+
+/// Where a socket of an inlined graph came from, see `NodeOrigin`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketOrigin {
+    pub group_path: Vec<NodeId>,
+    pub socket: SocketId,
+}
+
+/// Result of `inline_groups`: a group-free graph plus origin tracking
+#[derive(Debug, Clone)]
+pub struct InlinedGraph {
+    pub graph: Graph,
+    node_origins: HashMap<NodeId, NodeOrigin>,
+    socket_origins: HashMap<SocketId, SocketOrigin>,
+    group_outputs: HashMap<NodeId, NodeId>,
+}
+
+impl InlinedGraph {
+    /// Origin of a node created by inlining; `None` for untouched top-level nodes
+    pub fn node_origin(&self, node: NodeId) -> Option<&NodeOrigin> {
+        self.node_origins.get(&node)
+    }
+
+    /// Origin of a socket created by inlining; `None` for untouched top-level sockets
+    pub fn socket_origin(&self, socket: SocketId) -> Option<&SocketOrigin> {
+        self.socket_origins.get(&socket)
+    }
+
+    /// Map a top-level root to its node in the inlined graph
+    ///
+    /// Group nodes are replaced by their `GroupOutput` boundary node.
+    pub fn map_root(&self, root: NodeId) -> NodeId {
+        self.group_outputs.get(&root).copied().unwrap_or(root)
+    }
+
+    pub fn map_roots(&self, roots: &[NodeId]) -> Vec<NodeId> {
+        roots.iter().map(|&root| self.map_root(root)).collect()
+    }
+
+    /// Re-target a diagnostic from the inlined graph to the top-level graph
+    ///
+    /// Diagnostics on nodes or sockets inside a group are attached to the
+    /// outermost group node, with the path into the group added to the message.
+    pub fn remap_diagnostic(&self, diagnostic: Diagnostic) -> Diagnostic {
+        let mut diagnostic = diagnostic;
+        // Links are renumbered by inlining and have no origin to map back to
+        diagnostic.link = None;
+
+        if let Some(origin) = diagnostic.socket.and_then(|s| self.socket_origin(s)) {
+            if origin.group_path.is_empty() {
+                diagnostic.socket = Some(origin.socket);
+            } else {
+                diagnostic.socket = None;
+                diagnostic.node = Some(origin.group_path[0]);
+                diagnostic.message = format!(
+                    "{} (in {})",
+                    diagnostic.message,
+                    format_group_path(&origin.group_path, &format!("socket {}", origin.socket.0))
+                );
+            }
+            return diagnostic;
+        }
+
+        if let Some(origin) = diagnostic.node.and_then(|n| self.node_origin(n)) {
+            if origin.group_path.is_empty() {
+                diagnostic.node = Some(origin.node);
+            } else {
+                diagnostic.node = Some(origin.group_path[0]);
+                diagnostic.message = format!(
+                    "{} (in {})",
+                    diagnostic.message,
+                    format_group_path(&origin.group_path, &format!("node {}", origin.node.0))
+                );
+            }
+        }
+
+        diagnostic
+    }
+
+    pub fn remap_diagnostics(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics
+            .into_iter()
+            .map(|d| self.remap_diagnostic(d))
+            .collect()
+    }
+}
+
+fn format_group_path(path: &[NodeId], leaf: &str) -> String {
+    let mut parts: Vec<String> = path
+        .iter()
+        .map(|id| format!("group node {}", id.0))
+        .collect();
+    parts.push(leaf.to_string());
+    parts.join(" > ")
+}
+
+/// Splice every group instance of `graph` into a single flat graph
+///
+/// Top-level node and socket ids are preserved; group nodes are removed and
+/// replaced by `GroupInput` boundary node, the group's contents, and a
+/// `GroupOutput` boundary node. Boundary nodes forward input `i` to output `i`.
+pub fn inline_groups(graph: &Graph, library: &GroupLibrary) -> Result<InlinedGraph, GroupError> {
+    let mut stack = Vec::new();
+    inline_recursive(graph, library, &mut stack)
+}
+
+fn inline_recursive(
+    graph: &Graph,
+    library: &GroupLibrary,
+    stack: &mut Vec<GroupId>,
+) -> Result<InlinedGraph, GroupError> {
+    let mut inlined = InlinedGraph {
+        graph: graph.clone(),
+        node_origins: HashMap::new(),
+        socket_origins: HashMap::new(),
+        group_outputs: HashMap::new(),
+    };
+
+    let group_nodes: Vec<&Node> = sorted_nodes(graph)
+        .into_iter()
+        .filter(|node| matches!(node.kind, NodeKind::Group(_)))
+        .collect();
+
+    for group_node in group_nodes {
+        let NodeKind::Group(group_id) = group_node.kind else {
+            continue;
+        };
+
+        if stack.contains(&group_id) {
+            let mut path = stack.clone();
+            path.push(group_id);
+            return Err(GroupError::RecursiveGroup {
+                group: group_id,
+                path,
+            });
+        }
+
+        let def = library
+            .group(group_id)
+            .ok_or(GroupError::GroupNotFound { group: group_id })?;
+        let interface = GroupInterface::from_graph(group_id, &def.graph)?;
+
+        if !interface.matches_node(graph, group_node) {
+            return Err(GroupError::InterfaceMismatch {
+                node: group_node.id,
+                group: group_id,
+            });
+        }
+
+        stack.push(group_id);
+        let inner = inline_recursive(&def.graph, library, stack)?;
+        stack.pop();
+
+        splice_group(&mut inlined, group_node, &inner, &interface)?;
+    }
+
+    Ok(inlined)
+}
+
+fn splice_group(
+    target: &mut InlinedGraph,
+    group_node: &Node,
+    inner: &InlinedGraph,
+    interface: &GroupInterface,
+) -> Result<(), GroupError> {
+    let graph = &mut target.graph;
+
+    let upstream: Vec<Option<SocketId>> = group_node
+        .inputs
+        .iter()
+        .map(|&socket| graph.links_into(socket).next().map(|link| link.from))
+        .collect();
+    let downstream: Vec<Vec<SocketId>> = group_node
+        .outputs
+        .iter()
+        .map(|&socket| {
+            let mut targets: Vec<SocketId> = graph.links_out_of(socket).map(|l| l.to).collect();
+            targets.sort_by_key(|s| s.0);
+            targets
+        })
+        .collect();
+
+    let group_input_defs: Vec<InputDef> = group_node
+        .inputs
+        .iter()
+        .map(|&socket| input_def_from_socket(graph, socket))
+        .collect();
+    let group_output_defs = output_defs_from_sockets(graph, &group_node.outputs);
+
+    graph.remove_node(group_node.id);
+
+    let here = |origin_path: &[NodeId]| -> Vec<NodeId> {
+        let mut path = vec![group_node.id];
+        path.extend_from_slice(origin_path);
+        path
+    };
+
+    // Boundary node standing in for the group's inputs
+    let boundary_in = graph.add_node_with_config(
+        NodeKind::GroupInput,
+        group_node.position,
+        group_input_defs,
+        interface.inputs.clone(),
+    );
+    let boundary_in_node = graph.node(boundary_in).expect("node just added").clone();
+
+    for (index, &socket) in boundary_in_node.inputs.iter().enumerate() {
+        target.socket_origins.insert(
+            socket,
+            SocketOrigin {
+                group_path: Vec::new(),
+                socket: group_node.inputs[index],
+            },
+        );
+        if let Some(from) = upstream[index] {
+            graph.connect(from, socket)?;
+        }
+    }
+
+    let mut socket_map: HashMap<SocketId, SocketId> = HashMap::new();
+
+    if let Some(input_node) = interface.input_node.and_then(|id| inner.graph.node(id)) {
+        for (&inner_socket, &socket) in input_node.outputs.iter().zip(&boundary_in_node.outputs) {
+            socket_map.insert(inner_socket, socket);
+            target.socket_origins.insert(
+                socket,
+                SocketOrigin {
+                    group_path: vec![group_node.id],
+                    socket: inner_socket,
+                },
+            );
+        }
+    }
+
+    // Group contents, excluding the interface nodes
+    for node in sorted_nodes(&inner.graph) {
+        if Some(node.id) == interface.input_node || Some(node.id) == interface.output_node {
+            continue;
+        }
+
+        let input_defs = node
+            .inputs
+            .iter()
+            .map(|&socket| input_def_from_socket(&inner.graph, socket))
+            .collect();
+        let output_defs = output_defs_from_sockets(&inner.graph, &node.outputs);
+
+        let new_id =
+            graph.add_node_with_config(node.kind.clone(), node.position, input_defs, output_defs);
+        for (name, value) in &node.properties {
+            graph
+                .set_node_property(new_id, name.clone(), value.clone())
+                .expect("node just added");
+        }
+        let new_node = graph.node(new_id).expect("node just added");
+
+        let node_origin = match inner.node_origin(node.id) {
+            Some(origin) => NodeOrigin {
+                group_path: here(&origin.group_path),
+                node: origin.node,
+            },
+            None => NodeOrigin {
+                group_path: vec![group_node.id],
+                node: node.id,
+            },
+        };
+        target.node_origins.insert(new_id, node_origin);
+
+        let old_sockets = node.inputs.iter().chain(node.outputs.iter());
+        let new_sockets = new_node.inputs.iter().chain(new_node.outputs.iter());
+        for (&old, &new) in old_sockets.zip(new_sockets) {
+            socket_map.insert(old, new);
+            let socket_origin = match inner.socket_origin(old) {
+                Some(origin) => SocketOrigin {
+                    group_path: here(&origin.group_path),
+                    socket: origin.socket,
+                },
+                None => SocketOrigin {
+                    group_path: vec![group_node.id],
+                    socket: old,
+                },
+            };
+            target.socket_origins.insert(new, socket_origin);
+        }
+    }
+
+    // Boundary node standing in for the group's outputs
+    let (output_input_defs, output_inner_sockets) =
+        match interface.output_node.and_then(|id| inner.graph.node(id)) {
+            Some(output_node) => (
+                output_node
+                    .inputs
+                    .iter()
+                    .map(|&socket| input_def_from_socket(&inner.graph, socket))
+                    .collect(),
+                output_node.inputs.clone(),
+            ),
+            None => (Vec::new(), Vec::new()),
+        };
+
+    let boundary_out = graph.add_node_with_config(
+        NodeKind::GroupOutput,
+        group_node.position,
+        output_input_defs,
+        group_output_defs,
+    );
+    let boundary_out_node = graph.node(boundary_out).expect("node just added").clone();
+
+    for (&inner_socket, &socket) in output_inner_sockets.iter().zip(&boundary_out_node.inputs) {
+        socket_map.insert(inner_socket, socket);
+        target.socket_origins.insert(
+            socket,
+            SocketOrigin {
+                group_path: vec![group_node.id],
+                socket: inner_socket,
+            },
+        );
+    }
+
+    for (index, &socket) in boundary_out_node.outputs.iter().enumerate() {
+        target.socket_origins.insert(
+            socket,
+            SocketOrigin {
+                group_path: Vec::new(),
+                socket: group_node.outputs[index],
+            },
+        );
+        for &to in &downstream[index] {
+            graph.connect(socket, to)?;
+        }
+    }
+
+    let standin = NodeOrigin {
+        group_path: Vec::new(),
+        node: group_node.id,
+    };
+    target.node_origins.insert(boundary_in, standin.clone());
+    target.node_origins.insert(boundary_out, standin);
+    target.group_outputs.insert(group_node.id, boundary_out);
+
+    // Internal links, rewired onto the spliced sockets
+    let mut links: Vec<_> = inner.graph.links().collect();
+    links.sort_by_key(|link| link.id.0);
+    for link in links {
+        if let (Some(&from), Some(&to)) = (socket_map.get(&link.from), socket_map.get(&link.to)) {
+            graph.connect(from, to)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn input_def_from_socket(graph: &Graph, socket: SocketId) -> InputDef {
+    let socket = graph.socket(socket).expect("socket from node must exist");
+    InputDef {
+        name: socket.name.clone(),
+        value_type: socket.value_type,
+        config: socket.input_config.clone().unwrap_or_default(),
+    }
+}
+
+fn output_defs_from_sockets(graph: &Graph, sockets: &[SocketId]) -> Vec<(String, ValueType)> {
+    sockets
+        .iter()
+        .map(|&id| {
+            let socket = graph.socket(id).expect("socket from node must exist");
+            (socket.name.clone(), socket.value_type)
+        })
+        .collect()
+}
+
+fn sorted_nodes(graph: &Graph) -> Vec<&Node> {
+    let mut nodes: Vec<&Node> = graph.nodes().collect();
+    nodes.sort_by_key(|node| node.id.0);
+    nodes
+}
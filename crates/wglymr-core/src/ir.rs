@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::ValueType;
+use crate::{NodeId, ValueType};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ValueId(pub u32);
@@ -14,8 +14,24 @@ pub enum IrType {
     Color,
     Bool,
     Int,
+    Mat2,
+    Mat3,
+    Mat4,
 }
 
+impl IrType {
+    /// Matrix dimension, None for non-matrix types
+    pub fn matrix_size(&self) -> Option<u8> {
+        match self {
+            IrType::Mat2 => Some(2),
+            IrType::Mat3 => Some(3),
+            IrType::Mat4 => Some(4),
+            _ => None,
+        }
+    }
+}
+
+/// Matrix literals are stored column-major
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Literal {
     Float(f32),
@@ -24,6 +40,9 @@ pub enum Literal {
     Vec4([f32; 4]),
     Bool(bool),
     Int(i32),
+    Mat2([f32; 4]),
+    Mat3([f32; 9]),
+    Mat4([f32; 16]),
 }
 
 impl Literal {
@@ -35,10 +54,32 @@ impl Literal {
             Literal::Vec4(_) => ValueType::Vec4,
             Literal::Bool(_) => ValueType::Bool,
             Literal::Int(_) => ValueType::Int,
+            Literal::Mat2(_) => ValueType::Mat2,
+            Literal::Mat3(_) => ValueType::Mat3,
+            Literal::Mat4(_) => ValueType::Mat4,
+        }
+    }
+
+    /// Identity matrix literal for a matrix type
+    pub fn identity(value_type: ValueType) -> Option<Literal> {
+        match value_type {
+            ValueType::Mat2 => Some(Literal::Mat2(identity_columns())),
+            ValueType::Mat3 => Some(Literal::Mat3(identity_columns())),
+            ValueType::Mat4 => Some(Literal::Mat4(identity_columns())),
+            _ => None,
         }
     }
 }
 
+fn identity_columns<const N: usize>() -> [f32; N] {
+    let size = (N as f64).sqrt() as usize;
+    let mut values = [0.0; N];
+    for i in 0..size {
+        values[i * size + i] = 1.0;
+    }
+    values
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BinaryOp {
     Add,
@@ -66,9 +107,119 @@ pub enum IrInst {
         from_ty: IrType,
         to_ty: IrType,
     },
+
+    /// Value supplied by the caller, emitted as a parameter of `main`
+    Input {
+        name: String,
+        ty: IrType,
+    },
+
+    /// Call of a WGSL built-in or a helper function from `IrProgram::helpers`
+    Call {
+        function: String,
+        args: Vec<ValueId>,
+        ty: IrType,
+    },
 }
 
+impl IrInst {
+    /// Type of the value produced by this instruction
+    pub fn result_type(&self) -> IrType {
+        match self {
+            IrInst::Constant { ty, .. } => *ty,
+            IrInst::Binary { ty, .. } => *ty,
+            IrInst::Convert { to_ty, .. } => *to_ty,
+            IrInst::Call { ty, .. } => *ty,
+            IrInst::Input { ty, .. } => *ty,
+        }
+    }
+
+    /// Values read by this instruction
+    pub fn operands(&self) -> Vec<ValueId> {
+        match self {
+            IrInst::Binary { lhs, rhs, .. } => vec![*lhs, *rhs],
+            IrInst::Convert { from, .. } => vec![*from],
+            IrInst::Call { args, .. } => args.clone(),
+            IrInst::Constant { .. } | IrInst::Input { .. } => vec![],
+        }
+    }
+
+    /// Rewrite every value read by this instruction
+    pub fn map_operands(self, map: impl Fn(ValueId) -> ValueId) -> IrInst {
+        match self {
+            IrInst::Binary { op, lhs, rhs, ty } => IrInst::Binary {
+                op,
+                lhs: map(lhs),
+                rhs: map(rhs),
+                ty,
+            },
+            IrInst::Convert {
+                from,
+                from_ty,
+                to_ty,
+            } => IrInst::Convert {
+                from: map(from),
+                from_ty,
+                to_ty,
+            },
+            IrInst::Call { function, args, ty } => IrInst::Call {
+                function,
+                args: args.into_iter().map(map).collect(),
+                ty,
+            },
+            inst @ (IrInst::Constant { .. } | IrInst::Input { .. }) => inst,
+        }
+    }
+}
+
+/// WGSL function emitted ahead of the entry point
 #[derive(Debug, Clone, PartialEq)]
+pub struct IrHelper {
+    pub name: String,
+    pub source: String,
+}
+
+/// Named result of a program
+#[derive(Debug, Clone, PartialEq)]
+pub struct IrOutput {
+    pub name: String,
+    pub value: ValueId,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct IrProgram {
     pub instructions: Vec<IrInst>,
+    pub helpers: Vec<IrHelper>,
+    /// Program results in declaration order
+    pub outputs: Vec<IrOutput>,
+    /// Node each instruction was lowered from, parallel to `instructions`
+    pub origins: Vec<Option<NodeId>>,
+}
+
+impl IrProgram {
+    /// Add a helper unless one with the same name is already present
+    pub fn add_helper(&mut self, helper: IrHelper) {
+        if !self.helpers.iter().any(|h| h.name == helper.name) {
+            self.helpers.push(helper);
+        }
+    }
+
+    pub fn add_output(&mut self, name: impl Into<String>, value: ValueId) {
+        self.outputs.push(IrOutput {
+            name: name.into(),
+            value,
+        });
+    }
+
+    /// Node the instruction producing `value` was lowered from
+    pub fn origin(&self, value: ValueId) -> Option<NodeId> {
+        self.origins.get(value.0 as usize).copied().flatten()
+    }
+
+    /// Type of a value, if it is defined in this program
+    pub fn value_type(&self, value: ValueId) -> Option<IrType> {
+        self.instructions
+            .get(value.0 as usize)
+            .map(IrInst::result_type)
+    }
 }
@@ -1,4 +1,4 @@
-use crate::{IrInst, IrProgram, IrType, ValueId};
+use crate::{BinaryOp, IrInst, IrOutput, IrProgram, IrType, ValueId};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -19,11 +19,39 @@ fn is_valid_conversion(from: IrType, to: IrType) -> bool {
         // Vec3 to Color (append alpha = 1.0)
         (IrType::Vec3, IrType::Color) => true,
 
+        // Matrix resize (truncate, or embed in identity)
+        (from, to) if from != to => from.matrix_size().is_some() && to.matrix_size().is_some(),
+
         // All other conversions are not allowed
         _ => false,
     }
 }
 
+/// Whether `lhs * rhs` is a matrix product yielding `ty` without conversions
+pub(crate) fn is_matrix_product(op: BinaryOp, lhs: IrType, rhs: IrType, ty: IrType) -> bool {
+    if op != BinaryOp::Mul || (lhs.matrix_size().is_none() && rhs.matrix_size().is_none()) {
+        return false;
+    }
+
+    let vector_size = |vector: IrType| match vector {
+        IrType::Vec2 => Some(2),
+        IrType::Vec3 => Some(3),
+        IrType::Vec4 => Some(4),
+        _ => None,
+    };
+
+    match (lhs.matrix_size(), rhs.matrix_size()) {
+        (Some(_), Some(_)) => lhs == rhs && ty == lhs,
+        (Some(size), None) => {
+            (rhs == IrType::Float && ty == lhs) || (vector_size(rhs) == Some(size) && ty == rhs)
+        }
+        (None, Some(size)) => {
+            (lhs == IrType::Float && ty == rhs) || (vector_size(lhs) == Some(size) && ty == lhs)
+        }
+        (None, None) => false,
+    }
+}
+
 /// Insert explicit conversion instructions where types are incompatible but convertible
 ///
 /// This pass walks the IR and detects type mismatches at use sites.
@@ -33,13 +61,19 @@ fn is_valid_conversion(from: IrType, to: IrType) -> bool {
 /// Supported conversions:
 /// - Float => Vec2/Vec3/Vec4/Color (splat)
 /// - Vec3 => Color (append alpha = 1.0)
+/// - MatN => MatM (truncate, or embed in the identity)
 ///
-/// All other type mismatches result in an error.
+/// Matrix products (`MatN * VecN`, `MatN * Float`, ...) keep their operand
+/// types. All other type mismatches result in an error.
 pub fn insert_conversions(ir: IrProgram) -> Result<IrProgram, ConversionError> {
     let mut new_instructions = Vec::new();
     let mut value_types: Vec<IrType> = Vec::new();
+    // Inserted conversions shift later values; map old ids to new ones
+    let mut remap: Vec<ValueId> = Vec::new();
 
-    for inst in ir.instructions {
+    let mut origins = Vec::new();
+
+    for (index, inst) in ir.instructions.into_iter().enumerate() {
         match inst {
             IrInst::Constant { value, ty } => {
                 value_types.push(ty);
@@ -47,50 +81,20 @@ pub fn insert_conversions(ir: IrProgram) -> Result<IrProgram, ConversionError> {
             }
 
             IrInst::Binary { op, lhs, rhs, ty } => {
+                let lhs = remap[lhs.0 as usize];
+                let rhs = remap[rhs.0 as usize];
+
                 // Check if operands need conversion
                 let lhs_ty = value_types[lhs.0 as usize];
                 let rhs_ty = value_types[rhs.0 as usize];
 
-                // Convert lhs if needed
-                let lhs_converted = if lhs_ty != ty {
-                    if is_valid_conversion(lhs_ty, ty) {
-                        let converted_id = ValueId(new_instructions.len() as u32);
-                        new_instructions.push(IrInst::Convert {
-                            from: lhs,
-                            from_ty: lhs_ty,
-                            to_ty: ty,
-                        });
-                        value_types.push(ty);
-                        converted_id
-                    } else {
-                        return Err(ConversionError::NoConversion {
-                            from: lhs_ty,
-                            to: ty,
-                        });
-                    }
-                } else {
-                    lhs
-                };
-
-                // Convert rhs if needed
-                let rhs_converted = if rhs_ty != ty {
-                    if is_valid_conversion(rhs_ty, ty) {
-                        let converted_id = ValueId(new_instructions.len() as u32);
-                        new_instructions.push(IrInst::Convert {
-                            from: rhs,
-                            from_ty: rhs_ty,
-                            to_ty: ty,
-                        });
-                        value_types.push(ty);
-                        converted_id
-                    } else {
-                        return Err(ConversionError::NoConversion {
-                            from: rhs_ty,
-                            to: ty,
-                        });
-                    }
+                let (lhs_converted, rhs_converted) = if is_matrix_product(op, lhs_ty, rhs_ty, ty) {
+                    (lhs, rhs)
                 } else {
-                    rhs
+                    (
+                        convert_operand(&mut new_instructions, &mut value_types, lhs, lhs_ty, ty)?,
+                        convert_operand(&mut new_instructions, &mut value_types, rhs, rhs_ty, ty)?,
+                    )
                 };
 
                 value_types.push(ty);
@@ -117,15 +121,73 @@ pub fn insert_conversions(ir: IrProgram) -> Result<IrProgram, ConversionError> {
 
                 value_types.push(to_ty);
                 new_instructions.push(IrInst::Convert {
-                    from,
+                    from: remap[from.0 as usize],
                     from_ty,
                     to_ty,
                 });
             }
+
+            IrInst::Input { name, ty } => {
+                value_types.push(ty);
+                new_instructions.push(IrInst::Input { name, ty });
+            }
+
+            IrInst::Call { function, args, ty } => {
+                // Helper signatures come from socket types, arguments already match
+                let args = args.iter().map(|arg| remap[arg.0 as usize]).collect();
+                value_types.push(ty);
+                new_instructions.push(IrInst::Call { function, args, ty });
+            }
         }
+
+        remap.push(ValueId((new_instructions.len() - 1) as u32));
+        // Inserted conversions belong to the node that needed them
+        let origin = ir.origins.get(index).copied().flatten();
+        origins.resize(new_instructions.len(), origin);
     }
 
+    let outputs = ir
+        .outputs
+        .into_iter()
+        .map(|output| IrOutput {
+            value: remap[output.value.0 as usize],
+            ..output
+        })
+        .collect();
+
     Ok(IrProgram {
         instructions: new_instructions,
+        helpers: ir.helpers,
+        outputs,
+        origins,
     })
 }
+
+/// Convert `value` to `ty`, returning the id to use in its place
+fn convert_operand(
+    instructions: &mut Vec<IrInst>,
+    value_types: &mut Vec<IrType>,
+    value: ValueId,
+    value_ty: IrType,
+    ty: IrType,
+) -> Result<ValueId, ConversionError> {
+    if value_ty == ty {
+        return Ok(value);
+    }
+
+    if !is_valid_conversion(value_ty, ty) {
+        return Err(ConversionError::NoConversion {
+            from: value_ty,
+            to: ty,
+        });
+    }
+
+    let converted_id = ValueId(instructions.len() as u32);
+    instructions.push(IrInst::Convert {
+        from: value,
+        from_ty: value_ty,
+        to_ty: ty,
+    });
+    value_types.push(ty);
+    Ok(converted_id)
+}
@@ -8,6 +8,9 @@ pub enum IrValidationError {
 
     #[error("instruction {0} references future value v{1}")]
     FutureValueRef(usize, u32),
+
+    #[error("output {0:?} references invalid value v{1}")]
+    InvalidOutputRef(String, u32),
 }
 
 pub fn pretty_print(ir: &IrProgram) -> String {
@@ -41,19 +44,41 @@ pub fn pretty_print(ir: &IrProgram) -> String {
                     value_id, to_str, from.0, from_str, to_str
                 )
             }
+            IrInst::Input { name, ty } => {
+                format!("{}: {} = input {}\n", value_id, format_type(*ty), name)
+            }
+            IrInst::Call { function, args, ty } => {
+                let type_str = format_type(*ty);
+                let args_str: Vec<String> = args.iter().map(|a| format!("v{}", a.0)).collect();
+                format!(
+                    "{}: {} = call {}({})\n",
+                    value_id,
+                    type_str,
+                    function,
+                    args_str.join(", ")
+                )
+            }
         };
         output.push_str(&line);
     }
 
+    for ir_output in &ir.outputs {
+        output.push_str(&format!(
+            "output {:?} = v{}\n",
+            ir_output.name, ir_output.value.0
+        ));
+    }
+
     output
 }
 
 pub fn validate_ir(ir: &IrProgram) -> Result<(), IrValidationError> {
     for (idx, inst) in ir.instructions.iter().enumerate() {
         let referenced_values = match inst {
-            IrInst::Constant { .. } => vec![],
+            IrInst::Constant { .. } | IrInst::Input { .. } => vec![],
             IrInst::Binary { lhs, rhs, .. } => vec![*lhs, *rhs],
             IrInst::Convert { from, .. } => vec![*from],
+            IrInst::Call { args, .. } => args.clone(),
         };
 
         for value_id in referenced_values {
@@ -69,6 +94,15 @@ pub fn validate_ir(ir: &IrProgram) -> Result<(), IrValidationError> {
         }
     }
 
+    for output in &ir.outputs {
+        if output.value.0 as usize >= ir.instructions.len() {
+            return Err(IrValidationError::InvalidOutputRef(
+                output.name.clone(),
+                output.value.0,
+            ));
+        }
+    }
+
     Ok(())
 }
 
@@ -81,6 +115,9 @@ fn format_type(ty: IrType) -> &'static str {
         IrType::Color => "color",
         IrType::Bool => "bool",
         IrType::Int => "i32",
+        IrType::Mat2 => "mat2",
+        IrType::Mat3 => "mat3",
+        IrType::Mat4 => "mat4",
     }
 }
 
@@ -92,9 +129,17 @@ fn format_literal(lit: &Literal) -> String {
         Literal::Vec4(v) => format!("vec4({}, {}, {}, {})", v[0], v[1], v[2], v[3]),
         Literal::Bool(b) => format!("{}", b),
         Literal::Int(i) => format!("{}", i),
+        Literal::Mat2(m) => format_matrix("mat2", m),
+        Literal::Mat3(m) => format_matrix("mat3", m),
+        Literal::Mat4(m) => format_matrix("mat4", m),
     }
 }
 
+fn format_matrix(name: &str, values: &[f32]) -> String {
+    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    format!("{}({})", name, values.join(", "))
+}
+
 fn format_binary_op(op: BinaryOp) -> &'static str {
     match op {
         BinaryOp::Add => "add",
@@ -128,6 +173,7 @@ mod tests {
                     ty: IrType::Float,
                 },
             ],
+            ..Default::default()
         };
 
         let output = pretty_print(&ir);
@@ -154,6 +200,7 @@ mod tests {
                     ty: IrType::Float,
                 },
             ],
+            ..Default::default()
         };
 
         assert!(validate_ir(&ir).is_ok());
@@ -174,6 +221,7 @@ mod tests {
                     ty: IrType::Float,
                 },
             ],
+            ..Default::default()
         };
 
         let result = validate_ir(&ir);
@@ -203,6 +251,7 @@ mod tests {
                     ty: IrType::Float,
                 },
             ],
+            ..Default::default()
         };
 
         let result = validate_ir(&ir);
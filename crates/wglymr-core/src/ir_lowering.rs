@@ -6,9 +6,10 @@
 use std::collections::HashMap;
 use thiserror::Error;
 
+use crate::registry::zero_literal;
 use crate::{
-    BinaryOp, GraphView, IrInst, IrProgram, IrType, Literal, MathOp, NodeKind, SocketId, TypeMap,
-    ValueId, ValueType,
+    generate_code_functions, CodeError, GraphView, IrHelper, IrInst, IrOutput, IrProgram, IrType,
+    Literal, LoweringRule, NodeId, NodeKind, NodeRegistry, SocketId, TypeMap, ValueId, ValueType,
 };
 
 #[derive(Error, Debug)]
@@ -19,16 +20,26 @@ pub enum IrLoweringError {
     #[error("missing type for socket {0:?}")]
     MissingType(SocketId),
 
+    #[error("no value computed for output socket {0:?}")]
+    MissingOutput(SocketId),
+
     #[error("unsupported node kind")]
     UnsupportedNode,
 
     #[error("optional input socket {0:?} missing default value")]
     OptionalInputMissingDefault(SocketId),
+
+    #[error("unknown node definition {0:?}")]
+    UnknownNodeDefinition(String),
+
+    #[error("invalid code in node {node:?}: {error}")]
+    InvalidCode { node: NodeId, error: CodeError },
 }
 
 struct LoweringContext<'a> {
     view: &'a GraphView<'a>,
     instructions: Vec<IrInst>,
+    helpers: Vec<IrHelper>,
     socket_to_value: HashMap<SocketId, ValueId>,
     next_value_id: u32,
 }
@@ -38,6 +49,7 @@ impl<'a> LoweringContext<'a> {
         Self {
             view,
             instructions: Vec::new(),
+            helpers: Vec::new(),
             socket_to_value: HashMap::new(),
             next_value_id: 0,
         }
@@ -49,6 +61,12 @@ impl<'a> LoweringContext<'a> {
         id
     }
 
+    fn add_helper(&mut self, helper: IrHelper) {
+        if !self.helpers.iter().any(|h| h.name == helper.name) {
+            self.helpers.push(helper);
+        }
+    }
+
     fn emit_constant(&mut self, literal: Literal, ir_type: IrType) -> ValueId {
         let value_id = self.alloc_value_id();
         self.instructions.push(IrInst::Constant {
@@ -88,83 +106,366 @@ impl<'a> LoweringContext<'a> {
     }
 }
 
+/// Lower a typed graph view to IR using the built-in node registry
 pub fn lower_to_ir(view: &GraphView, types: &TypeMap) -> Result<IrProgram, IrLoweringError> {
+    lower_to_ir_with_registry(view, types, NodeRegistry::builtin())
+}
+
+/// Lower a typed graph view to IR
+///
+/// Each reachable node is lowered according to the lowering rule of its
+/// definition in `registry`.
+pub fn lower_to_ir_with_registry(
+    view: &GraphView,
+    types: &TypeMap,
+    registry: &NodeRegistry,
+) -> Result<IrProgram, IrLoweringError> {
+    lower_view(view, types, registry).map(|(program, _)| program)
+}
+
+/// Lower a view, also returning the value computed for each socket
+pub(crate) fn lower_view(
+    view: &GraphView,
+    types: &TypeMap,
+    registry: &NodeRegistry,
+) -> Result<(IrProgram, HashMap<SocketId, ValueId>), IrLoweringError> {
     let mut ctx = LoweringContext::new(view);
+    let mut origins = Vec::new();
+    let mut current_node = None;
 
     for &node_id in &view.topo_order {
-        if !view.reachable.contains(&node_id) {
-            continue;
+        // Everything emitted since the previous node belongs to that node
+        origins.resize(ctx.instructions.len(), current_node);
+        current_node = Some(node_id);
+
+        lower_node(&mut ctx, types, registry, node_id)?;
+    }
+
+    origins.resize(ctx.instructions.len(), current_node);
+
+    let outputs = root_outputs(view, &ctx.socket_to_value)?;
+
+    let program = IrProgram {
+        instructions: ctx.instructions,
+        helpers: ctx.helpers,
+        outputs,
+        origins,
+    };
+    Ok((program, ctx.socket_to_value))
+}
+
+/// Lower one node of `view` into `ctx`
+fn lower_node(
+    ctx: &mut LoweringContext,
+    types: &TypeMap,
+    registry: &NodeRegistry,
+    node_id: NodeId,
+) -> Result<(), IrLoweringError> {
+    let node = ctx
+        .view
+        .graph
+        .node(node_id)
+        .expect("node in topo_order must exist");
+
+    match &node.kind {
+        NodeKind::GroupInput | NodeKind::GroupOutput if node.inputs.len() == node.outputs.len() => {
+            // Inlined group boundary: forward each input to its output
+            for (&input_socket, &output_socket) in node.inputs.iter().zip(&node.outputs) {
+                let input_value = ctx.resolve_input(input_socket)?;
+                ctx.socket_to_value.insert(output_socket, input_value);
+            }
+            return Ok(());
         }
 
-        let node = view
-            .graph
-            .node(node_id)
-            .expect("node in topo_order must exist");
-
-        match &node.kind {
-            NodeKind::Value(value_type) => {
-                let ir_type = value_type_to_ir_type(*value_type)?;
-                let literal = create_default_literal(*value_type);
-                let value_id = ctx.emit_constant(literal, ir_type);
-
-                if let Some(&output_socket) = node.outputs.first() {
-                    ctx.socket_to_value.insert(output_socket, value_id);
-                }
+        NodeKind::Group(_) | NodeKind::GroupInput | NodeKind::GroupOutput => {
+            return Err(IrLoweringError::UnsupportedNode);
+        }
+
+        NodeKind::Code(body) => {
+            lower_code_node(ctx, types, node_id, body)?;
+            return Ok(());
+        }
+
+        _ => {}
+    }
+
+    let definition = registry.resolve(&node.kind).ok_or_else(|| {
+        IrLoweringError::UnknownNodeDefinition(node.kind.definition_id().unwrap_or_default())
+    })?;
+
+    let inputs = registry.active_input_sockets(node);
+
+    match definition.lowering_for(&node.properties) {
+        LoweringRule::Constant => {
+            let output_socket = node
+                .outputs
+                .first()
+                .ok_or(IrLoweringError::UnsupportedNode)?;
+
+            let output_type = types
+                .get(*output_socket)
+                .ok_or(IrLoweringError::MissingType(*output_socket))?;
+
+            let literal = definition
+                .outputs
+                .first()
+                .and_then(|spec| spec.default.clone())
+                .unwrap_or_else(|| zero_literal(output_type));
+
+            let ir_type = value_type_to_ir_type(output_type)?;
+            let value_id = ctx.emit_constant(literal, ir_type);
+            ctx.socket_to_value.insert(*output_socket, value_id);
+        }
+
+        LoweringRule::Binary(op) => {
+            let mut input_values = Vec::new();
+            for &socket_id in &inputs {
+                let value_id = ctx.resolve_input(socket_id)?;
+                input_values.push(value_id);
+            }
+
+            if input_values.len() != 2 {
+                return Err(IrLoweringError::UnsupportedNode);
             }
 
-            NodeKind::Math(math_op) => {
-                let mut input_values = Vec::new();
-                for &socket_id in &node.inputs {
-                    let value_id = ctx.resolve_input(socket_id)?;
-                    input_values.push(value_id);
-                }
+            let output_socket = node
+                .outputs
+                .first()
+                .ok_or(IrLoweringError::UnsupportedNode)?;
 
-                if input_values.len() != 2 {
-                    return Err(IrLoweringError::UnsupportedNode);
-                }
+            let output_type = types
+                .get(*output_socket)
+                .ok_or(IrLoweringError::MissingType(*output_socket))?;
 
-                let output_socket = node
-                    .outputs
-                    .first()
-                    .ok_or(IrLoweringError::UnsupportedNode)?;
+            let ir_type = value_type_to_ir_type(output_type)?;
 
-                let output_type = types
-                    .get(*output_socket)
-                    .ok_or(IrLoweringError::MissingType(*output_socket))?;
+            let value_id = ctx.alloc_value_id();
 
-                let ir_type = value_type_to_ir_type(output_type)?;
+            ctx.instructions.push(IrInst::Binary {
+                op: *op,
+                lhs: input_values[0],
+                rhs: input_values[1],
+                ty: ir_type,
+            });
 
-                let value_id = ctx.alloc_value_id();
+            ctx.socket_to_value.insert(*output_socket, value_id);
+        }
 
-                ctx.instructions.push(IrInst::Binary {
-                    op: math_op_to_binary_op(math_op.clone()),
-                    lhs: input_values[0],
-                    rhs: input_values[1],
-                    ty: ir_type,
-                });
+        LoweringRule::PassThrough => {
+            if node.outputs.len() > inputs.len() {
+                return Err(IrLoweringError::UnsupportedNode);
+            }
 
-                ctx.socket_to_value.insert(*output_socket, value_id);
+            for (&input_socket, &output_socket) in inputs.iter().zip(&node.outputs) {
+                let input_value = ctx.resolve_input(input_socket)?;
+                ctx.socket_to_value.insert(output_socket, input_value);
             }
+        }
 
-            NodeKind::Generic(_) => {
-                if node.inputs.len() == 1 && node.outputs.len() == 1 {
-                    let input_socket = node.inputs[0];
-                    let output_socket = node.outputs[0];
+        LoweringRule::Input(name) => {
+            let output_socket = node
+                .outputs
+                .first()
+                .ok_or(IrLoweringError::UnsupportedNode)?;
+
+            let output_type = types
+                .get(*output_socket)
+                .ok_or(IrLoweringError::MissingType(*output_socket))?;
+
+            let value_id = ctx.alloc_value_id();
+            ctx.instructions.push(IrInst::Input {
+                name: name.clone(),
+                ty: value_type_to_ir_type(output_type)?,
+            });
+            ctx.socket_to_value.insert(*output_socket, value_id);
+        }
 
-                    let input_value = ctx.resolve_input(input_socket)?;
-                    ctx.socket_to_value.insert(output_socket, input_value);
-                } else {
-                    return Err(IrLoweringError::UnsupportedNode);
-                }
+        LoweringRule::Call { functions, helpers } => {
+            if node.outputs.len() > functions.len() {
+                return Err(IrLoweringError::UnsupportedNode);
             }
+
+            let mut args = Vec::new();
+            for &socket_id in &inputs {
+                args.push(ctx.resolve_input(socket_id)?);
+            }
+
+            for helper in helpers {
+                ctx.add_helper(helper.clone());
+            }
+
+            for (&output_socket, function) in node.outputs.iter().zip(functions) {
+                let output_type = types
+                    .get(output_socket)
+                    .ok_or(IrLoweringError::MissingType(output_socket))?;
+
+                let value_id = ctx.alloc_value_id();
+                ctx.instructions.push(IrInst::Call {
+                    function: function.clone(),
+                    args: args.clone(),
+                    ty: value_type_to_ir_type(output_type)?,
+                });
+                ctx.socket_to_value.insert(output_socket, value_id);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Program outputs: every output socket of every root, in root order
+pub(crate) fn root_outputs(
+    view: &GraphView,
+    socket_to_value: &HashMap<SocketId, ValueId>,
+) -> Result<Vec<IrOutput>, IrLoweringError> {
+    let mut outputs = Vec::new();
+    for &root in &view.roots {
+        let node = view.graph.node(root).expect("root node must exist");
+        for &output_socket in &node.outputs {
+            if outputs
+                .iter()
+                .any(|(socket, _): &(SocketId, IrOutput)| *socket == output_socket)
+            {
+                continue;
+            }
+            let value = *socket_to_value
+                .get(&output_socket)
+                .ok_or(IrLoweringError::MissingOutput(output_socket))?;
+            let socket = view
+                .graph
+                .socket(output_socket)
+                .expect("socket from node must exist");
+            outputs.push((
+                output_socket,
+                IrOutput {
+                    name: socket.name.clone(),
+                    value,
+                },
+            ));
         }
     }
 
-    Ok(IrProgram {
+    Ok(outputs.into_iter().map(|(_, output)| output).collect())
+}
+
+/// Instructions lowered for a single node
+///
+/// Value ids below `upstream_count` are placeholders for the values feeding
+/// the node, see `lower_fragment`. Instruction `i` defines
+/// `ValueId(upstream_count + i)`.
+#[derive(Debug, Clone)]
+pub(crate) struct LoweredFragment {
+    pub upstream_count: u32,
+    pub instructions: Vec<IrInst>,
+    pub helpers: Vec<IrHelper>,
+    /// Value of each output socket, in socket order
+    pub outputs: Vec<ValueId>,
+}
+
+/// Lower one node on its own
+///
+/// `upstream` lists the output sockets linked into the node's inputs; the
+/// value of `upstream[i]` is referenced as `ValueId(i)`.
+pub(crate) fn lower_fragment(
+    view: &GraphView,
+    types: &TypeMap,
+    registry: &NodeRegistry,
+    node_id: NodeId,
+    upstream: &[SocketId],
+) -> Result<LoweredFragment, IrLoweringError> {
+    let mut ctx = LoweringContext::new(view);
+    for &socket in upstream {
+        let placeholder = ctx.alloc_value_id();
+        ctx.socket_to_value.insert(socket, placeholder);
+    }
+
+    lower_node(&mut ctx, types, registry, node_id)?;
+
+    let node = view.graph.node(node_id).expect("lowered node must exist");
+    let outputs = node
+        .outputs
+        .iter()
+        .map(|socket| {
+            ctx.socket_to_value
+                .get(socket)
+                .copied()
+                .ok_or(IrLoweringError::MissingOutput(*socket))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(LoweredFragment {
+        upstream_count: upstream.len() as u32,
         instructions: ctx.instructions,
+        helpers: ctx.helpers,
+        outputs,
     })
 }
 
+/// Lower a code node to a helper function and one call per output
+fn lower_code_node(
+    ctx: &mut LoweringContext,
+    types: &TypeMap,
+    node_id: NodeId,
+    body: &str,
+) -> Result<(), IrLoweringError> {
+    let node = ctx
+        .view
+        .graph
+        .node(node_id)
+        .expect("node in topo_order must exist");
+
+    let mut args = Vec::new();
+    let mut params = Vec::new();
+    for &socket_id in &node.inputs {
+        args.push(ctx.resolve_input(socket_id)?);
+        let socket = ctx
+            .view
+            .graph
+            .socket(socket_id)
+            .expect("socket from node must exist");
+        params.push((
+            socket.name.clone(),
+            value_type_to_ir_type(socket.value_type)?,
+        ));
+    }
+
+    let mut results = Vec::new();
+    for &socket_id in &node.outputs {
+        let socket = ctx
+            .view
+            .graph
+            .socket(socket_id)
+            .expect("socket from node must exist");
+        let output_type = types
+            .get(socket_id)
+            .ok_or(IrLoweringError::MissingType(socket_id))?;
+        results.push((socket.name.clone(), value_type_to_ir_type(output_type)?));
+    }
+
+    let functions = generate_code_functions(node_id, body, &params, &results).map_err(|error| {
+        IrLoweringError::InvalidCode {
+            node: node_id,
+            error,
+        }
+    })?;
+
+    ctx.add_helper(functions.helper);
+
+    for ((&output_socket, (_, ty)), function) in
+        node.outputs.iter().zip(&results).zip(functions.outputs)
+    {
+        let value_id = ctx.alloc_value_id();
+        ctx.instructions.push(IrInst::Call {
+            function,
+            args: args.clone(),
+            ty: *ty,
+        });
+        ctx.socket_to_value.insert(output_socket, value_id);
+    }
+
+    Ok(())
+}
+
 fn value_type_to_ir_type(value_type: ValueType) -> Result<IrType, IrLoweringError> {
     Ok(match value_type {
         ValueType::Float => IrType::Float,
@@ -174,26 +475,8 @@ fn value_type_to_ir_type(value_type: ValueType) -> Result<IrType, IrLoweringErro
         ValueType::Bool => IrType::Bool,
         ValueType::Int => IrType::Int,
         ValueType::Color => IrType::Color,
+        ValueType::Mat2 => IrType::Mat2,
+        ValueType::Mat3 => IrType::Mat3,
+        ValueType::Mat4 => IrType::Mat4,
     })
 }
-
-fn create_default_literal(value_type: ValueType) -> Literal {
-    match value_type {
-        ValueType::Float => Literal::Float(0.0),
-        ValueType::Vec2 => Literal::Vec2([0.0, 0.0]),
-        ValueType::Vec3 => Literal::Vec3([0.0, 0.0, 0.0]),
-        ValueType::Vec4 => Literal::Vec4([0.0, 0.0, 0.0, 0.0]),
-        ValueType::Bool => Literal::Bool(false),
-        ValueType::Int => Literal::Int(0),
-        ValueType::Color => Literal::Vec4([0.0, 0.0, 0.0, 1.0]),
-    }
-}
-
-fn math_op_to_binary_op(math_op: MathOp) -> BinaryOp {
-    match math_op {
-        MathOp::Add => BinaryOp::Add,
-        MathOp::Subtract => BinaryOp::Sub,
-        MathOp::Multiply => BinaryOp::Mul,
-        MathOp::Divide => BinaryOp::Div,
-    }
-}
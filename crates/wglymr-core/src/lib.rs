@@ -12,39 +12,80 @@
 //!
 //! This crate must be usable without any editor or UI.
 
+mod code;
 pub mod diagnostics;
 mod error;
 mod graph;
+pub mod group;
+mod incremental;
 pub mod ir;
 pub mod ir_conversion;
 pub mod ir_debug;
 mod ir_lowering;
 mod link;
+mod matrix;
 mod node;
 pub mod passes;
+mod pipeline;
+mod preview;
+mod property;
+pub mod range_analysis;
+mod registry;
+pub mod simplify;
 mod socket;
+mod stats;
 mod type_propagation;
 mod types;
 pub mod wgsl;
+#[cfg(feature = "naga")]
+mod wgsl_import;
 
 #[cfg(test)]
 mod tests;
 
+pub use code::{generate_code_functions, validate_wgsl, CodeError, CodeFunctions};
 pub use diagnostics::{
-    diagnostics_from_conversion_error, diagnostics_from_lowering_error,
+    cycle_diagnostics, diagnostics_from_compile_error, diagnostics_from_conversion_error,
+    diagnostics_from_graph_error, diagnostics_from_group_error, diagnostics_from_lowering_error,
     diagnostics_from_type_error, unreachable_nodes, warning_conversion_inserted,
     warning_default_value_used, warnings_unreachable_nodes, Diagnostic, DiagnosticSeverity,
 };
 pub use error::GraphError;
 pub use graph::{Graph, InputDef};
-pub use ir::{BinaryOp, IrInst, IrProgram, IrType, Literal, ValueId};
+pub use group::{
+    inline_groups, GroupDef, GroupError, GroupId, GroupInterface, GroupLibrary, InlinedGraph,
+    NodeOrigin, SocketOrigin,
+};
+pub use incremental::IncrementalCompiler;
+pub use ir::{BinaryOp, IrHelper, IrInst, IrOutput, IrProgram, IrType, Literal, ValueId};
 pub use ir_conversion::{insert_conversions, ConversionError};
 pub use ir_debug::{pretty_print, validate_ir, IrValidationError};
-pub use ir_lowering::{lower_to_ir, IrLoweringError};
+pub use ir_lowering::{lower_to_ir, lower_to_ir_with_registry, IrLoweringError};
 pub use link::{Link, LinkId};
 pub use node::{MathOp, Node, NodeId, NodeKind};
-pub use passes::{build_graph_view, detect_cycles, reachable_from, topological_sort, GraphView};
-pub use socket::{InputSocketConfig, Socket, SocketDirection, SocketId};
-pub use type_propagation::{propagate_types, TypeMap};
-pub use types::{are_compatible, unify, TypeError, ValueType};
+pub use passes::{
+    analyze_graph, build_graph_view, detect_cycles, reachable_from, topological_sort,
+    GraphAnalysis, GraphCycle, GraphView,
+};
+pub use pipeline::{
+    compile_graph, compile_graph_with, CompileError, CompileOptions, CompiledProgram,
+};
+pub use preview::{
+    compile_preview, compile_preview_with, compile_preview_with_registry, PreviewError,
+};
+pub use property::{NodeProperties, PropertyError, PropertyKind, PropertySpec, PropertyValue};
+pub use range_analysis::{analyze_ranges, Interval, RangeAnalysis, RangeHints, ValueRange};
+pub use registry::{
+    LoweringRule, NodeDefinition, NodeRegistry, PropertyVariant, RegistryError, SocketSpec,
+    TypeRule,
+};
+pub use simplify::simplify;
+pub use socket::{
+    InputSocketConfig, OutOfRange, Socket, SocketDirection, SocketId, SocketMetadata, SocketSubtype,
+};
+pub use stats::{compile_stats, CompileStats, InstructionCounts, NodeCost, TOP_NODE_COUNT};
+pub use type_propagation::{propagate_types, propagate_types_with_registry, TypeMap};
+pub use types::{are_compatible, binary_result_type, unify, TypeError, ValueType};
 pub use wgsl::emit_wgsl;
+#[cfg(feature = "naga")]
+pub use wgsl_import::{import_wgsl_functions, ImportError, SkippedFunction, WgslImport};
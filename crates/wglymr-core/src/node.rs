@@ -1,7 +1,7 @@
 use glam::Vec2;
 use serde::{Deserialize, Serialize};
 
-use crate::{SocketId, ValueType};
+use crate::{GroupId, NodeProperties, SocketId, ValueType};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct NodeId(pub u64);
@@ -18,7 +18,23 @@ pub enum MathOp {
 pub enum NodeKind {
     Value(ValueType),
     Math(MathOp),
-    Generic(String),
+    /// Node described by a `NodeRegistry` definition id
+    Registered(String),
+    /// Instance of a node group from a `GroupLibrary`
+    Group(GroupId),
+    /// Exposes the group's input sockets inside the group graph
+    GroupInput,
+    /// Collects the group's output sockets inside the group graph
+    GroupOutput,
+    /// Custom WGSL snippet; sockets declare its parameters and results
+    Code(String),
+}
+
+impl NodeKind {
+    /// Group interface nodes forward input `i` to output `i` once inlined
+    pub fn is_group_interface(&self) -> bool {
+        matches!(self, NodeKind::GroupInput | NodeKind::GroupOutput)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,4 +44,7 @@ pub struct Node {
     pub inputs: Vec<SocketId>,
     pub outputs: Vec<SocketId>,
     pub position: Vec2,
+    /// Values of the properties declared by the node's definition
+    #[serde(default)]
+    pub properties: NodeProperties,
 }
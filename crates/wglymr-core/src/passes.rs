@@ -1,159 +1,299 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 
-use crate::{Graph, GraphError, NodeId};
+use crate::{Graph, GraphError, LinkId, NodeId};
 
-pub fn detect_cycles(graph: &Graph) -> Vec<Vec<NodeId>> {
-    let mut visited = HashSet::new();
-    let mut rec_stack = HashSet::new();
-    let mut cycles = Vec::new();
-
-    for node_id in graph.node_ids() {
-        if !visited.contains(&node_id) {
-            let mut path = Vec::new();
-            dfs_detect_cycle(
-                graph,
-                node_id,
-                &mut visited,
-                &mut rec_stack,
-                &mut path,
-                &mut cycles,
-            );
+/// Strongly connected set of nodes that feed each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphCycle {
+    /// Every node of the cycle, sorted by id
+    pub nodes: Vec<NodeId>,
+    /// Every link between two nodes of the cycle, sorted by id
+    pub links: Vec<LinkId>,
+    /// One closed loop through the cycle in data-flow order, starting at
+    /// the node with the smallest id
+    pub path: Vec<LinkId>,
+}
+
+/// Result of a single walk over the graph upstream of some roots
+#[derive(Debug, Clone, Default)]
+pub struct GraphAnalysis {
+    /// Reachable nodes, every node after the nodes feeding it
+    ///
+    /// Nodes of a cycle are listed next to each other in no particular order.
+    pub topo_order: Vec<NodeId>,
+    pub reachable: HashSet<NodeId>,
+    pub cycles: Vec<GraphCycle>,
+}
+
+impl GraphAnalysis {
+    /// Cycle containing `node`, if any
+    pub fn cycle_of(&self, node: NodeId) -> Option<&GraphCycle> {
+        self.cycles.iter().find(|cycle| cycle.nodes.contains(&node))
+    }
+}
+
+/// Nodes feeding each node through its inputs, in input order
+type Upstream = HashMap<NodeId, Vec<(NodeId, LinkId)>>;
+
+fn upstream_edges(graph: &Graph) -> Upstream {
+    let mut incoming = HashMap::new();
+    for link in graph.links() {
+        if let Some(from) = graph.socket(link.from) {
+            incoming.insert(link.to, (from.node, link.id));
         }
     }
 
-    cycles
+    graph
+        .nodes()
+        .map(|node| {
+            let edges = node
+                .inputs
+                .iter()
+                .filter_map(|socket| incoming.get(socket).copied())
+                .collect();
+            (node.id, edges)
+        })
+        .collect()
 }
 
-fn dfs_detect_cycle(
-    graph: &Graph,
-    node_id: NodeId,
-    visited: &mut HashSet<NodeId>,
-    rec_stack: &mut HashSet<NodeId>,
-    path: &mut Vec<NodeId>,
-    cycles: &mut Vec<Vec<NodeId>>,
-) {
-    visited.insert(node_id);
-    rec_stack.insert(node_id);
-    path.push(node_id);
-
-    if let Some(node) = graph.node(node_id) {
-        for input_socket in &node.inputs {
-            for link in graph.links_into(*input_socket) {
-                if let Some(from_socket) = graph.socket(link.from) {
-                    let upstream_node = from_socket.node;
-
-                    if rec_stack.contains(&upstream_node) {
-                        if let Some(cycle_start) = path.iter().position(|&n| n == upstream_node) {
-                            let cycle = path[cycle_start..].to_vec();
-                            if !cycles.iter().any(|c| cycles_equal(c, &cycle)) {
-                                cycles.push(cycle);
-                            }
-                        }
-                    } else if !visited.contains(&upstream_node) {
-                        dfs_detect_cycle(graph, upstream_node, visited, rec_stack, path, cycles);
-                    }
+/// Walk the graph upstream of `roots` once
+///
+/// Tarjan's algorithm over input links yields the topological order,
+/// the reachable set and every cycle together. Roots missing from the graph
+/// are ignored. The order only depends on the roots and input order, so
+/// repeated analyses of an unchanged graph agree.
+pub fn analyze_graph(graph: &Graph, roots: &[NodeId]) -> GraphAnalysis {
+    let upstream = upstream_edges(graph);
+    let no_edges = Vec::new();
+    let edges_of = |node: NodeId| upstream.get(&node).unwrap_or(&no_edges);
+
+    let mut analysis = GraphAnalysis::default();
+    let mut walk = Tarjan::default();
+
+    for &root in roots {
+        if walk.index.contains_key(&root) || graph.node(root).is_none() {
+            continue;
+        }
+
+        // Explicit call stack of (node, next edge) keeps deep graphs safe
+        let mut calls: Vec<(NodeId, usize)> = vec![walk.enter(root)];
+
+        while let Some((node, edge)) = calls.last_mut() {
+            let node = *node;
+            if let Some(&(next, _)) = edges_of(node).get(*edge) {
+                *edge += 1;
+                if !walk.index.contains_key(&next) {
+                    calls.push(walk.enter(next));
+                } else if walk.on_stack.contains(&next) {
+                    walk.lower(node, walk.index[&next]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                walk.lower(parent, walk.lowlink[&node]);
+            }
+
+            if walk.lowlink[&node] == walk.index[&node] {
+                let component = walk.pop_component(node);
+                analysis.topo_order.extend(&component);
+
+                let self_loop = edges_of(node).iter().any(|&(from, _)| from == node);
+                if component.len() > 1 || self_loop {
+                    analysis.cycles.push(describe_cycle(&upstream, component));
                 }
             }
         }
     }
 
-    path.pop();
-    rec_stack.remove(&node_id);
+    analysis.reachable = analysis.topo_order.iter().copied().collect();
+    analysis.cycles.sort_by_key(|cycle| cycle.nodes[0].0);
+    analysis
 }
 
-fn cycles_equal(a: &[NodeId], b: &[NodeId]) -> bool {
-    if a.len() != b.len() {
-        return false;
+#[derive(Default)]
+struct Tarjan {
+    index: HashMap<NodeId, usize>,
+    lowlink: HashMap<NodeId, usize>,
+    on_stack: HashSet<NodeId>,
+    stack: Vec<NodeId>,
+}
+
+impl Tarjan {
+    fn enter(&mut self, node: NodeId) -> (NodeId, usize) {
+        let next = self.index.len();
+        self.index.insert(node, next);
+        self.lowlink.insert(node, next);
+        self.stack.push(node);
+        self.on_stack.insert(node);
+        (node, 0)
     }
-    if a.is_empty() {
-        return true;
+
+    fn lower(&mut self, node: NodeId, low: usize) {
+        let current = self
+            .lowlink
+            .get_mut(&node)
+            .expect("visited node has a lowlink");
+        *current = (*current).min(low);
     }
 
-    let start = b.iter().position(|&n| n == a[0]);
-    if let Some(start_pos) = start {
-        for i in 0..a.len() {
-            if a[i] != b[(start_pos + i) % b.len()] {
-                return false;
+    /// Pop the component rooted at `node`, upstream nodes first
+    fn pop_component(&mut self, node: NodeId) -> Vec<NodeId> {
+        let mut component = Vec::new();
+        while let Some(member) = self.stack.pop() {
+            self.on_stack.remove(&member);
+            component.push(member);
+            if member == node {
+                break;
             }
         }
-        true
-    } else {
-        false
+        component.reverse();
+        component
     }
 }
 
-pub fn topological_sort(graph: &Graph) -> Result<Vec<NodeId>, GraphError> {
-    if !detect_cycles(graph).is_empty() {
-        return Err(GraphError::CycleDetected);
-    }
+fn describe_cycle(upstream: &Upstream, mut nodes: Vec<NodeId>) -> GraphCycle {
+    nodes.sort_by_key(|node| node.0);
+    let members: HashSet<NodeId> = nodes.iter().copied().collect();
 
-    let mut in_degree: HashMap<NodeId, usize> = HashMap::new();
-    let mut adjacency: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+    let mut links: Vec<LinkId> = nodes
+        .iter()
+        .flat_map(|node| &upstream[node])
+        .filter(|(from, _)| members.contains(from))
+        .map(|&(_, link)| link)
+        .collect();
+    links.sort_by_key(|link| link.0);
 
-    for node_id in graph.node_ids() {
-        in_degree.insert(node_id, 0);
-        adjacency.insert(node_id, Vec::new());
+    GraphCycle {
+        path: loop_path(upstream, &members, nodes[0]),
+        nodes,
+        links,
     }
+}
 
-    for node in graph.nodes() {
-        for input_socket in &node.inputs {
-            for link in graph.links_into(*input_socket) {
-                if let Some(from_socket) = graph.socket(link.from) {
-                    let from_node = from_socket.node;
-                    adjacency.entry(from_node).or_default().push(node.id);
-                    *in_degree.entry(node.id).or_insert(0) += 1;
+/// Shortest loop through `start` inside `members`, as links in data-flow order
+fn loop_path(upstream: &Upstream, members: &HashSet<NodeId>, start: NodeId) -> Vec<LinkId> {
+    // Search upstream from `start` until reaching it again; the links found
+    // lead back down to `start`
+    let mut came_from: HashMap<NodeId, (NodeId, LinkId)> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        for &(from, link) in &upstream[&node] {
+            if !members.contains(&from) {
+                continue;
+            }
+            if from == start {
+                let mut path = vec![link];
+                let mut current = node;
+                while current != start {
+                    let (downstream, link) = came_from[&current];
+                    path.push(link);
+                    current = downstream;
                 }
+                return path;
+            }
+            if let std::collections::hash_map::Entry::Vacant(entry) = came_from.entry(from) {
+                entry.insert((node, link));
+                queue.push_back(from);
             }
         }
     }
 
-    let mut queue: VecDeque<NodeId> = in_degree
-        .iter()
-        .filter_map(|(&node, &degree)| if degree == 0 { Some(node) } else { None })
-        .collect();
+    Vec::new()
+}
+
+/// Shortest downstream route from `from` to `to`
+///
+/// Returns the nodes along the route, both ends included, and the links
+/// joining them in data-flow order.
+pub(crate) fn downstream_path(
+    graph: &Graph,
+    from: NodeId,
+    to: NodeId,
+) -> Option<(Vec<NodeId>, Vec<LinkId>)> {
+    let mut links: Vec<_> = graph.links().collect();
+    links.sort_by_key(|link| link.id.0);
+
+    let mut downstream: HashMap<NodeId, Vec<(NodeId, LinkId)>> = HashMap::new();
+    for link in links {
+        if let (Some(source), Some(target)) = (graph.socket(link.from), graph.socket(link.to)) {
+            downstream
+                .entry(source.node)
+                .or_default()
+                .push((target.node, link.id));
+        }
+    }
 
-    let mut result = Vec::new();
+    let mut came_from: HashMap<NodeId, (NodeId, LinkId)> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+    let mut found = from == to;
 
     while let Some(node) = queue.pop_front() {
-        result.push(node);
-
-        if let Some(neighbors) = adjacency.get(&node) {
-            for &neighbor in neighbors {
-                if let Some(degree) = in_degree.get_mut(&neighbor) {
-                    *degree -= 1;
-                    if *degree == 0 {
-                        queue.push_back(neighbor);
-                    }
-                }
+        if found {
+            break;
+        }
+        for &(next, link) in downstream.get(&node).into_iter().flatten() {
+            if next == from || came_from.contains_key(&next) {
+                continue;
             }
+            came_from.insert(next, (node, link));
+            if next == to {
+                found = true;
+                break;
+            }
+            queue.push_back(next);
         }
     }
 
-    Ok(result)
+    if !found {
+        return None;
+    }
+
+    let mut nodes = vec![to];
+    let mut path = Vec::new();
+    let mut current = to;
+    while current != from {
+        let (previous, link) = came_from[&current];
+        nodes.push(previous);
+        path.push(link);
+        current = previous;
+    }
+    nodes.reverse();
+    path.reverse();
+    Some((nodes, path))
 }
 
-pub fn reachable_from(graph: &Graph, roots: &[NodeId]) -> HashSet<NodeId> {
-    let mut reachable = HashSet::new();
-    let mut stack = roots.to_vec();
-
-    while let Some(node_id) = stack.pop() {
-        if reachable.insert(node_id) {
-            if let Some(node) = graph.node(node_id) {
-                for input_socket in &node.inputs {
-                    for link in graph.links_into(*input_socket) {
-                        if let Some(from_socket) = graph.socket(link.from) {
-                            let upstream_node = from_socket.node;
-                            if !reachable.contains(&upstream_node) {
-                                stack.push(upstream_node);
-                            }
-                        }
-                    }
-                }
-            }
-        }
+/// Every node in the graph, ordered by id
+fn all_nodes(graph: &Graph) -> Vec<NodeId> {
+    let mut nodes: Vec<NodeId> = graph.node_ids().collect();
+    nodes.sort_by_key(|node| node.0);
+    nodes
+}
+
+/// Nodes of every cycle in the graph
+pub fn detect_cycles(graph: &Graph) -> Vec<Vec<NodeId>> {
+    analyze_graph(graph, &all_nodes(graph))
+        .cycles
+        .into_iter()
+        .map(|cycle| cycle.nodes)
+        .collect()
+}
+
+/// Every node in the graph, each after the nodes feeding it
+pub fn topological_sort(graph: &Graph) -> Result<Vec<NodeId>, GraphError> {
+    let analysis = analyze_graph(graph, &all_nodes(graph));
+    if !analysis.cycles.is_empty() {
+        return Err(GraphError::CycleDetected {
+            cycles: analysis.cycles,
+        });
     }
+    Ok(analysis.topo_order)
+}
 
-    reachable
+pub fn reachable_from(graph: &Graph, roots: &[NodeId]) -> HashSet<NodeId> {
+    analyze_graph(graph, roots).reachable
 }
 
 #[derive(Debug)]
@@ -161,6 +301,7 @@ pub struct GraphView<'a> {
     pub graph: &'a Graph,
     pub roots: Vec<NodeId>,
     pub reachable: HashSet<NodeId>,
+    /// Reachable nodes only, each after the nodes feeding it
     pub topo_order: Vec<NodeId>,
 }
 
@@ -175,18 +316,18 @@ pub fn build_graph_view<'a>(
         }
     }
 
-    let cycles = detect_cycles(graph);
-    if !cycles.is_empty() {
-        return Err(GraphError::CycleDetected);
+    // Cycles that do not feed the roots cannot affect the result
+    let analysis = analyze_graph(graph, roots);
+    if !analysis.cycles.is_empty() {
+        return Err(GraphError::CycleDetected {
+            cycles: analysis.cycles,
+        });
     }
 
-    let topo_order = topological_sort(graph)?;
-    let reachable = reachable_from(graph, roots);
-
     Ok(GraphView {
         graph,
         roots: roots.to_vec(),
-        reachable,
-        topo_order,
+        reachable: analysis.reachable,
+        topo_order: analysis.topo_order,
     })
 }
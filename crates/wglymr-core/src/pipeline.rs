@@ -0,0 +1,93 @@
+//! Graph to WGSL compilation pipeline.
+//!
+//! `compile_graph` runs the passes in order:
+//! graph view => type propagation => IR lowering => conversion insertion =>
+//! simplification => WGSL emission, followed by a cost report.
+
+use thiserror::Error;
+
+use crate::{
+    build_graph_view, compile_stats, emit_wgsl, insert_conversions, lower_to_ir_with_registry,
+    propagate_types_with_registry, simplify, CompileStats, ConversionError, Graph, GraphError,
+    IrLoweringError, IrProgram, NodeId, NodeRegistry, TypeError,
+};
+
+#[derive(Debug, Error)]
+pub enum CompileError {
+    #[error(transparent)]
+    Graph(#[from] GraphError),
+
+    #[error(transparent)]
+    Type(#[from] TypeError),
+
+    #[error(transparent)]
+    Lowering(#[from] IrLoweringError),
+
+    #[error(transparent)]
+    Conversion(#[from] ConversionError),
+}
+
+/// Optional pipeline passes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompileOptions {
+    /// Run the algebraic simplification pass
+    pub simplify: bool,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self { simplify: true }
+    }
+}
+
+/// Output of a successful compilation
+#[derive(Debug, Clone)]
+pub struct CompiledProgram {
+    pub program: IrProgram,
+    pub wgsl: String,
+    pub stats: CompileStats,
+}
+
+/// Compile the graph upstream of `roots` with the built-in node registry
+pub fn compile_graph(graph: &Graph, roots: &[NodeId]) -> Result<CompiledProgram, CompileError> {
+    compile_graph_with(
+        graph,
+        roots,
+        NodeRegistry::builtin(),
+        CompileOptions::default(),
+    )
+}
+
+/// Compile the graph upstream of `roots`
+pub fn compile_graph_with(
+    graph: &Graph,
+    roots: &[NodeId],
+    registry: &NodeRegistry,
+    options: CompileOptions,
+) -> Result<CompiledProgram, CompileError> {
+    let view = build_graph_view(graph, roots)?;
+    let types = propagate_types_with_registry(&view, registry)?;
+    let program = lower_to_ir_with_registry(&view, &types, registry)?;
+    let program = run_ir_passes(program, options)?;
+    let wgsl = emit_wgsl(&program);
+    let stats = compile_stats(&program);
+
+    Ok(CompiledProgram {
+        program,
+        wgsl,
+        stats,
+    })
+}
+
+/// Run the IR passes that follow lowering
+pub(crate) fn run_ir_passes(
+    program: IrProgram,
+    options: CompileOptions,
+) -> Result<IrProgram, ConversionError> {
+    let program = insert_conversions(program)?;
+    Ok(if options.simplify {
+        simplify(program)
+    } else {
+        program
+    })
+}
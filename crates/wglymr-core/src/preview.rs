@@ -0,0 +1,186 @@
+//! Viewer previews of intermediate sockets.
+//!
+//! `compile_preview` compiles the part of a graph feeding one output socket
+//! and converts that socket's value to a displayable color:
+//! - Float, Int and Bool become gray
+//! - Vec2 fills red and green
+//! - Vec3 fills red, green and blue
+//! - Vec4 and Color are shown as is
+//!
+//! The resulting program has a single `"preview"` output of type Color.
+
+use thiserror::Error;
+
+use crate::ir_lowering::lower_view;
+use crate::pipeline::run_ir_passes;
+use crate::{
+    build_graph_view, propagate_types_with_registry, CompileOptions, ConversionError, Graph,
+    GraphError, IrInst, IrLoweringError, IrOutput, IrProgram, IrType, Literal, NodeRegistry,
+    SocketDirection, SocketId, TypeError, ValueId, ValueType,
+};
+
+#[derive(Debug, Error)]
+pub enum PreviewError {
+    #[error("socket {0:?} not found")]
+    SocketNotFound(SocketId),
+
+    #[error("socket {0:?} is not an output socket")]
+    NotAnOutput(SocketId),
+
+    #[error("values of type {0:?} cannot be previewed")]
+    UnsupportedType(ValueType),
+
+    #[error(transparent)]
+    Graph(#[from] GraphError),
+
+    #[error(transparent)]
+    Type(#[from] TypeError),
+
+    #[error(transparent)]
+    Lowering(#[from] IrLoweringError),
+
+    #[error(transparent)]
+    Conversion(#[from] ConversionError),
+}
+
+/// Compile a preview of `socket` using the built-in node registry
+pub fn compile_preview(graph: &Graph, socket: SocketId) -> Result<IrProgram, PreviewError> {
+    compile_preview_with_registry(graph, socket, NodeRegistry::builtin())
+}
+
+/// Compile a program whose only output is `socket` converted to Color
+///
+/// Only the nodes upstream of the socket's node are compiled. The returned
+/// program already has conversions inserted, is simplified and can be
+/// passed to `emit_wgsl`.
+pub fn compile_preview_with_registry(
+    graph: &Graph,
+    socket: SocketId,
+    registry: &NodeRegistry,
+) -> Result<IrProgram, PreviewError> {
+    compile_preview_with(graph, socket, registry, CompileOptions::default())
+}
+
+/// Compile a preview of `socket`, running the optional passes in `options`
+pub fn compile_preview_with(
+    graph: &Graph,
+    socket: SocketId,
+    registry: &NodeRegistry,
+    options: CompileOptions,
+) -> Result<IrProgram, PreviewError> {
+    let probed = graph
+        .socket(socket)
+        .ok_or(PreviewError::SocketNotFound(socket))?;
+    if probed.direction != SocketDirection::Output {
+        return Err(PreviewError::NotAnOutput(socket));
+    }
+
+    let view = build_graph_view(graph, &[probed.node])?;
+    let types = propagate_types_with_registry(&view, registry)?;
+    let (mut program, socket_values) = lower_view(&view, &types, registry)?;
+
+    let value = *socket_values
+        .get(&socket)
+        .ok_or(IrLoweringError::MissingOutput(socket))?;
+    let value_type = types
+        .get(socket)
+        .ok_or(IrLoweringError::MissingType(socket))?;
+
+    let color = to_color(&mut program, value, value_type)?;
+    program
+        .origins
+        .resize(program.instructions.len(), Some(probed.node));
+    program.outputs = vec![IrOutput {
+        name: "preview".to_string(),
+        value: color,
+    }];
+
+    Ok(run_ir_passes(program, options)?)
+}
+
+/// Append instructions turning `value` into a Color
+fn to_color(
+    program: &mut IrProgram,
+    value: ValueId,
+    value_type: ValueType,
+) -> Result<ValueId, PreviewError> {
+    match value_type {
+        ValueType::Color => Ok(value),
+
+        ValueType::Float => {
+            let rgb = push(
+                program,
+                IrInst::Convert {
+                    from: value,
+                    from_ty: IrType::Float,
+                    to_ty: IrType::Vec3,
+                },
+            );
+            to_color(program, rgb, ValueType::Vec3)
+        }
+
+        ValueType::Int | ValueType::Bool => {
+            let gray = push(
+                program,
+                IrInst::Call {
+                    function: "f32".to_string(),
+                    args: vec![value],
+                    ty: IrType::Float,
+                },
+            );
+            to_color(program, gray, ValueType::Float)
+        }
+
+        ValueType::Vec2 => {
+            let blue = push(
+                program,
+                IrInst::Constant {
+                    value: Literal::Float(0.0),
+                    ty: IrType::Float,
+                },
+            );
+            let alpha = push(
+                program,
+                IrInst::Constant {
+                    value: Literal::Float(1.0),
+                    ty: IrType::Float,
+                },
+            );
+            Ok(push(
+                program,
+                IrInst::Call {
+                    function: "vec4<f32>".to_string(),
+                    args: vec![value, blue, alpha],
+                    ty: IrType::Color,
+                },
+            ))
+        }
+
+        ValueType::Vec3 => Ok(push(
+            program,
+            IrInst::Convert {
+                from: value,
+                from_ty: IrType::Vec3,
+                to_ty: IrType::Color,
+            },
+        )),
+
+        ValueType::Vec4 => Ok(push(
+            program,
+            IrInst::Call {
+                function: "vec4<f32>".to_string(),
+                args: vec![value],
+                ty: IrType::Color,
+            },
+        )),
+
+        ValueType::Mat2 | ValueType::Mat3 | ValueType::Mat4 => {
+            Err(PreviewError::UnsupportedType(value_type))
+        }
+    }
+}
+
+fn push(program: &mut IrProgram, inst: IrInst) -> ValueId {
+    program.instructions.push(inst);
+    ValueId((program.instructions.len() - 1) as u32)
+}
@@ -0,0 +1,165 @@
+//! Node properties.
+//!
+//! Properties are typed parameters stored on a node instead of being fed
+//! through sockets, such as the operation of a Math node. A node definition
+//! declares its properties with `PropertySpec`s; nodes only store values that
+//! differ from, or were explicitly set over, the declared defaults.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Property values of a node, keyed by property name
+pub type NodeProperties = BTreeMap<String, PropertyValue>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PropertyValue {
+    /// Selected option of an enum property
+    Enum(String),
+    Bool(bool),
+    Int(i32),
+    String(String),
+}
+
+/// Type of a property and the values it accepts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropertyKind {
+    /// One of the listed options
+    Enum(Vec<String>),
+    Bool,
+    Int {
+        min: Option<i32>,
+        max: Option<i32>,
+    },
+    String,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PropertyError {
+    #[error("unknown property {0:?}")]
+    UnknownProperty(String),
+
+    #[error("property {name:?} expects a {expected} value")]
+    WrongKind {
+        name: String,
+        expected: &'static str,
+    },
+
+    #[error("{value:?} is not an option of property {name:?}")]
+    UnknownOption { name: String, value: String },
+
+    #[error("{value} is outside {min:?}..{max:?} for property {name:?}")]
+    OutOfRange {
+        name: String,
+        value: i32,
+        min: Option<i32>,
+        max: Option<i32>,
+    },
+}
+
+/// Declared property of a node definition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertySpec {
+    pub name: String,
+    pub kind: PropertyKind,
+    pub default: PropertyValue,
+}
+
+impl PropertySpec {
+    /// Enum property; `default` must be one of `options`
+    pub fn enumeration(name: impl Into<String>, options: &[&str], default: &str) -> Self {
+        Self {
+            name: name.into(),
+            kind: PropertyKind::Enum(options.iter().map(|o| o.to_string()).collect()),
+            default: PropertyValue::Enum(default.to_string()),
+        }
+    }
+
+    pub fn bool(name: impl Into<String>, default: bool) -> Self {
+        Self {
+            name: name.into(),
+            kind: PropertyKind::Bool,
+            default: PropertyValue::Bool(default),
+        }
+    }
+
+    pub fn int(name: impl Into<String>, default: i32) -> Self {
+        Self {
+            name: name.into(),
+            kind: PropertyKind::Int {
+                min: None,
+                max: None,
+            },
+            default: PropertyValue::Int(default),
+        }
+    }
+
+    pub fn string(name: impl Into<String>, default: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            kind: PropertyKind::String,
+            default: PropertyValue::String(default.into()),
+        }
+    }
+
+    /// Limit an int property to `min..=max`
+    pub fn with_range(mut self, min: i32, max: i32) -> Self {
+        if let PropertyKind::Int { .. } = self.kind {
+            self.kind = PropertyKind::Int {
+                min: Some(min),
+                max: Some(max),
+            };
+        }
+        self
+    }
+
+    /// Options of an enum property, empty for other kinds
+    pub fn options(&self) -> &[String] {
+        match &self.kind {
+            PropertyKind::Enum(options) => options,
+            _ => &[],
+        }
+    }
+
+    /// Check that `value` is accepted by this property
+    pub fn check(&self, value: &PropertyValue) -> Result<(), PropertyError> {
+        let wrong_kind = |expected| PropertyError::WrongKind {
+            name: self.name.clone(),
+            expected,
+        };
+
+        match (&self.kind, value) {
+            (PropertyKind::Enum(options), PropertyValue::Enum(option)) => {
+                if options.contains(option) {
+                    Ok(())
+                } else {
+                    Err(PropertyError::UnknownOption {
+                        name: self.name.clone(),
+                        value: option.clone(),
+                    })
+                }
+            }
+            (PropertyKind::Int { min, max }, PropertyValue::Int(v)) => {
+                let below = min.is_some_and(|min| *v < min);
+                let above = max.is_some_and(|max| *v > max);
+                if below || above {
+                    Err(PropertyError::OutOfRange {
+                        name: self.name.clone(),
+                        value: *v,
+                        min: *min,
+                        max: *max,
+                    })
+                } else {
+                    Ok(())
+                }
+            }
+            (PropertyKind::Bool, PropertyValue::Bool(_))
+            | (PropertyKind::String, PropertyValue::String(_)) => Ok(()),
+            (PropertyKind::Enum(_), _) => Err(wrong_kind("enum")),
+            (PropertyKind::Int { .. }, _) => Err(wrong_kind("int")),
+            (PropertyKind::Bool, _) => Err(wrong_kind("bool")),
+            (PropertyKind::String, _) => Err(wrong_kind("string")),
+        }
+    }
+}
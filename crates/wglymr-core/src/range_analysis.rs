@@ -0,0 +1,403 @@
+//! Static value range analysis over IR programs.
+//!
+//! Propagates a conservative interval for every component of every value,
+//! starting from constants and the ranges supplied for program inputs
+//! (`uv` is in [0, 1] by default). The ranges drive lint warnings:
+//! - division by a value whose range contains zero
+//! - operations that can produce NaN or infinity (`pow` with a negative
+//!   base, `sqrt`/`log` of negative values)
+//! - Color outputs that can leave the [0, 1] gamut
+//!
+//! Values whose range is unbounded are never warned about, so calls to opaque
+//! helpers do not flood the editor with false positives.
+
+use std::collections::HashMap;
+
+use crate::{BinaryOp, Diagnostic, IrInst, IrProgram, IrType, Literal, NodeId, ValueId};
+
+/// Closed interval of possible values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Interval {
+    pub const UNKNOWN: Interval = Interval {
+        min: f64::NEG_INFINITY,
+        max: f64::INFINITY,
+    };
+
+    pub const UNIT: Interval = Interval { min: 0.0, max: 1.0 };
+
+    pub fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    pub fn point(value: f64) -> Self {
+        Self::new(value, value)
+    }
+
+    /// Both bounds are finite
+    pub fn is_bounded(&self) -> bool {
+        self.min.is_finite() && self.max.is_finite()
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        self.min <= value && value <= self.max
+    }
+
+    fn add(self, other: Interval) -> Interval {
+        Interval::from_bounds(&[self.min + other.min, self.max + other.max])
+    }
+
+    fn sub(self, other: Interval) -> Interval {
+        Interval::from_bounds(&[self.min - other.max, self.max - other.min])
+    }
+
+    fn mul(self, other: Interval) -> Interval {
+        Interval::from_bounds(&[
+            self.min * other.min,
+            self.min * other.max,
+            self.max * other.min,
+            self.max * other.max,
+        ])
+    }
+
+    fn div(self, other: Interval) -> Interval {
+        if other.contains(0.0) {
+            return Interval::UNKNOWN;
+        }
+        self.mul(Interval::from_bounds(&[1.0 / other.min, 1.0 / other.max]))
+    }
+
+    /// Smallest interval containing `bounds`; unknown if any bound is NaN
+    fn from_bounds(bounds: &[f64]) -> Interval {
+        if bounds.iter().any(|b| b.is_nan()) {
+            return Interval::UNKNOWN;
+        }
+        Interval {
+            min: bounds.iter().copied().fold(f64::INFINITY, f64::min),
+            max: bounds.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+/// Per-component range of a value
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueRange {
+    pub components: Vec<Interval>,
+}
+
+impl ValueRange {
+    pub fn splat(interval: Interval, count: usize) -> Self {
+        Self {
+            components: vec![interval; count],
+        }
+    }
+
+    pub fn unknown(ty: IrType) -> Self {
+        Self::splat(Interval::UNKNOWN, component_count(ty))
+    }
+
+    /// Component `i`, broadcasting single-component values
+    fn component(&self, i: usize) -> Interval {
+        match self.components.as_slice() {
+            [single] => *single,
+            components => components.get(i).copied().unwrap_or(Interval::UNKNOWN),
+        }
+    }
+}
+
+/// Ranges of program inputs by name
+#[derive(Debug, Clone)]
+pub struct RangeHints {
+    pub inputs: HashMap<String, ValueRange>,
+}
+
+impl Default for RangeHints {
+    fn default() -> Self {
+        let mut inputs = HashMap::new();
+        inputs.insert("uv".to_string(), ValueRange::splat(Interval::UNIT, 2));
+        Self { inputs }
+    }
+}
+
+impl RangeHints {
+    /// Declare the range of input `name`, e.g. from a parameter's min/max
+    pub fn with_input(mut self, name: impl Into<String>, range: ValueRange) -> Self {
+        self.inputs.insert(name.into(), range);
+        self
+    }
+}
+
+/// Result of `analyze_ranges`
+#[derive(Debug, Clone)]
+pub struct RangeAnalysis {
+    /// Range of each value, indexed by `ValueId`
+    pub ranges: Vec<ValueRange>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl RangeAnalysis {
+    pub fn range(&self, value: ValueId) -> Option<&ValueRange> {
+        self.ranges.get(value.0 as usize)
+    }
+}
+
+/// Propagate value ranges through `ir` and collect lint warnings
+///
+/// Warnings are attached to the node each instruction was lowered from.
+pub fn analyze_ranges(ir: &IrProgram, hints: &RangeHints) -> RangeAnalysis {
+    let mut ranges: Vec<ValueRange> = Vec::with_capacity(ir.instructions.len());
+    let mut diagnostics = Vec::new();
+
+    for (index, inst) in ir.instructions.iter().enumerate() {
+        let origin = ir.origin(ValueId(index as u32));
+        let mut warn = |message: String| diagnostics.push(warning(origin, message));
+
+        let range = match inst {
+            IrInst::Constant { value, .. } => literal_range(value),
+
+            IrInst::Input { name, ty } => hints
+                .inputs
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| ValueRange::unknown(*ty)),
+
+            IrInst::Binary { op, lhs, rhs, ty } => {
+                let lhs_range = &ranges[lhs.0 as usize];
+                let rhs_range = &ranges[rhs.0 as usize];
+
+                if *op == BinaryOp::Div
+                    && rhs_range
+                        .components
+                        .iter()
+                        .any(|c| c.is_bounded() && c.contains(0.0))
+                {
+                    let always_zero = rhs_range
+                        .components
+                        .iter()
+                        .any(|c| c.min == 0.0 && c.max == 0.0);
+                    warn(if always_zero {
+                        "Division by zero".to_string()
+                    } else {
+                        "Possible division by zero".to_string()
+                    });
+                }
+
+                let operand_types = [ir.value_type(*lhs), ir.value_type(*rhs)];
+                binary_range(*op, lhs_range, rhs_range, operand_types, *ty)
+            }
+
+            IrInst::Convert {
+                from,
+                from_ty,
+                to_ty,
+            } => convert_range(&ranges[from.0 as usize], *from_ty, *to_ty),
+
+            IrInst::Call { function, args, ty } => {
+                let arg_ranges: Vec<&ValueRange> =
+                    args.iter().map(|arg| &ranges[arg.0 as usize]).collect();
+                call_range(function, &arg_ranges, *ty, &mut warn)
+            }
+        };
+
+        ranges.push(range);
+    }
+
+    for output in &ir.outputs {
+        if ir.value_type(output.value) != Some(IrType::Color) {
+            continue;
+        }
+        let Some(range) = ranges.get(output.value.0 as usize) else {
+            continue;
+        };
+        let out_of_gamut = range.components[..3.min(range.components.len())]
+            .iter()
+            .any(|c| c.is_bounded() && (c.min < 0.0 || c.max > 1.0));
+        if out_of_gamut {
+            diagnostics.push(warning(
+                ir.origin(output.value),
+                format!("Output {:?} may leave the [0, 1] color range", output.name),
+            ));
+        }
+    }
+
+    RangeAnalysis {
+        ranges,
+        diagnostics,
+    }
+}
+
+fn warning(origin: Option<NodeId>, message: String) -> Diagnostic {
+    match origin {
+        Some(node) => Diagnostic::warning_at_node(node, message),
+        None => Diagnostic::warning(message),
+    }
+}
+
+fn component_count(ty: IrType) -> usize {
+    match ty {
+        IrType::Float | IrType::Bool | IrType::Int => 1,
+        IrType::Vec2 => 2,
+        IrType::Vec3 => 3,
+        IrType::Vec4 | IrType::Color => 4,
+        IrType::Mat2 => 4,
+        IrType::Mat3 => 9,
+        IrType::Mat4 => 16,
+    }
+}
+
+fn literal_range(literal: &Literal) -> ValueRange {
+    let values: Vec<f64> = match literal {
+        Literal::Float(v) => vec![*v as f64],
+        Literal::Vec2(v) => v.iter().map(|c| *c as f64).collect(),
+        Literal::Vec3(v) => v.iter().map(|c| *c as f64).collect(),
+        Literal::Vec4(v) => v.iter().map(|c| *c as f64).collect(),
+        Literal::Bool(b) => vec![if *b { 1.0 } else { 0.0 }],
+        Literal::Int(i) => vec![*i as f64],
+        Literal::Mat2(v) => v.iter().map(|c| *c as f64).collect(),
+        Literal::Mat3(v) => v.iter().map(|c| *c as f64).collect(),
+        Literal::Mat4(v) => v.iter().map(|c| *c as f64).collect(),
+    };
+    ValueRange {
+        components: values.into_iter().map(Interval::point).collect(),
+    }
+}
+
+fn binary_range(
+    op: BinaryOp,
+    lhs: &ValueRange,
+    rhs: &ValueRange,
+    operand_types: [Option<IrType>; 2],
+    ty: IrType,
+) -> ValueRange {
+    // Matrix products mix components; only elementwise ops are tracked
+    let is_matrix = |ty: Option<IrType>| ty.is_some_and(|ty| ty.matrix_size().is_some());
+    let matrix_product = op == BinaryOp::Mul
+        && (operand_types.into_iter().any(is_matrix) || ty.matrix_size().is_some());
+    if matrix_product {
+        return ValueRange::unknown(ty);
+    }
+
+    let components = (0..component_count(ty))
+        .map(|i| {
+            let (a, b) = (lhs.component(i), rhs.component(i));
+            match op {
+                BinaryOp::Add => a.add(b),
+                BinaryOp::Sub => a.sub(b),
+                BinaryOp::Mul => a.mul(b),
+                BinaryOp::Div => a.div(b),
+            }
+        })
+        .collect();
+    ValueRange { components }
+}
+
+fn convert_range(from: &ValueRange, from_ty: IrType, to_ty: IrType) -> ValueRange {
+    match (from_ty, to_ty) {
+        (IrType::Float, _) => ValueRange::splat(from.component(0), component_count(to_ty)),
+        (IrType::Vec3, IrType::Color) => {
+            let mut components = from.components.clone();
+            components.push(Interval::point(1.0));
+            ValueRange { components }
+        }
+        _ => ValueRange::unknown(to_ty),
+    }
+}
+
+fn call_range(
+    function: &str,
+    args: &[&ValueRange],
+    ty: IrType,
+    warn: &mut impl FnMut(String),
+) -> ValueRange {
+    let count = component_count(ty);
+    let elementwise = |f: &dyn Fn(&[Interval]) -> Interval| ValueRange {
+        components: (0..count)
+            .map(|i| {
+                let components: Vec<Interval> = args.iter().map(|arg| arg.component(i)).collect();
+                f(&components)
+            })
+            .collect(),
+    };
+    let any_component = |arg: usize, f: &dyn Fn(Interval) -> bool| {
+        args.get(arg)
+            .is_some_and(|range| range.components.iter().any(|c| c.is_bounded() && f(*c)))
+    };
+
+    match function {
+        "pow" => {
+            if any_component(0, &|c| c.min < 0.0) {
+                warn("pow with a possibly negative base can produce NaN".to_string());
+            }
+            ValueRange::unknown(ty)
+        }
+        "sqrt" | "inverseSqrt" => {
+            if any_component(0, &|c| c.min < 0.0) {
+                warn(format!(
+                    "{} of a possibly negative value produces NaN",
+                    function
+                ));
+            }
+            if function == "sqrt" {
+                elementwise(&|c| {
+                    Interval::from_bounds(&[c[0].min.max(0.0).sqrt(), c[0].max.max(0.0).sqrt()])
+                })
+            } else {
+                ValueRange::unknown(ty)
+            }
+        }
+        "log" | "log2" => {
+            if any_component(0, &|c| c.min <= 0.0) {
+                warn(format!(
+                    "{} of a possibly non-positive value produces NaN or infinity",
+                    function
+                ));
+            }
+            ValueRange::unknown(ty)
+        }
+        "abs" => elementwise(&|c| {
+            if c[0].min >= 0.0 {
+                c[0]
+            } else if c[0].max <= 0.0 {
+                Interval::new(-c[0].max, -c[0].min)
+            } else {
+                Interval::new(0.0, c[0].max.max(-c[0].min))
+            }
+        }),
+        "min" if args.len() == 2 => {
+            elementwise(&|c| Interval::new(c[0].min.min(c[1].min), c[0].max.min(c[1].max)))
+        }
+        "max" if args.len() == 2 => {
+            elementwise(&|c| Interval::new(c[0].min.max(c[1].min), c[0].max.max(c[1].max)))
+        }
+        // clamp(x, low, high) is min(max(x, low), high)
+        "clamp" if args.len() == 3 => elementwise(&|c| {
+            Interval::new(
+                c[0].min.max(c[1].min).min(c[2].min),
+                c[0].max.max(c[1].max).min(c[2].max),
+            )
+        }),
+        "saturate" => {
+            elementwise(&|c| Interval::new(c[0].min.clamp(0.0, 1.0), c[0].max.clamp(0.0, 1.0)))
+        }
+        "sin" | "cos" => ValueRange::splat(Interval::new(-1.0, 1.0), count),
+        "f32" => elementwise(&|c| c[0]),
+        "vec2<f32>" | "vec3<f32>" | "vec4<f32>" if args.len() > 1 => {
+            // Constructors concatenate their arguments' components
+            let components: Vec<Interval> = args
+                .iter()
+                .flat_map(|arg| arg.components.iter().copied())
+                .collect();
+            if components.len() == count {
+                ValueRange { components }
+            } else {
+                ValueRange::unknown(ty)
+            }
+        }
+        "vec2<f32>" | "vec3<f32>" | "vec4<f32>" => elementwise(&|c| c[0]),
+        _ => ValueRange::unknown(ty),
+    }
+}
@@ -0,0 +1,743 @@
+//! Data-driven node definitions.
+//!
+//! A `NodeRegistry` maps definition ids (e.g. `"math.add"`) to `NodeDefinition`s
+//! describing a node's sockets, how its output types are inferred and how it is
+//! lowered to IR. Type propagation and lowering resolve every non-structural
+//! node through the registry instead of matching on `NodeKind`.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use glam::Vec2;
+use thiserror::Error;
+
+use crate::matrix::{column_type, inverse_helper};
+use crate::{
+    BinaryOp, Graph, InputDef, IrHelper, Literal, MathOp, Node, NodeId, NodeKind, NodeProperties,
+    PropertyError, PropertySpec, PropertyValue, SocketId, SocketMetadata, TypeError, ValueType,
+};
+
+#[derive(Debug, Error)]
+pub enum RegistryError {
+    #[error("node definition {0:?} is already registered")]
+    DuplicateDefinition(String),
+
+    #[error("unknown node definition {0:?}")]
+    UnknownDefinition(String),
+}
+
+/// Declared socket of a node definition
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketSpec {
+    pub name: String,
+    pub value_type: ValueType,
+    pub default: Option<Literal>,
+    /// Ranges, subtype and tooltip of inputs; unused on outputs
+    pub metadata: SocketMetadata,
+}
+
+impl SocketSpec {
+    /// Input that must be connected
+    pub fn required(name: impl Into<String>, value_type: ValueType) -> Self {
+        Self {
+            name: name.into(),
+            value_type,
+            default: None,
+            metadata: SocketMetadata::default(),
+        }
+    }
+
+    /// Input that falls back to `default` when unconnected
+    pub fn optional(name: impl Into<String>, value_type: ValueType, default: Literal) -> Self {
+        Self {
+            name: name.into(),
+            value_type,
+            default: Some(default),
+            metadata: SocketMetadata::default(),
+        }
+    }
+
+    /// Output socket; `default` is only used by constant-producing nodes
+    pub fn output(name: impl Into<String>, value_type: ValueType) -> Self {
+        Self::required(name, value_type)
+    }
+
+    pub fn with_metadata(mut self, metadata: SocketMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    fn input_def(&self) -> InputDef {
+        let def = match &self.default {
+            Some(default) => {
+                InputDef::optional(self.name.clone(), self.value_type, default.clone())
+            }
+            None => InputDef::required(self.name.clone(), self.value_type),
+        };
+        def.with_metadata(self.metadata.clone())
+    }
+}
+
+/// How a definition derives output types from input types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeRule {
+    /// Outputs have the types declared in the definition
+    Declared,
+    /// All inputs must share one type, which every output takes
+    Unify,
+    /// Output `i` takes the type of input `i`
+    PassThrough,
+    /// Outputs take the type of `A op B`, allowing matrix products
+    Arithmetic(BinaryOp),
+}
+
+impl TypeRule {
+    /// Infer one type per output of a node built from `definition`
+    pub fn infer(
+        &self,
+        definition: &NodeDefinition,
+        input_types: &[ValueType],
+        output_count: usize,
+    ) -> Result<Vec<ValueType>, TypeError> {
+        self.infer_with_arity(
+            definition,
+            definition.inputs.len(),
+            input_types,
+            output_count,
+        )
+    }
+
+    fn infer_with_arity(
+        &self,
+        definition: &NodeDefinition,
+        expected_inputs: usize,
+        input_types: &[ValueType],
+        output_count: usize,
+    ) -> Result<Vec<ValueType>, TypeError> {
+        if input_types.len() != expected_inputs {
+            return Err(TypeError::ArityMismatch {
+                expected: expected_inputs,
+                found: input_types.len(),
+            });
+        }
+
+        match self {
+            TypeRule::Declared => Ok(definition
+                .outputs
+                .iter()
+                .map(|spec| spec.value_type)
+                .take(output_count)
+                .collect()),
+
+            TypeRule::Unify => {
+                let unified = crate::unify(input_types)?;
+                Ok(vec![unified; output_count])
+            }
+
+            TypeRule::PassThrough => {
+                if output_count > input_types.len() {
+                    return Err(TypeError::ArityMismatch {
+                        expected: input_types.len(),
+                        found: output_count,
+                    });
+                }
+                Ok(input_types[..output_count].to_vec())
+            }
+
+            TypeRule::Arithmetic(op) => {
+                if input_types.len() != 2 {
+                    return Err(TypeError::ArityMismatch {
+                        expected: 2,
+                        found: input_types.len(),
+                    });
+                }
+                let result = crate::binary_result_type(*op, input_types[0], input_types[1])?;
+                Ok(vec![result; output_count])
+            }
+        }
+    }
+}
+
+/// How a definition is lowered to IR instructions
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoweringRule {
+    /// Emit the first output's default (or a zero value) as a constant
+    Constant,
+    /// Emit a binary instruction over the first two inputs
+    Binary(BinaryOp),
+    /// Forward input `i` to output `i` without emitting instructions
+    PassThrough,
+    /// Read the program input `name`, supplied to `main` by the caller
+    Input(String),
+    /// Call `functions[i]` with every input to produce output `i`
+    Call {
+        functions: Vec<String>,
+        helpers: Vec<IrHelper>,
+    },
+}
+
+/// Behavior of a definition while one of its enum properties has a value
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyVariant {
+    pub property: String,
+    pub value: String,
+    pub type_rule: TypeRule,
+    pub lowering: LoweringRule,
+    /// Indices of the inputs in use; the others are hidden. `None` keeps all
+    pub inputs: Option<Vec<usize>>,
+}
+
+/// Description of a node type available to the editor and compiler
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeDefinition {
+    pub id: String,
+    pub category: String,
+    pub display_name: String,
+    pub inputs: Vec<SocketSpec>,
+    pub outputs: Vec<SocketSpec>,
+    pub properties: Vec<PropertySpec>,
+    /// Overrides of the rules below; the first variant matching the node's
+    /// properties applies
+    pub variants: Vec<PropertyVariant>,
+    pub type_rule: TypeRule,
+    pub lowering: LoweringRule,
+}
+
+impl NodeDefinition {
+    /// `NodeKind` for nodes created from this definition
+    pub fn node_kind(&self) -> NodeKind {
+        NodeKind::from_definition_id(&self.id)
+    }
+
+    pub fn property_spec(&self, name: &str) -> Option<&PropertySpec> {
+        self.properties.iter().find(|spec| spec.name == name)
+    }
+
+    /// Value of property `name` in a node's `properties`, falling back to the
+    /// declared default
+    pub fn property<'a>(
+        &'a self,
+        properties: &'a NodeProperties,
+        name: &str,
+    ) -> Option<&'a PropertyValue> {
+        let spec = self.property_spec(name)?;
+        Some(properties.get(name).unwrap_or(&spec.default))
+    }
+
+    /// Every declared property with its value in `properties` or its default
+    pub fn resolved_properties(&self, properties: &NodeProperties) -> NodeProperties {
+        self.properties
+            .iter()
+            .map(|spec| {
+                let value = properties.get(&spec.name).unwrap_or(&spec.default);
+                (spec.name.clone(), value.clone())
+            })
+            .collect()
+    }
+
+    /// Check that `value` may be assigned to property `name`
+    pub fn check_property(&self, name: &str, value: &PropertyValue) -> Result<(), PropertyError> {
+        self.property_spec(name)
+            .ok_or_else(|| PropertyError::UnknownProperty(name.to_string()))?
+            .check(value)
+    }
+
+    fn variant(&self, properties: &NodeProperties) -> Option<&PropertyVariant> {
+        self.variants.iter().find(|variant| {
+            matches!(
+                self.property(properties, &variant.property),
+                Some(PropertyValue::Enum(value)) if *value == variant.value
+            )
+        })
+    }
+
+    /// Type rule applying to a node with `properties`
+    pub fn type_rule_for(&self, properties: &NodeProperties) -> TypeRule {
+        self.variant(properties)
+            .map_or(self.type_rule, |variant| variant.type_rule)
+    }
+
+    /// Lowering rule applying to a node with `properties`
+    pub fn lowering_for(&self, properties: &NodeProperties) -> &LoweringRule {
+        self.variant(properties)
+            .map_or(&self.lowering, |variant| &variant.lowering)
+    }
+
+    /// Indices of the inputs a node with `properties` reads; the remaining
+    /// inputs are hidden
+    pub fn active_inputs(&self, properties: &NodeProperties) -> Vec<usize> {
+        match self
+            .variant(properties)
+            .and_then(|variant| variant.inputs.clone())
+        {
+            Some(inputs) => inputs,
+            None => (0..self.inputs.len()).collect(),
+        }
+    }
+
+    /// Infer the output types of `node` from the types of its active inputs
+    pub fn infer_output_types(
+        &self,
+        node: &Node,
+        input_types: &[ValueType],
+        output_count: usize,
+    ) -> Result<Vec<ValueType>, TypeError> {
+        let expected = self.active_inputs(&node.properties).len();
+        self.type_rule_for(&node.properties).infer_with_arity(
+            self,
+            expected,
+            input_types,
+            output_count,
+        )
+    }
+}
+
+/// Collection of node definitions, keyed by id
+#[derive(Debug, Clone, Default)]
+pub struct NodeRegistry {
+    definitions: Vec<NodeDefinition>,
+    index: HashMap<String, usize>,
+}
+
+impl NodeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry containing the built-in value, math and utility nodes
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        for definition in builtin_definitions() {
+            registry
+                .register(definition)
+                .expect("built-in definition ids are unique");
+        }
+        registry
+    }
+
+    /// Shared built-in registry used when no registry is supplied
+    pub fn builtin() -> &'static NodeRegistry {
+        static BUILTIN: OnceLock<NodeRegistry> = OnceLock::new();
+        BUILTIN.get_or_init(NodeRegistry::with_builtins)
+    }
+
+    pub fn register(&mut self, definition: NodeDefinition) -> Result<(), RegistryError> {
+        if self.index.contains_key(&definition.id) {
+            return Err(RegistryError::DuplicateDefinition(definition.id));
+        }
+        self.index
+            .insert(definition.id.clone(), self.definitions.len());
+        self.definitions.push(definition);
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<&NodeDefinition> {
+        self.index.get(id).map(|&index| &self.definitions[index])
+    }
+
+    /// Definition backing a node kind; `None` for structural kinds and unknown ids
+    pub fn resolve(&self, kind: &NodeKind) -> Option<&NodeDefinition> {
+        kind.definition_id().and_then(|id| self.get(&id))
+    }
+
+    /// Input sockets `node` reads, skipping inputs hidden by its properties
+    ///
+    /// Nodes without a definition read every input.
+    pub fn active_input_sockets(&self, node: &Node) -> Vec<SocketId> {
+        match self.resolve(&node.kind) {
+            Some(definition) => definition
+                .active_inputs(&node.properties)
+                .into_iter()
+                .filter_map(|index| node.inputs.get(index).copied())
+                .collect(),
+            None => node.inputs.clone(),
+        }
+    }
+
+    /// All definitions in registration order
+    pub fn definitions(&self) -> impl Iterator<Item = &NodeDefinition> {
+        self.definitions.iter()
+    }
+
+    /// Distinct categories in registration order
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = Vec::new();
+        for definition in &self.definitions {
+            if !categories.contains(&definition.category.as_str()) {
+                categories.push(&definition.category);
+            }
+        }
+        categories
+    }
+
+    pub fn definitions_in_category<'a>(
+        &'a self,
+        category: &'a str,
+    ) -> impl Iterator<Item = &'a NodeDefinition> + 'a {
+        self.definitions
+            .iter()
+            .filter(move |definition| definition.category == category)
+    }
+
+    /// Add a node built from definition `id` to `graph`
+    pub fn instantiate(
+        &self,
+        graph: &mut Graph,
+        id: &str,
+        position: Vec2,
+    ) -> Result<NodeId, RegistryError> {
+        let definition = self
+            .get(id)
+            .ok_or_else(|| RegistryError::UnknownDefinition(id.to_string()))?;
+
+        let inputs = definition
+            .inputs
+            .iter()
+            .map(SocketSpec::input_def)
+            .collect();
+        let outputs = definition
+            .outputs
+            .iter()
+            .map(|spec| (spec.name.clone(), spec.value_type))
+            .collect();
+
+        Ok(graph.add_node_with_config(definition.node_kind(), position, inputs, outputs))
+    }
+}
+
+impl NodeKind {
+    /// Registry id of the definition backing this kind, if any
+    pub fn definition_id(&self) -> Option<String> {
+        match self {
+            NodeKind::Value(value_type) => Some(format!("value.{}", value_type_key(*value_type))),
+            NodeKind::Math(op) => Some(format!("math.{}", math_op_key(op))),
+            NodeKind::Registered(id) => Some(id.clone()),
+            NodeKind::Group(_)
+            | NodeKind::GroupInput
+            | NodeKind::GroupOutput
+            | NodeKind::Code(_) => None,
+        }
+    }
+
+    /// Kind for a definition id, using the dedicated variants for built-ins
+    pub fn from_definition_id(id: &str) -> NodeKind {
+        if let Some(key) = id.strip_prefix("value.") {
+            if let Some(value_type) = ALL_VALUE_TYPES
+                .iter()
+                .find(|ty| value_type_key(**ty) == key)
+            {
+                return NodeKind::Value(*value_type);
+            }
+        }
+        if let Some(key) = id.strip_prefix("math.") {
+            if let Some(op) = ALL_MATH_OPS.iter().find(|op| math_op_key(op) == key) {
+                return NodeKind::Math(op.clone());
+            }
+        }
+        NodeKind::Registered(id.to_string())
+    }
+}
+
+const ALL_VALUE_TYPES: [ValueType; 10] = [
+    ValueType::Float,
+    ValueType::Vec2,
+    ValueType::Vec3,
+    ValueType::Vec4,
+    ValueType::Bool,
+    ValueType::Int,
+    ValueType::Color,
+    ValueType::Mat2,
+    ValueType::Mat3,
+    ValueType::Mat4,
+];
+
+const ALL_MATRIX_TYPES: [ValueType; 3] = [ValueType::Mat2, ValueType::Mat3, ValueType::Mat4];
+
+const ALL_MATH_OPS: [MathOp; 4] = [
+    MathOp::Add,
+    MathOp::Subtract,
+    MathOp::Multiply,
+    MathOp::Divide,
+];
+
+fn value_type_key(value_type: ValueType) -> &'static str {
+    match value_type {
+        ValueType::Float => "float",
+        ValueType::Vec2 => "vec2",
+        ValueType::Vec3 => "vec3",
+        ValueType::Vec4 => "vec4",
+        ValueType::Bool => "bool",
+        ValueType::Int => "int",
+        ValueType::Color => "color",
+        ValueType::Mat2 => "mat2",
+        ValueType::Mat3 => "mat3",
+        ValueType::Mat4 => "mat4",
+    }
+}
+
+fn math_op_key(op: &MathOp) -> &'static str {
+    match op {
+        MathOp::Add => "add",
+        MathOp::Subtract => "subtract",
+        MathOp::Multiply => "multiply",
+        MathOp::Divide => "divide",
+    }
+}
+
+/// Zero value for a type, used for value nodes and missing constants
+pub(crate) fn zero_literal(value_type: ValueType) -> Literal {
+    match value_type {
+        ValueType::Float => Literal::Float(0.0),
+        ValueType::Vec2 => Literal::Vec2([0.0, 0.0]),
+        ValueType::Vec3 => Literal::Vec3([0.0, 0.0, 0.0]),
+        ValueType::Vec4 => Literal::Vec4([0.0, 0.0, 0.0, 0.0]),
+        ValueType::Bool => Literal::Bool(false),
+        ValueType::Int => Literal::Int(0),
+        ValueType::Color => Literal::Vec4([0.0, 0.0, 0.0, 1.0]),
+        ValueType::Mat2 => Literal::Mat2([0.0; 4]),
+        ValueType::Mat3 => Literal::Mat3([0.0; 9]),
+        ValueType::Mat4 => Literal::Mat4([0.0; 16]),
+    }
+}
+
+fn builtin_definitions() -> Vec<NodeDefinition> {
+    let mut definitions = Vec::new();
+
+    for value_type in ALL_VALUE_TYPES {
+        let key = value_type_key(value_type);
+        let mut output = SocketSpec::output("Value", value_type);
+        output.default =
+            Some(Literal::identity(value_type).unwrap_or_else(|| zero_literal(value_type)));
+
+        definitions.push(NodeDefinition {
+            id: format!("value.{}", key),
+            category: "Input".to_string(),
+            display_name: format!("{:?}", value_type),
+            inputs: vec![],
+            outputs: vec![output],
+            properties: vec![],
+            variants: vec![],
+            type_rule: TypeRule::Declared,
+            lowering: LoweringRule::Constant,
+        });
+    }
+
+    for op in ALL_MATH_OPS {
+        let (display_name, binary_op, b_default) = match op {
+            MathOp::Add => ("Add", BinaryOp::Add, 0.0),
+            MathOp::Subtract => ("Subtract", BinaryOp::Sub, 0.0),
+            MathOp::Multiply => ("Multiply", BinaryOp::Mul, 1.0),
+            MathOp::Divide => ("Divide", BinaryOp::Div, 1.0),
+        };
+
+        definitions.push(NodeDefinition {
+            id: format!("math.{}", math_op_key(&op)),
+            category: "Math".to_string(),
+            display_name: display_name.to_string(),
+            inputs: vec![
+                SocketSpec::optional("A", ValueType::Float, Literal::Float(0.0)),
+                SocketSpec::optional("B", ValueType::Float, Literal::Float(b_default)),
+            ],
+            outputs: vec![SocketSpec::output("Result", ValueType::Float)],
+            properties: vec![],
+            variants: vec![],
+            type_rule: TypeRule::Arithmetic(binary_op),
+            lowering: LoweringRule::Binary(binary_op),
+        });
+    }
+
+    definitions.push(math_definition());
+
+    for matrix in ALL_MATRIX_TYPES {
+        definitions.extend(matrix_definitions(matrix));
+    }
+
+    definitions.push(NodeDefinition {
+        id: "input.uv".to_string(),
+        category: "Input".to_string(),
+        display_name: "UV".to_string(),
+        inputs: vec![],
+        outputs: vec![SocketSpec::output("UV", ValueType::Vec2)],
+        properties: vec![],
+        variants: vec![],
+        type_rule: TypeRule::Declared,
+        lowering: LoweringRule::Input("uv".to_string()),
+    });
+
+    // Links need matching socket types, so each value type has its own
+    // reroute; the float one keeps the plain id
+    for value_type in ALL_VALUE_TYPES {
+        let id = match value_type {
+            ValueType::Float => "utility.reroute".to_string(),
+            _ => format!("utility.reroute.{}", value_type_key(value_type)),
+        };
+        definitions.push(NodeDefinition {
+            id,
+            category: "Layout".to_string(),
+            display_name: "Reroute".to_string(),
+            inputs: vec![SocketSpec::required("Input", value_type)],
+            outputs: vec![SocketSpec::output("Output", value_type)],
+            properties: vec![],
+            variants: vec![],
+            type_rule: TypeRule::PassThrough,
+            lowering: LoweringRule::PassThrough,
+        });
+    }
+
+    definitions
+}
+
+/// Math node whose operation is a property, switchable in place
+///
+/// Single-input operations hide the second input.
+fn math_definition() -> NodeDefinition {
+    let binary = |op: BinaryOp| (TypeRule::Arithmetic(op), LoweringRule::Binary(op));
+    let call = |function: &str| {
+        (
+            TypeRule::Unify,
+            LoweringRule::Call {
+                functions: vec![function.to_string()],
+                helpers: vec![],
+            },
+        )
+    };
+    let operations = [
+        ("add", binary(BinaryOp::Add), 2),
+        ("subtract", binary(BinaryOp::Sub), 2),
+        ("multiply", binary(BinaryOp::Mul), 2),
+        ("divide", binary(BinaryOp::Div), 2),
+        ("minimum", call("min"), 2),
+        ("maximum", call("max"), 2),
+        ("power", call("pow"), 2),
+        ("absolute", call("abs"), 1),
+        ("sqrt", call("sqrt"), 1),
+    ];
+
+    let options: Vec<&str> = operations.iter().map(|(name, ..)| *name).collect();
+    let variants = operations
+        .iter()
+        .map(|(name, (type_rule, lowering), arity)| PropertyVariant {
+            property: "operation".to_string(),
+            value: name.to_string(),
+            type_rule: *type_rule,
+            lowering: lowering.clone(),
+            inputs: Some((0..*arity).collect()),
+        })
+        .collect();
+    let (type_rule, lowering) = binary(BinaryOp::Add);
+
+    NodeDefinition {
+        id: "math.math".to_string(),
+        category: "Math".to_string(),
+        display_name: "Math".to_string(),
+        inputs: vec![
+            SocketSpec::optional("A", ValueType::Float, Literal::Float(0.0)),
+            // Non-zero so switching to divide does not divide by zero
+            SocketSpec::optional("B", ValueType::Float, Literal::Float(0.5)),
+        ],
+        outputs: vec![SocketSpec::output("Result", ValueType::Float)],
+        properties: vec![PropertySpec::enumeration("operation", &options, "add")],
+        variants,
+        type_rule,
+        lowering,
+    }
+}
+
+/// Construction, transpose, inverse and product nodes for one matrix size
+fn matrix_definitions(matrix: ValueType) -> Vec<NodeDefinition> {
+    let key = value_type_key(matrix);
+    let size = matrix.matrix_size().expect("matrix type") as usize;
+    let column = column_type(matrix).expect("matrix type");
+    let identity = Literal::identity(matrix).expect("matrix type");
+    let wgsl_name = match matrix {
+        ValueType::Mat2 => "mat2x2<f32>",
+        ValueType::Mat3 => "mat3x3<f32>",
+        _ => "mat4x4<f32>",
+    };
+    let matrix_input = |name: &str| SocketSpec::optional(name, matrix, identity.clone());
+
+    let columns = (0..size)
+        .map(|index| {
+            let mut unit = [0.0; 4];
+            unit[index] = 1.0;
+            let default = match column {
+                ValueType::Vec2 => Literal::Vec2([unit[0], unit[1]]),
+                ValueType::Vec3 => Literal::Vec3([unit[0], unit[1], unit[2]]),
+                _ => Literal::Vec4(unit),
+            };
+            SocketSpec::optional(format!("Column {}", index), column, default)
+        })
+        .collect();
+
+    let inverse = inverse_helper(matrix).expect("matrix type");
+
+    vec![
+        NodeDefinition {
+            id: format!("matrix.combine_{}", key),
+            category: "Matrix".to_string(),
+            display_name: format!("Combine {:?}", matrix),
+            inputs: columns,
+            outputs: vec![SocketSpec::output("Matrix", matrix)],
+            properties: vec![],
+            variants: vec![],
+            type_rule: TypeRule::Declared,
+            lowering: LoweringRule::Call {
+                functions: vec![wgsl_name.to_string()],
+                helpers: vec![],
+            },
+        },
+        NodeDefinition {
+            id: format!("matrix.transpose_{}", key),
+            category: "Matrix".to_string(),
+            display_name: format!("Transpose {:?}", matrix),
+            inputs: vec![matrix_input("Matrix")],
+            outputs: vec![SocketSpec::output("Matrix", matrix)],
+            properties: vec![],
+            variants: vec![],
+            type_rule: TypeRule::Declared,
+            lowering: LoweringRule::Call {
+                functions: vec!["transpose".to_string()],
+                helpers: vec![],
+            },
+        },
+        NodeDefinition {
+            id: format!("matrix.inverse_{}", key),
+            category: "Matrix".to_string(),
+            display_name: format!("Inverse {:?}", matrix),
+            inputs: vec![matrix_input("Matrix")],
+            outputs: vec![SocketSpec::output("Matrix", matrix)],
+            properties: vec![],
+            variants: vec![],
+            type_rule: TypeRule::Declared,
+            lowering: LoweringRule::Call {
+                functions: vec![inverse.name.clone()],
+                helpers: vec![inverse],
+            },
+        },
+        NodeDefinition {
+            id: format!("matrix.multiply_{}", key),
+            category: "Matrix".to_string(),
+            display_name: format!("Multiply {:?}", matrix),
+            inputs: vec![matrix_input("A"), matrix_input("B")],
+            outputs: vec![SocketSpec::output("Matrix", matrix)],
+            properties: vec![],
+            variants: vec![],
+            type_rule: TypeRule::Arithmetic(BinaryOp::Mul),
+            lowering: LoweringRule::Binary(BinaryOp::Mul),
+        },
+        NodeDefinition {
+            id: format!("matrix.transform_{}", value_type_key(column)),
+            category: "Matrix".to_string(),
+            display_name: format!("Transform {:?}", column),
+            inputs: vec![
+                matrix_input("Matrix"),
+                SocketSpec::optional("Vector", column, zero_literal(column)),
+            ],
+            outputs: vec![SocketSpec::output("Vector", column)],
+            properties: vec![],
+            variants: vec![],
+            type_rule: TypeRule::Arithmetic(BinaryOp::Mul),
+            lowering: LoweringRule::Binary(BinaryOp::Mul),
+        },
+    ]
+}
@@ -0,0 +1,283 @@
+//! Algebraic simplification of IR programs.
+//!
+//! A single forward pass applies peephole rules to each instruction, with
+//! operands already simplified:
+//! - identities: `x + 0`, `0 + x`, `x - 0`, `x * 1`, `1 * x`, `x / 1` => `x`
+//! - strength reduction: `x / c` => `x * (1 / c)` for float constants
+//! - double negation: `-(-x)` => `x`, where negation is `0 - x` or `x * -1`
+//! - conversions: same-type conversions are dropped, matrix resize chains
+//!   are fused, and repeated conversions of one value are shared
+//!
+//! Instructions no longer reachable from the program outputs are removed
+//! afterwards. Programs without outputs keep every instruction.
+
+use crate::{BinaryOp, IrInst, IrProgram, IrType, Literal, ValueId};
+
+/// Simplify `ir`, preserving its outputs
+pub fn simplify(ir: IrProgram) -> IrProgram {
+    let mut instructions: Vec<IrInst> = Vec::with_capacity(ir.instructions.len());
+    let mut origins = Vec::with_capacity(ir.instructions.len());
+    // Old value id => id of the equivalent value in `instructions`
+    let mut remap: Vec<ValueId> = Vec::with_capacity(ir.instructions.len());
+
+    for (index, inst) in ir.instructions.into_iter().enumerate() {
+        let origin = ir.origins.get(index).copied().flatten();
+        let inst = inst.map_operands(|value| remap[value.0 as usize]);
+
+        let value = match rewrite(&instructions, &inst) {
+            Rewrite::Alias(value) => value,
+            Rewrite::Replace(replacement) => {
+                for inst in replacement {
+                    instructions.push(inst);
+                    origins.push(origin);
+                }
+                ValueId((instructions.len() - 1) as u32)
+            }
+            Rewrite::Keep => {
+                instructions.push(inst);
+                origins.push(origin);
+                ValueId((instructions.len() - 1) as u32)
+            }
+        };
+        remap.push(value);
+    }
+
+    let mut outputs = ir.outputs;
+    for output in &mut outputs {
+        output.value = remap[output.value.0 as usize];
+    }
+
+    let program = IrProgram {
+        instructions,
+        helpers: ir.helpers,
+        outputs,
+        origins,
+    };
+
+    if program.outputs.is_empty() {
+        program
+    } else {
+        remove_dead_instructions(program)
+    }
+}
+
+enum Rewrite {
+    /// Use an existing value instead of the instruction
+    Alias(ValueId),
+    /// Emit these instructions; the last one produces the value
+    Replace(Vec<IrInst>),
+    Keep,
+}
+
+fn rewrite(instructions: &[IrInst], inst: &IrInst) -> Rewrite {
+    match inst {
+        IrInst::Binary { op, lhs, rhs, ty } => rewrite_binary(instructions, *op, *lhs, *rhs, *ty),
+        IrInst::Convert {
+            from,
+            from_ty,
+            to_ty,
+        } => rewrite_convert(instructions, *from, *from_ty, *to_ty),
+        _ => Rewrite::Keep,
+    }
+}
+
+fn rewrite_binary(
+    instructions: &[IrInst],
+    op: BinaryOp,
+    lhs: ValueId,
+    rhs: ValueId,
+    ty: IrType,
+) -> Rewrite {
+    let ty_of = |value: ValueId| instructions[value.0 as usize].result_type();
+    let lhs_constant = splat_constant(instructions, lhs);
+    let rhs_constant = splat_constant(instructions, rhs);
+
+    // Identities only apply when the kept operand already has the result type
+    match op {
+        BinaryOp::Add if rhs_constant == Some(0.0) && ty_of(lhs) == ty => {
+            return Rewrite::Alias(lhs);
+        }
+        BinaryOp::Add if lhs_constant == Some(0.0) && ty_of(rhs) == ty => {
+            return Rewrite::Alias(rhs);
+        }
+        BinaryOp::Sub if rhs_constant == Some(0.0) && ty_of(lhs) == ty => {
+            return Rewrite::Alias(lhs);
+        }
+        BinaryOp::Mul if rhs_constant == Some(1.0) && ty_of(lhs) == ty => {
+            return Rewrite::Alias(lhs);
+        }
+        BinaryOp::Mul if lhs_constant == Some(1.0) && ty_of(rhs) == ty => {
+            return Rewrite::Alias(rhs);
+        }
+        BinaryOp::Div if rhs_constant == Some(1.0) && ty_of(lhs) == ty => {
+            return Rewrite::Alias(lhs);
+        }
+        _ => {}
+    }
+
+    let negation = IrInst::Binary { op, lhs, rhs, ty };
+    if let Some(inner) = negated(instructions, &negation) {
+        if let Some(value) = negated(instructions, &instructions[inner.0 as usize]) {
+            if ty_of(value) == ty {
+                return Rewrite::Alias(value);
+            }
+        }
+    }
+
+    if op == BinaryOp::Div {
+        if let Some(IrInst::Constant {
+            value,
+            ty: constant_ty,
+        }) = instructions.get(rhs.0 as usize)
+        {
+            if let Some(reciprocal) = reciprocal(value) {
+                return Rewrite::Replace(vec![
+                    IrInst::Constant {
+                        value: reciprocal,
+                        ty: *constant_ty,
+                    },
+                    IrInst::Binary {
+                        op: BinaryOp::Mul,
+                        lhs,
+                        rhs: ValueId(instructions.len() as u32),
+                        ty,
+                    },
+                ]);
+            }
+        }
+    }
+
+    Rewrite::Keep
+}
+
+fn rewrite_convert(
+    instructions: &[IrInst],
+    from: ValueId,
+    from_ty: IrType,
+    to_ty: IrType,
+) -> Rewrite {
+    if from_ty == to_ty {
+        return Rewrite::Alias(from);
+    }
+
+    // Share an identical conversion emitted earlier
+    let same = IrInst::Convert {
+        from,
+        from_ty,
+        to_ty,
+    };
+    if let Some(existing) = instructions.iter().position(|inst| *inst == same) {
+        return Rewrite::Alias(ValueId(existing as u32));
+    }
+
+    if let IrInst::Convert {
+        from: source,
+        from_ty: source_ty,
+        ..
+    } = &instructions[from.0 as usize]
+    {
+        // A resize chain A => B => C equals A => C unless B dropped columns
+        // that C needs back
+        if let (Some(a), Some(b), Some(c)) = (
+            source_ty.matrix_size(),
+            from_ty.matrix_size(),
+            to_ty.matrix_size(),
+        ) {
+            if b >= a.min(c) {
+                if a == c {
+                    return Rewrite::Alias(*source);
+                }
+                return rewrite_convert(instructions, *source, *source_ty, to_ty);
+            }
+        }
+    }
+
+    Rewrite::Replace(vec![same])
+}
+
+/// `x` if `inst` computes `0 - x`, `x * -1` or `-1 * x`
+fn negated(instructions: &[IrInst], inst: &IrInst) -> Option<ValueId> {
+    let IrInst::Binary { op, lhs, rhs, ty } = inst else {
+        return None;
+    };
+    let operand = match op {
+        BinaryOp::Sub if splat_constant(instructions, *lhs) == Some(0.0) => *rhs,
+        BinaryOp::Mul if splat_constant(instructions, *rhs) == Some(-1.0) => *lhs,
+        BinaryOp::Mul if splat_constant(instructions, *lhs) == Some(-1.0) => *rhs,
+        _ => return None,
+    };
+    (instructions[operand.0 as usize].result_type() == *ty).then_some(operand)
+}
+
+/// Value of a constant whose components are all equal
+fn splat_constant(instructions: &[IrInst], value: ValueId) -> Option<f64> {
+    let IrInst::Constant { value, .. } = instructions.get(value.0 as usize)? else {
+        return None;
+    };
+    let components: Vec<f64> = match value {
+        Literal::Float(v) => vec![*v as f64],
+        Literal::Int(v) => vec![*v as f64],
+        Literal::Vec2(v) => v.iter().map(|c| *c as f64).collect(),
+        Literal::Vec3(v) => v.iter().map(|c| *c as f64).collect(),
+        Literal::Vec4(v) => v.iter().map(|c| *c as f64).collect(),
+        Literal::Bool(_) | Literal::Mat2(_) | Literal::Mat3(_) | Literal::Mat4(_) => return None,
+    };
+    let first = components[0];
+    components.iter().all(|c| *c == first).then_some(first)
+}
+
+/// `1 / literal` for float literals without zero components
+fn reciprocal(literal: &Literal) -> Option<Literal> {
+    let invert = |values: &[f32]| -> Option<Vec<f32>> {
+        values
+            .iter()
+            .map(|v| (*v != 0.0).then(|| 1.0 / v))
+            .collect()
+    };
+    Some(match literal {
+        Literal::Float(v) => Literal::Float(invert(&[*v])?[0]),
+        Literal::Vec2(v) => Literal::Vec2(invert(v)?.try_into().ok()?),
+        Literal::Vec3(v) => Literal::Vec3(invert(v)?.try_into().ok()?),
+        Literal::Vec4(v) => Literal::Vec4(invert(v)?.try_into().ok()?),
+        _ => return None,
+    })
+}
+
+/// Drop instructions that no output depends on and renumber the rest
+fn remove_dead_instructions(ir: IrProgram) -> IrProgram {
+    let mut live = vec![false; ir.instructions.len()];
+    for output in &ir.outputs {
+        live[output.value.0 as usize] = true;
+    }
+    for index in (0..ir.instructions.len()).rev() {
+        if live[index] {
+            for operand in ir.instructions[index].operands() {
+                live[operand.0 as usize] = true;
+            }
+        }
+    }
+
+    let mut remap = vec![ValueId(0); ir.instructions.len()];
+    let mut instructions = Vec::new();
+    let mut origins = Vec::new();
+    for (index, inst) in ir.instructions.into_iter().enumerate() {
+        if !live[index] {
+            continue;
+        }
+        remap[index] = ValueId(instructions.len() as u32);
+        instructions.push(inst.map_operands(|value| remap[value.0 as usize]));
+        origins.push(ir.origins.get(index).copied().flatten());
+    }
+
+    let mut outputs = ir.outputs;
+    for output in &mut outputs {
+        output.value = remap[output.value.0 as usize];
+    }
+
+    IrProgram {
+        instructions,
+        helpers: ir.helpers,
+        outputs,
+        origins,
+    }
+}
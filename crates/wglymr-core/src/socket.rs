@@ -11,10 +11,123 @@ pub enum SocketDirection {
     Output,
 }
 
+/// What an input value means, used to pick an inspector widget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum SocketSubtype {
+    #[default]
+    None,
+    /// Blend amount, usually within 0..1
+    Factor,
+    /// Angle in radians
+    Angle,
+    Distance,
+    Color,
+    /// Normalized direction vector
+    Direction,
+}
+
+/// Handling of default values outside the hard range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum OutOfRange {
+    /// Clamp each component into the range
+    #[default]
+    Clamp,
+    /// Refuse the value
+    Reject,
+}
+
+/// Presentation and validation hints for an input socket
+///
+/// Ranges apply per component to float, vector and int values. The hard
+/// range is enforced when a default is set; the soft range only bounds
+/// slider widgets and lies within the hard range.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SocketMetadata {
+    pub hard_min: Option<f32>,
+    pub hard_max: Option<f32>,
+    pub soft_min: Option<f32>,
+    pub soft_max: Option<f32>,
+    pub step: Option<f32>,
+    pub subtype: SocketSubtype,
+    pub unit: Option<String>,
+    pub description: Option<String>,
+    pub out_of_range: OutOfRange,
+}
+
+impl SocketMetadata {
+    pub fn with_range(mut self, min: f32, max: f32) -> Self {
+        self.hard_min = Some(min);
+        self.hard_max = Some(max);
+        self
+    }
+
+    pub fn with_soft_range(mut self, min: f32, max: f32) -> Self {
+        self.soft_min = Some(min);
+        self.soft_max = Some(max);
+        self
+    }
+
+    pub fn with_step(mut self, step: f32) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    pub fn with_subtype(mut self, subtype: SocketSubtype) -> Self {
+        self.subtype = subtype;
+        self
+    }
+
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn with_out_of_range(mut self, out_of_range: OutOfRange) -> Self {
+        self.out_of_range = out_of_range;
+        self
+    }
+
+    /// Whether every component of `value` lies within the hard range
+    pub fn in_range(&self, value: &Literal) -> bool {
+        self.clamp(value) == *value
+    }
+
+    /// `value` with every component clamped into the hard range
+    ///
+    /// Bool and matrix values are returned unchanged.
+    pub fn clamp(&self, value: &Literal) -> Literal {
+        let clamp = |v: f32| {
+            let v = self.hard_min.map_or(v, |min| v.max(min));
+            self.hard_max.map_or(v, |max| v.min(max))
+        };
+        match value {
+            Literal::Float(v) => Literal::Float(clamp(*v)),
+            Literal::Vec2(v) => Literal::Vec2(v.map(clamp)),
+            Literal::Vec3(v) => Literal::Vec3(v.map(clamp)),
+            Literal::Vec4(v) => Literal::Vec4(v.map(clamp)),
+            Literal::Int(v) => {
+                let v = self.hard_min.map_or(*v, |min| (*v).max(min.ceil() as i32));
+                Literal::Int(self.hard_max.map_or(v, |max| v.min(max.floor() as i32)))
+            }
+            Literal::Bool(_) | Literal::Mat2(_) | Literal::Mat3(_) | Literal::Mat4(_) => {
+                value.clone()
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct InputSocketConfig {
     pub optional: bool,
     pub default: Option<Literal>,
+    #[serde(default)]
+    pub metadata: SocketMetadata,
 }
 
 impl InputSocketConfig {
@@ -22,6 +135,7 @@ impl InputSocketConfig {
         Self {
             optional: false,
             default: None,
+            metadata: SocketMetadata::default(),
         }
     }
 
@@ -29,8 +143,14 @@ impl InputSocketConfig {
         Self {
             optional: true,
             default: Some(default),
+            metadata: SocketMetadata::default(),
         }
     }
+
+    pub fn with_metadata(mut self, metadata: SocketMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,237 @@
+//! Cost and complexity report for compiled programs.
+//!
+//! `compile_stats` inspects a lowered program (usually after conversions and
+//! simplification) and reports:
+//! - instruction counts by category
+//! - an estimated cost in abstract ALU units
+//! - live-value pressure, the most values alive at once
+//! - the nodes contributing the most cost
+//!
+//! Costs are rough weights meant for comparing materials, not cycle counts.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ir_conversion::is_matrix_product;
+use crate::{IrInst, IrProgram, IrType, NodeId};
+
+/// Number of nodes listed in `CompileStats::top_nodes`
+pub const TOP_NODE_COUNT: usize = 5;
+
+const CONVERSION_COST: u32 = 1;
+const TRANSCENDENTAL_COST: u32 = 4;
+const HELPER_CALL_COST: u32 = 8;
+const TEXTURE_SAMPLE_COST: u32 = 16;
+
+/// WGSL built-ins costing more than one ALU operation per component
+const TRANSCENDENTALS: &[&str] = &[
+    "sin",
+    "cos",
+    "tan",
+    "asin",
+    "acos",
+    "atan",
+    "atan2",
+    "sinh",
+    "cosh",
+    "tanh",
+    "exp",
+    "exp2",
+    "log",
+    "log2",
+    "pow",
+    "sqrt",
+    "inverseSqrt",
+];
+
+/// WGSL built-ins costing one ALU operation per component
+const BUILTINS: &[&str] = &[
+    "abs",
+    "ceil",
+    "clamp",
+    "cross",
+    "degrees",
+    "determinant",
+    "distance",
+    "dot",
+    "floor",
+    "fma",
+    "fract",
+    "length",
+    "max",
+    "min",
+    "mix",
+    "normalize",
+    "radians",
+    "reflect",
+    "refract",
+    "round",
+    "saturate",
+    "select",
+    "sign",
+    "smoothstep",
+    "step",
+    "transpose",
+    "trunc",
+];
+
+/// Instruction counts by category
+///
+/// Constants and program inputs are free and not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstructionCounts {
+    /// Arithmetic, built-in functions and constructors
+    pub alu: usize,
+    pub conversions: usize,
+    pub texture_samples: usize,
+    /// Calls to generated or imported helper functions
+    pub helper_calls: usize,
+}
+
+/// Estimated cost attributed to one node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeCost {
+    pub node: NodeId,
+    pub cost: u32,
+    pub instructions: usize,
+}
+
+/// Cost and complexity report for one program
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompileStats {
+    pub counts: InstructionCounts,
+    pub estimated_cost: u32,
+    /// Most values alive at the same point of `main`
+    pub max_live_values: usize,
+    /// Most expensive nodes, highest cost first, at most `TOP_NODE_COUNT`
+    pub top_nodes: Vec<NodeCost>,
+}
+
+/// Compute the cost report for `ir`
+pub fn compile_stats(ir: &IrProgram) -> CompileStats {
+    let mut stats = CompileStats::default();
+    let mut node_costs: Vec<NodeCost> = Vec::new();
+
+    for (index, inst) in ir.instructions.iter().enumerate() {
+        let cost = instruction_cost(ir, inst, &mut stats.counts);
+        stats.estimated_cost += cost;
+
+        let Some(node) = ir.origins.get(index).copied().flatten() else {
+            continue;
+        };
+        if matches!(inst, IrInst::Constant { .. } | IrInst::Input { .. }) {
+            continue;
+        }
+        match node_costs.iter_mut().find(|entry| entry.node == node) {
+            Some(entry) => {
+                entry.cost += cost;
+                entry.instructions += 1;
+            }
+            None => node_costs.push(NodeCost {
+                node,
+                cost,
+                instructions: 1,
+            }),
+        }
+    }
+
+    // Stable sort keeps first-lowered nodes first among equal costs
+    node_costs.sort_by_key(|entry| std::cmp::Reverse(entry.cost));
+    node_costs.truncate(TOP_NODE_COUNT);
+    stats.top_nodes = node_costs;
+    stats.max_live_values = max_live_values(ir);
+
+    stats
+}
+
+fn instruction_cost(ir: &IrProgram, inst: &IrInst, counts: &mut InstructionCounts) -> u32 {
+    match inst {
+        IrInst::Constant { .. } | IrInst::Input { .. } => 0,
+
+        IrInst::Binary { op, lhs, rhs, ty } => {
+            counts.alu += 1;
+            let operand_types = ir.value_type(*lhs).zip(ir.value_type(*rhs));
+            match operand_types {
+                Some((lhs_ty, rhs_ty)) if is_matrix_product(*op, lhs_ty, rhs_ty, *ty) => {
+                    // Each result component is a dot product
+                    let size = lhs_ty.matrix_size().or(rhs_ty.matrix_size()).unwrap_or(1);
+                    components(*ty) * size as u32
+                }
+                _ => components(*ty),
+            }
+        }
+
+        IrInst::Convert { .. } => {
+            counts.conversions += 1;
+            CONVERSION_COST
+        }
+
+        IrInst::Call { function, ty, .. } => {
+            if function.starts_with("texture") {
+                counts.texture_samples += 1;
+                TEXTURE_SAMPLE_COST
+            } else if TRANSCENDENTALS.contains(&function.as_str()) {
+                counts.alu += 1;
+                TRANSCENDENTAL_COST * components(*ty)
+            } else if BUILTINS.contains(&function.as_str()) || is_constructor(function) {
+                counts.alu += 1;
+                components(*ty)
+            } else {
+                counts.helper_calls += 1;
+                HELPER_CALL_COST
+            }
+        }
+    }
+}
+
+/// Type constructors such as `f32` and `vec4<f32>`
+fn is_constructor(function: &str) -> bool {
+    matches!(function, "f32" | "i32" | "u32" | "bool")
+        || function.starts_with("vec")
+        || function.starts_with("mat")
+}
+
+fn components(ty: IrType) -> u32 {
+    match ty {
+        IrType::Float | IrType::Bool | IrType::Int => 1,
+        IrType::Vec2 => 2,
+        IrType::Vec3 => 3,
+        IrType::Vec4 | IrType::Color | IrType::Mat2 => 4,
+        IrType::Mat3 => 9,
+        IrType::Mat4 => 16,
+    }
+}
+
+/// Most values alive at once in instruction order
+///
+/// A value is alive from its definition to its last use. Output values stay
+/// alive until the end of `main`.
+fn max_live_values(ir: &IrProgram) -> usize {
+    let end = ir.instructions.len();
+    let mut last_use: Vec<Option<usize>> = vec![None; end];
+
+    for (index, inst) in ir.instructions.iter().enumerate() {
+        for operand in inst.operands() {
+            if let Some(slot) = last_use.get_mut(operand.0 as usize) {
+                *slot = Some(index);
+            }
+        }
+    }
+    for output in &ir.outputs {
+        if let Some(slot) = last_use.get_mut(output.value.0 as usize) {
+            *slot = Some(end);
+        }
+    }
+
+    let mut max = 0;
+    let mut live = 0;
+    // Values dying at each instruction index
+    let mut deaths = vec![0usize; end + 1];
+    for (index, last) in last_use.iter().enumerate() {
+        live += 1;
+        max = max.max(live);
+        deaths[last.unwrap_or(index)] += 1;
+        live -= deaths[index];
+    }
+
+    max
+}
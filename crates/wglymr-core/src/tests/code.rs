@@ -0,0 +1,163 @@
+use glam::Vec2;
+
+use crate::{
+    build_graph_view, diagnostics_from_lowering_error, emit_wgsl, generate_code_functions,
+    lower_to_ir, propagate_types, CodeError, Graph, IrInst, IrLoweringError, IrType, NodeId,
+    NodeKind, ValueType,
+};
+
+fn value_node(graph: &mut Graph, ty: ValueType) -> NodeId {
+    graph.add_node(
+        NodeKind::Value(ty),
+        Vec2::ZERO,
+        vec![],
+        vec![("out".to_string(), ty)],
+    )
+}
+
+/// Code node with inputs `a`, `b` fed by value nodes
+fn code_graph(body: &str, outputs: Vec<(String, ValueType)>) -> (Graph, NodeId) {
+    let mut graph = Graph::new();
+    let a = value_node(&mut graph, ValueType::Float);
+    let b = value_node(&mut graph, ValueType::Float);
+    let code = graph.add_node(
+        NodeKind::Code(body.to_string()),
+        Vec2::ZERO,
+        vec![
+            ("a".to_string(), ValueType::Float),
+            ("b".to_string(), ValueType::Float),
+        ],
+        outputs,
+    );
+
+    let a_out = graph.node(a).unwrap().outputs[0];
+    let b_out = graph.node(b).unwrap().outputs[0];
+    let inputs = graph.node(code).unwrap().inputs.clone();
+    graph.connect(a_out, inputs[0]).unwrap();
+    graph.connect(b_out, inputs[1]).unwrap();
+
+    (graph, code)
+}
+
+#[test]
+fn test_expression_body_is_wrapped_in_function() {
+    let functions = generate_code_functions(
+        NodeId(3),
+        "a * b + 1.0",
+        &[
+            ("a".to_string(), IrType::Float),
+            ("b".to_string(), IrType::Float),
+        ],
+        &[("result".to_string(), IrType::Float)],
+    )
+    .unwrap();
+
+    assert_eq!(functions.outputs, vec!["code_node_3".to_string()]);
+    assert!(functions
+        .helper
+        .source
+        .contains("fn code_node_3(a: f32, b: f32) -> f32 {"));
+    assert!(functions.helper.source.contains("return (a * b + 1.0);"));
+}
+
+#[test]
+fn test_code_node_compiles_to_helper_call() {
+    let (graph, code) = code_graph("a * b", vec![("result".to_string(), ValueType::Float)]);
+
+    let view = build_graph_view(&graph, &[code]).unwrap();
+    let types = propagate_types(&view).unwrap();
+    let program = lower_to_ir(&view, &types).unwrap();
+
+    assert_eq!(program.helpers.len(), 1);
+    assert!(matches!(
+        &program.instructions[2],
+        IrInst::Call { function, args, ty: IrType::Float }
+            if function == "code_node_2" && args.len() == 2
+    ));
+
+    let wgsl = emit_wgsl(&program);
+    assert!(wgsl.contains("fn code_node_2(a: f32, b: f32) -> f32"));
+    assert!(wgsl.contains("let v2: f32 = code_node_2("));
+}
+
+#[test]
+fn test_multiple_outputs_use_accessors() {
+    let (graph, code) = code_graph(
+        "sum = a + b; diff = a - b;",
+        vec![
+            ("sum".to_string(), ValueType::Float),
+            ("diff".to_string(), ValueType::Float),
+        ],
+    );
+
+    let view = build_graph_view(&graph, &[code]).unwrap();
+    let types = propagate_types(&view).unwrap();
+    let program = lower_to_ir(&view, &types).unwrap();
+
+    let calls: Vec<&str> = program
+        .instructions
+        .iter()
+        .filter_map(|inst| match inst {
+            IrInst::Call { function, .. } => Some(function.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(calls, vec!["code_node_2_sum", "code_node_2_diff"]);
+    assert!(program.helpers[0].source.contains("struct CodeNode2"));
+}
+
+#[test]
+fn test_syntax_error_is_reported_on_node() {
+    let (graph, code) = code_graph("a * (b + ", vec![("result".to_string(), ValueType::Float)]);
+
+    let view = build_graph_view(&graph, &[code]).unwrap();
+    let types = propagate_types(&view).unwrap();
+    let err = lower_to_ir(&view, &types).unwrap_err();
+
+    assert!(matches!(
+        &err,
+        IrLoweringError::InvalidCode {
+            node,
+            error: CodeError::Syntax(_)
+        } if *node == code
+    ));
+
+    let diagnostics = diagnostics_from_lowering_error(&err);
+    assert_eq!(diagnostics[0].node, Some(code));
+}
+
+#[cfg(feature = "naga")]
+#[test]
+fn test_type_error_is_reported() {
+    let (graph, code) = code_graph(
+        "vec3<f32>(a, b, 0.0)",
+        vec![("result".to_string(), ValueType::Float)],
+    );
+
+    let view = build_graph_view(&graph, &[code]).unwrap();
+    let types = propagate_types(&view).unwrap();
+    let err = lower_to_ir(&view, &types).unwrap_err();
+
+    assert!(matches!(
+        err,
+        IrLoweringError::InvalidCode {
+            error: CodeError::Type(_),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_invalid_socket_name_is_rejected() {
+    let result = generate_code_functions(
+        NodeId(0),
+        "x",
+        &[("my input".to_string(), IrType::Float)],
+        &[("out".to_string(), IrType::Float)],
+    );
+
+    assert_eq!(
+        result,
+        Err(CodeError::InvalidIdentifier("my input".to_string()))
+    );
+}
@@ -226,6 +226,7 @@ fn test_diagnostic_equality() {
         message: "Test error".to_string(),
         node: None,
         socket: Some(socket),
+        link: None,
     };
 
     let diag2 = diagnostics::Diagnostic {
@@ -233,9 +234,54 @@ fn test_diagnostic_equality() {
         message: "Test error".to_string(),
         node: None,
         socket: Some(socket),
+        link: None,
     };
 
     assert_eq!(diag1.severity, diag2.severity);
     assert_eq!(diag1.message, diag2.message);
     assert_eq!(diag1.socket, diag2.socket);
 }
+
+#[test]
+fn test_cycle_error_produces_node_and_link_diagnostics() {
+    let mut graph = Graph::new();
+    let relay = |graph: &mut Graph| {
+        graph.add_node(
+            NodeKind::Registered("Relay".to_string()),
+            glam::Vec2::ZERO,
+            vec![("in".to_string(), ValueType::Float)],
+            vec![("out".to_string(), ValueType::Float)],
+        )
+    };
+    let a = relay(&mut graph);
+    let b = relay(&mut graph);
+    let a_out = graph.node(a).unwrap().outputs[0];
+    let b_in = graph.node(b).unwrap().inputs[0];
+    let b_out = graph.node(b).unwrap().outputs[0];
+    let a_in = graph.node(a).unwrap().inputs[0];
+    let forward = graph.connect(a_out, b_in).unwrap();
+    let back = graph.connect(b_out, a_in).unwrap();
+
+    let err = build_graph_view(&graph, &[b]).unwrap_err();
+    let diags = diagnostics::diagnostics_from_graph_error(&err);
+
+    let nodes: Vec<NodeId> = diags.iter().filter_map(|d| d.node).collect();
+    let links: Vec<LinkId> = diags.iter().filter_map(|d| d.link).collect();
+    assert_eq!(nodes, vec![a, b]);
+    assert_eq!(links, vec![forward, back]);
+    assert!(diags
+        .iter()
+        .all(|d| d.severity == diagnostics::DiagnosticSeverity::Error));
+}
+
+#[test]
+fn test_compile_error_diagnostics_follow_the_failing_pass() {
+    let socket = SocketId(7);
+    let err = CompileError::Lowering(IrLoweringError::MissingInput(socket));
+
+    let diags = diagnostics::diagnostics_from_compile_error(&err);
+
+    assert_eq!(diags.len(), 1);
+    assert_eq!(diags[0].socket, Some(socket));
+    assert!(diags[0].message.contains("Required input"));
+}
@@ -1,13 +1,13 @@
 use glam::Vec2;
 
-use crate::{Graph, GraphError, NodeKind, SocketDirection, ValueType};
+use crate::{Graph, GraphError, NodeKind, PropertyValue, SocketDirection, ValueType};
 
 #[test]
 fn test_create_node_creates_sockets() {
     let mut graph = Graph::new();
 
     let node_id = graph.add_node(
-        NodeKind::Generic("TestNode".to_string()),
+        NodeKind::Registered("TestNode".to_string()),
         Vec2::new(0.0, 0.0),
         vec![("input1".to_string(), ValueType::Float)],
         vec![("output1".to_string(), ValueType::Float)],
@@ -35,14 +35,14 @@ fn test_valid_connection_succeeds() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::new(0.0, 0.0),
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::new(100.0, 0.0),
         vec![("in".to_string(), ValueType::Float)],
         vec![],
@@ -63,14 +63,14 @@ fn test_input_to_input_fails() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::new(0.0, 0.0),
         vec![("in1".to_string(), ValueType::Float)],
         vec![],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::new(100.0, 0.0),
         vec![("in2".to_string(), ValueType::Float)],
         vec![],
@@ -88,14 +88,14 @@ fn test_type_mismatch_fails() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::new(0.0, 0.0),
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::new(100.0, 0.0),
         vec![("in".to_string(), ValueType::Vec3)],
         vec![],
@@ -113,21 +113,21 @@ fn test_double_connection_to_input_fails() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::new(0.0, 0.0),
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::new(100.0, 0.0),
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node3 = graph.add_node(
-        NodeKind::Generic("Node3".to_string()),
+        NodeKind::Registered("Node3".to_string()),
         Vec2::new(200.0, 0.0),
         vec![("in".to_string(), ValueType::Float)],
         vec![],
@@ -148,14 +148,14 @@ fn test_disconnect_removes_link() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::new(0.0, 0.0),
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::new(100.0, 0.0),
         vec![("in".to_string(), ValueType::Float)],
         vec![],
@@ -183,21 +183,21 @@ fn test_links_into_and_out_of() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::new(0.0, 0.0),
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::new(100.0, 0.0),
         vec![("in1".to_string(), ValueType::Float)],
         vec![],
     );
 
     let node3 = graph.add_node(
-        NodeKind::Generic("Node3".to_string()),
+        NodeKind::Registered("Node3".to_string()),
         Vec2::new(100.0, 100.0),
         vec![("in2".to_string(), ValueType::Float)],
         vec![],
@@ -227,7 +227,7 @@ fn test_node_positions_stored() {
 
     let position = Vec2::new(42.5, 123.75);
     let node_id = graph.add_node(
-        NodeKind::Generic("TestNode".to_string()),
+        NodeKind::Registered("TestNode".to_string()),
         position,
         vec![],
         vec![],
@@ -242,14 +242,14 @@ fn test_stable_ids_not_reused() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::ZERO,
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![],
@@ -272,7 +272,7 @@ fn test_add_node_with_optional_input() {
     let mut graph = Graph::new();
 
     let node_id = graph.add_node_with_config(
-        NodeKind::Generic("TestNode".to_string()),
+        NodeKind::Registered("TestNode".to_string()),
         Vec2::ZERO,
         vec![InputDef::optional(
             "input".to_string(),
@@ -302,7 +302,7 @@ fn test_add_node_with_required_input() {
     let mut graph = Graph::new();
 
     let node_id = graph.add_node_with_config(
-        NodeKind::Generic("TestNode".to_string()),
+        NodeKind::Registered("TestNode".to_string()),
         Vec2::ZERO,
         vec![InputDef::required("input", ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
@@ -320,7 +320,7 @@ fn test_add_node_simple_creates_required_config() {
     let mut graph = Graph::new();
 
     let node_id = graph.add_node(
-        NodeKind::Generic("TestNode".to_string()),
+        NodeKind::Registered("TestNode".to_string()),
         Vec2::ZERO,
         vec![("input".to_string(), ValueType::Float)],
         vec![],
@@ -339,14 +339,14 @@ fn test_invariant_checker_valid_graph() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::ZERO,
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![],
@@ -358,3 +358,209 @@ fn test_invariant_checker_valid_graph() {
 
     assert!(graph.check_invariants().is_ok());
 }
+
+#[test]
+fn test_mutations_bump_revision() {
+    let mut graph = Graph::new();
+    assert_eq!(graph.revision(), 0);
+
+    let a = graph.add_node(
+        NodeKind::Value(ValueType::Float),
+        Vec2::ZERO,
+        vec![],
+        vec![("out".to_string(), ValueType::Float)],
+    );
+    let b = graph.add_node(
+        NodeKind::Math(crate::MathOp::Add),
+        Vec2::ZERO,
+        vec![("a".to_string(), ValueType::Float)],
+        vec![("result".to_string(), ValueType::Float)],
+    );
+    assert_eq!(graph.revision(), 2);
+
+    let from = graph.node(a).unwrap().outputs[0];
+    let to = graph.node(b).unwrap().inputs[0];
+    let link = graph.connect(from, to).unwrap();
+    assert_eq!(graph.revision(), 3);
+
+    // Failed mutations leave the revision alone
+    assert!(graph.connect(from, to).is_err());
+    assert!(graph.disconnect(link));
+    assert!(!graph.disconnect(link));
+    assert_eq!(graph.revision(), 4);
+
+    graph.remove_node(a);
+    assert_eq!(graph.revision(), 5);
+}
+
+#[test]
+fn test_set_input_default_checks_socket() {
+    use crate::{InputDef, Literal};
+
+    let mut graph = Graph::new();
+    let node = graph.add_node_with_config(
+        NodeKind::Math(crate::MathOp::Add),
+        Vec2::ZERO,
+        vec![
+            InputDef::required("a", ValueType::Float),
+            InputDef::optional("b", ValueType::Float, Literal::Float(1.0)),
+        ],
+        vec![("result".to_string(), ValueType::Float)],
+    );
+    let node = graph.node(node).unwrap().clone();
+
+    graph
+        .set_input_default(node.inputs[1], Literal::Float(4.0))
+        .unwrap();
+    let config = graph.socket(node.inputs[1]).unwrap().input_config.as_ref();
+    assert_eq!(config.unwrap().default, Some(Literal::Float(4.0)));
+
+    assert!(matches!(
+        graph.set_input_default(node.inputs[1], Literal::Int(4)),
+        Err(GraphError::DefaultLiteralTypeMismatch { .. })
+    ));
+    assert!(matches!(
+        graph.set_input_default(node.inputs[0], Literal::Float(4.0)),
+        Err(GraphError::RequiredInput { .. })
+    ));
+    assert!(matches!(
+        graph.set_input_default(node.outputs[0], Literal::Float(4.0)),
+        Err(GraphError::WrongDirection { .. })
+    ));
+}
+
+#[test]
+fn test_connect_rejects_cycles_when_enabled() {
+    let mut graph = Graph::new();
+    let relay = |graph: &mut Graph, name: &str| {
+        graph.add_node(
+            NodeKind::Registered(name.to_string()),
+            Vec2::ZERO,
+            vec![("in".to_string(), ValueType::Float)],
+            vec![("out".to_string(), ValueType::Float)],
+        )
+    };
+    let a = relay(&mut graph, "A");
+    let b = relay(&mut graph, "B");
+    let c = relay(&mut graph, "C");
+    let out = |graph: &Graph, node| graph.node(node).unwrap().outputs[0];
+    let input = |graph: &Graph, node| graph.node(node).unwrap().inputs[0];
+
+    let a_to_b = graph.connect(out(&graph, a), input(&graph, b)).unwrap();
+    let b_to_c = graph.connect(out(&graph, b), input(&graph, c)).unwrap();
+
+    graph.set_reject_cycles(true);
+    let revision = graph.revision();
+    let result = graph.connect(out(&graph, c), input(&graph, a));
+    match result {
+        Err(GraphError::LinkClosesCycle { nodes, path }) => {
+            assert_eq!(nodes, vec![a, b, c]);
+            assert_eq!(path, vec![a_to_b, b_to_c]);
+        }
+        other => panic!("expected LinkClosesCycle, got {other:?}"),
+    }
+    assert_eq!(graph.revision(), revision);
+    assert_eq!(graph.links().count(), 2);
+
+    assert!(matches!(
+        graph.connect(out(&graph, a), input(&graph, a)),
+        Err(GraphError::LinkClosesCycle { .. })
+    ));
+
+    graph.set_reject_cycles(false);
+    assert!(graph.connect(out(&graph, c), input(&graph, a)).is_ok());
+}
+
+#[test]
+fn test_set_input_default_enforces_hard_range() {
+    use crate::{InputDef, Literal, OutOfRange, SocketMetadata, SocketSubtype};
+
+    let mut graph = Graph::new();
+    let factor = SocketMetadata::default()
+        .with_range(0.0, 1.0)
+        .with_subtype(SocketSubtype::Factor);
+    let node = graph.add_node_with_config(
+        NodeKind::Registered("Mix".to_string()),
+        Vec2::ZERO,
+        vec![
+            InputDef::optional("fac", ValueType::Float, Literal::Float(0.5))
+                .with_metadata(factor.clone()),
+            InputDef::optional("color", ValueType::Vec3, Literal::Vec3([0.0; 3]))
+                .with_metadata(factor.clone()),
+            InputDef::optional("steps", ValueType::Int, Literal::Int(1))
+                .with_metadata(SocketMetadata::default().with_range(1.0, 8.0)),
+            InputDef::optional("strict", ValueType::Float, Literal::Float(0.0))
+                .with_metadata(factor.with_out_of_range(OutOfRange::Reject)),
+        ],
+        vec![("result".to_string(), ValueType::Float)],
+    );
+    let inputs = graph.node(node).unwrap().inputs.clone();
+    let default_of = |graph: &Graph, socket| {
+        graph
+            .socket(socket)
+            .unwrap()
+            .input_config
+            .as_ref()
+            .unwrap()
+            .default
+            .clone()
+    };
+
+    graph
+        .set_input_default(inputs[0], Literal::Float(2.0))
+        .unwrap();
+    assert_eq!(default_of(&graph, inputs[0]), Some(Literal::Float(1.0)));
+
+    graph
+        .set_input_default(inputs[1], Literal::Vec3([-1.0, 0.25, 3.0]))
+        .unwrap();
+    assert_eq!(
+        default_of(&graph, inputs[1]),
+        Some(Literal::Vec3([0.0, 0.25, 1.0]))
+    );
+
+    graph.set_input_default(inputs[2], Literal::Int(0)).unwrap();
+    assert_eq!(default_of(&graph, inputs[2]), Some(Literal::Int(1)));
+
+    let revision = graph.revision();
+    assert!(matches!(
+        graph.set_input_default(inputs[3], Literal::Float(-0.5)),
+        Err(GraphError::DefaultOutOfRange {
+            min: Some(0.0),
+            max: Some(1.0),
+            ..
+        })
+    ));
+    assert_eq!(graph.revision(), revision);
+    assert_eq!(default_of(&graph, inputs[3]), Some(Literal::Float(0.0)));
+    graph
+        .set_input_default(inputs[3], Literal::Float(0.75))
+        .unwrap();
+}
+
+#[test]
+fn test_remove_node_property_restores_default() {
+    let mut graph = Graph::new();
+    let node = graph.add_node(
+        NodeKind::Registered("math.math".to_string()),
+        Vec2::ZERO,
+        vec![],
+        vec![],
+    );
+    let value = PropertyValue::Enum("power".to_string());
+
+    graph
+        .set_node_property(node, "operation", value.clone())
+        .unwrap();
+    let revision = graph.revision();
+
+    assert_eq!(
+        graph.remove_node_property(node, "operation").unwrap(),
+        Some(value)
+    );
+    assert!(graph.node(node).unwrap().properties.is_empty());
+    assert_eq!(graph.revision(), revision + 1);
+
+    assert_eq!(graph.remove_node_property(node, "operation").unwrap(), None);
+    assert_eq!(graph.revision(), revision + 1);
+}
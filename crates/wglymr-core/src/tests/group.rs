@@ -0,0 +1,356 @@
+use glam::Vec2;
+
+use crate::{
+    build_graph_view, diagnostics_from_lowering_error, emit_wgsl, inline_groups, lower_to_ir,
+    propagate_types, Graph, GroupError, GroupLibrary, InputDef, IrInst, Literal, MathOp, NodeId,
+    NodeKind, ValueId, ValueType,
+};
+
+/// Group computing `a + b`
+fn add_group() -> Graph {
+    let mut graph = Graph::new();
+
+    let input = graph.add_node(
+        NodeKind::GroupInput,
+        Vec2::ZERO,
+        vec![],
+        vec![
+            ("a".to_string(), ValueType::Float),
+            ("b".to_string(), ValueType::Float),
+        ],
+    );
+    let add = graph.add_node(
+        NodeKind::Math(MathOp::Add),
+        Vec2::new(100.0, 0.0),
+        vec![
+            ("a".to_string(), ValueType::Float),
+            ("b".to_string(), ValueType::Float),
+        ],
+        vec![("result".to_string(), ValueType::Float)],
+    );
+    let output = graph.add_node(
+        NodeKind::GroupOutput,
+        Vec2::new(200.0, 0.0),
+        vec![("sum".to_string(), ValueType::Float)],
+        vec![],
+    );
+
+    let input_node = graph.node(input).unwrap().clone();
+    let add_node = graph.node(add).unwrap().clone();
+    let output_node = graph.node(output).unwrap().clone();
+
+    graph
+        .connect(input_node.outputs[0], add_node.inputs[0])
+        .unwrap();
+    graph
+        .connect(input_node.outputs[1], add_node.inputs[1])
+        .unwrap();
+    graph
+        .connect(add_node.outputs[0], output_node.inputs[0])
+        .unwrap();
+
+    graph
+}
+
+fn value_node(graph: &mut Graph) -> NodeId {
+    graph.add_node(
+        NodeKind::Value(ValueType::Float),
+        Vec2::ZERO,
+        vec![],
+        vec![("out".to_string(), ValueType::Float)],
+    )
+}
+
+#[test]
+fn test_interface_derived_from_group_nodes() {
+    let mut library = GroupLibrary::new();
+    let group = library.add_group("Add", add_group());
+
+    let interface = library.interface(group).unwrap();
+
+    assert_eq!(
+        interface.inputs,
+        vec![
+            ("a".to_string(), ValueType::Float),
+            ("b".to_string(), ValueType::Float)
+        ]
+    );
+    assert_eq!(
+        interface.outputs,
+        vec![("sum".to_string(), ValueType::Float)]
+    );
+}
+
+#[test]
+fn test_inlined_group_compiles_like_flat_graph() {
+    let mut library = GroupLibrary::new();
+    let group = library.add_group("Add", add_group());
+
+    let mut graph = Graph::new();
+    let a = value_node(&mut graph);
+    let b = value_node(&mut graph);
+    let group_node = library.instantiate(&mut graph, group, Vec2::ZERO).unwrap();
+
+    let a_out = graph.node(a).unwrap().outputs[0];
+    let b_out = graph.node(b).unwrap().outputs[0];
+    let group_inputs = graph.node(group_node).unwrap().inputs.clone();
+    graph.connect(a_out, group_inputs[0]).unwrap();
+    graph.connect(b_out, group_inputs[1]).unwrap();
+
+    let inlined = inline_groups(&graph, &library).unwrap();
+
+    assert!(inlined.graph.node(group_node).is_none());
+    assert!(inlined
+        .graph
+        .nodes()
+        .all(|node| !matches!(node.kind, NodeKind::Group(_))));
+
+    let roots = inlined.map_roots(&[group_node]);
+    let view = build_graph_view(&inlined.graph, &roots).unwrap();
+    let types = propagate_types(&view).unwrap();
+    let program = lower_to_ir(&view, &types).unwrap();
+
+    assert_eq!(program.instructions.len(), 3);
+    assert!(matches!(program.instructions[2], IrInst::Binary { .. }));
+    assert!(matches!(
+        program.instructions[2],
+        IrInst::Binary {
+            lhs: ValueId(0),
+            rhs: ValueId(1),
+            ..
+        } | IrInst::Binary {
+            lhs: ValueId(1),
+            rhs: ValueId(0),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_group_output_feeds_downstream_nodes() {
+    let mut library = GroupLibrary::new();
+    let group = library.add_group("Add", add_group());
+
+    let mut graph = Graph::new();
+    let a = value_node(&mut graph);
+    let group_node = library.instantiate(&mut graph, group, Vec2::ZERO).unwrap();
+    let multiply = graph.add_node(
+        NodeKind::Math(MathOp::Multiply),
+        Vec2::ZERO,
+        vec![
+            ("a".to_string(), ValueType::Float),
+            ("b".to_string(), ValueType::Float),
+        ],
+        vec![("result".to_string(), ValueType::Float)],
+    );
+
+    let a_out = graph.node(a).unwrap().outputs[0];
+    let group = graph.node(group_node).unwrap().clone();
+    let multiply_inputs = graph.node(multiply).unwrap().inputs.clone();
+    graph.connect(a_out, group.inputs[0]).unwrap();
+    graph.connect(a_out, group.inputs[1]).unwrap();
+    graph.connect(group.outputs[0], multiply_inputs[0]).unwrap();
+    graph.connect(a_out, multiply_inputs[1]).unwrap();
+
+    let inlined = inline_groups(&graph, &library).unwrap();
+    let view = build_graph_view(&inlined.graph, &[multiply]).unwrap();
+    let types = propagate_types(&view).unwrap();
+    let program = lower_to_ir(&view, &types).unwrap();
+
+    let wgsl = emit_wgsl(&program);
+    assert!(wgsl.contains("v0 + v0"));
+    assert!(wgsl.contains("v1 * v0"));
+}
+
+#[test]
+fn test_nested_groups_are_inlined() {
+    let mut library = GroupLibrary::new();
+    let inner = library.add_group("Add", add_group());
+
+    let mut outer_graph = Graph::new();
+    let input = outer_graph.add_node(
+        NodeKind::GroupInput,
+        Vec2::ZERO,
+        vec![],
+        vec![("x".to_string(), ValueType::Float)],
+    );
+    let inner_node = library
+        .instantiate(&mut outer_graph, inner, Vec2::ZERO)
+        .unwrap();
+    let output = outer_graph.add_node(
+        NodeKind::GroupOutput,
+        Vec2::ZERO,
+        vec![("doubled".to_string(), ValueType::Float)],
+        vec![],
+    );
+    let x = outer_graph.node(input).unwrap().outputs[0];
+    let inner_sockets = outer_graph.node(inner_node).unwrap().clone();
+    let doubled = outer_graph.node(output).unwrap().inputs[0];
+    outer_graph.connect(x, inner_sockets.inputs[0]).unwrap();
+    outer_graph.connect(x, inner_sockets.inputs[1]).unwrap();
+    outer_graph
+        .connect(inner_sockets.outputs[0], doubled)
+        .unwrap();
+    let outer = library.add_group("Double", outer_graph);
+
+    let mut graph = Graph::new();
+    let value = value_node(&mut graph);
+    let group_node = library.instantiate(&mut graph, outer, Vec2::ZERO).unwrap();
+    let value_out = graph.node(value).unwrap().outputs[0];
+    let group_in = graph.node(group_node).unwrap().inputs[0];
+    graph.connect(value_out, group_in).unwrap();
+
+    let inlined = inline_groups(&graph, &library).unwrap();
+    let view = build_graph_view(&inlined.graph, &inlined.map_roots(&[group_node])).unwrap();
+    let types = propagate_types(&view).unwrap();
+    let program = lower_to_ir(&view, &types).unwrap();
+
+    assert!(emit_wgsl(&program).contains("v0 + v0"));
+
+    let add_origin = inlined
+        .graph
+        .nodes()
+        .find(|node| matches!(node.kind, NodeKind::Math(_)))
+        .and_then(|node| inlined.node_origin(node.id))
+        .unwrap();
+    assert_eq!(add_origin.group_path, vec![group_node, inner_node]);
+}
+
+#[test]
+fn test_recursive_group_reference_is_rejected() {
+    let mut library = GroupLibrary::new();
+    let group = library.add_group("Recursive", Graph::new());
+
+    let mut group_graph = Graph::new();
+    library
+        .instantiate(&mut group_graph, group, Vec2::ZERO)
+        .unwrap();
+    library.group_mut(group).unwrap().graph = group_graph;
+
+    let mut graph = Graph::new();
+    library.instantiate(&mut graph, group, Vec2::ZERO).unwrap();
+
+    let result = inline_groups(&graph, &library);
+    assert!(matches!(
+        result,
+        Err(GroupError::RecursiveGroup { path, .. }) if path == vec![group, group]
+    ));
+}
+
+#[test]
+fn test_missing_group_is_rejected() {
+    let library = GroupLibrary::new();
+    let mut graph = Graph::new();
+    graph.add_node(
+        NodeKind::Group(crate::GroupId(7)),
+        Vec2::ZERO,
+        vec![],
+        vec![],
+    );
+
+    let result = inline_groups(&graph, &library);
+    assert!(matches!(result, Err(GroupError::GroupNotFound { .. })));
+}
+
+#[test]
+fn test_interface_mismatch_is_rejected() {
+    let mut library = GroupLibrary::new();
+    let group = library.add_group("Add", add_group());
+
+    let mut graph = Graph::new();
+    let node = graph.add_node(
+        NodeKind::Group(group),
+        Vec2::ZERO,
+        vec![("a".to_string(), ValueType::Vec3)],
+        vec![("sum".to_string(), ValueType::Float)],
+    );
+
+    let result = inline_groups(&graph, &library);
+    assert!(matches!(
+        result,
+        Err(GroupError::InterfaceMismatch { node: n, .. }) if n == node
+    ));
+}
+
+#[test]
+fn test_uninlined_group_fails_type_propagation() {
+    let mut library = GroupLibrary::new();
+    let group = library.add_group("Add", add_group());
+
+    let mut graph = Graph::new();
+    let node = library.instantiate(&mut graph, group, Vec2::ZERO).unwrap();
+
+    let view = build_graph_view(&graph, &[node]).unwrap();
+    assert!(propagate_types(&view).is_err());
+}
+
+#[test]
+fn test_diagnostics_point_through_group_boundary() {
+    let mut library = GroupLibrary::new();
+    let group = library.add_group("Add", add_group());
+
+    let mut graph = Graph::new();
+    let group_node = library.instantiate(&mut graph, group, Vec2::ZERO).unwrap();
+    let group_inputs = graph.node(group_node).unwrap().inputs.clone();
+
+    let inlined = inline_groups(&graph, &library).unwrap();
+    let view = build_graph_view(&inlined.graph, &inlined.map_roots(&[group_node])).unwrap();
+    let types = propagate_types(&view);
+
+    // Unconnected group input surfaces on the group node's own socket
+    let err = match types {
+        Err(crate::TypeError::UnconnectedRequiredInput { socket }) => socket,
+        other => panic!("expected unconnected input, got {:?}", other.err()),
+    };
+    let diagnostic = inlined.remap_diagnostic(
+        crate::diagnostics_from_type_error(&crate::TypeError::UnconnectedRequiredInput {
+            socket: err,
+        })
+        .remove(0),
+    );
+    assert_eq!(diagnostic.socket, Some(group_inputs[0]));
+
+    // Errors deep inside the group attach to the group node
+    let inner_add = inlined
+        .graph
+        .nodes()
+        .find(|node| matches!(node.kind, NodeKind::Math(_)))
+        .unwrap()
+        .inputs[0];
+    let diagnostic = inlined.remap_diagnostic(
+        diagnostics_from_lowering_error(&crate::IrLoweringError::MissingInput(inner_add)).remove(0),
+    );
+    assert_eq!(diagnostic.node, Some(group_node));
+    assert_eq!(diagnostic.socket, None);
+    assert!(diagnostic.message.contains("group node"));
+}
+
+#[test]
+fn test_group_input_defaults_survive_inlining() {
+    let mut library = GroupLibrary::new();
+    let group = library.add_group("Add", add_group());
+
+    let mut graph = Graph::new();
+    let group_node = graph.add_node_with_config(
+        NodeKind::Group(group),
+        Vec2::ZERO,
+        vec![
+            InputDef::optional("a", ValueType::Float, Literal::Float(2.0)),
+            InputDef::optional("b", ValueType::Float, Literal::Float(3.0)),
+        ],
+        vec![("sum".to_string(), ValueType::Float)],
+    );
+
+    let inlined = inline_groups(&graph, &library).unwrap();
+    let view = build_graph_view(&inlined.graph, &inlined.map_roots(&[group_node])).unwrap();
+    let types = propagate_types(&view).unwrap();
+    let program = lower_to_ir(&view, &types).unwrap();
+
+    assert_eq!(
+        program.instructions[0],
+        IrInst::Constant {
+            value: Literal::Float(2.0),
+            ty: crate::IrType::Float
+        }
+    );
+}
@@ -0,0 +1,185 @@
+use glam::Vec2;
+
+use crate::{
+    compile_graph, validate_ir, CompiledProgram, Graph, IncrementalCompiler, InputDef, Literal,
+    MathOp, NodeId, NodeKind, ValueType,
+};
+
+fn value(graph: &mut Graph) -> NodeId {
+    graph.add_node(
+        NodeKind::Value(ValueType::Float),
+        Vec2::ZERO,
+        vec![],
+        vec![("out".to_string(), ValueType::Float)],
+    )
+}
+
+/// Math node whose `b` input is an optional default
+fn math(graph: &mut Graph, op: MathOp, b: f32) -> NodeId {
+    graph.add_node_with_config(
+        NodeKind::Math(op),
+        Vec2::ZERO,
+        vec![
+            InputDef::required("a", ValueType::Float),
+            InputDef::optional("b", ValueType::Float, Literal::Float(b)),
+        ],
+        vec![("result".to_string(), ValueType::Float)],
+    )
+}
+
+fn link(graph: &mut Graph, from: NodeId, to: NodeId, input: usize) {
+    let from = graph.node(from).unwrap().outputs[0];
+    let to = graph.node(to).unwrap().inputs[input];
+    graph.connect(from, to).unwrap();
+}
+
+struct TwoBranches {
+    graph: Graph,
+    left: [NodeId; 2],
+    right: [NodeId; 2],
+    join: NodeId,
+}
+
+/// `(left_value * 2) + (right_value + 3)`
+fn two_branches() -> TwoBranches {
+    let mut graph = Graph::new();
+    let left_value = value(&mut graph);
+    let left_math = math(&mut graph, MathOp::Multiply, 2.0);
+    let right_value = value(&mut graph);
+    let right_math = math(&mut graph, MathOp::Add, 3.0);
+    let join = graph.add_node(
+        NodeKind::Math(MathOp::Add),
+        Vec2::ZERO,
+        vec![
+            ("a".to_string(), ValueType::Float),
+            ("b".to_string(), ValueType::Float),
+        ],
+        vec![("result".to_string(), ValueType::Float)],
+    );
+
+    link(&mut graph, left_value, left_math, 0);
+    link(&mut graph, right_value, right_math, 0);
+    link(&mut graph, left_math, join, 0);
+    link(&mut graph, right_math, join, 1);
+
+    TwoBranches {
+        graph,
+        left: [left_value, left_math],
+        right: [right_value, right_math],
+        join,
+    }
+}
+
+/// Independent nodes may be ordered differently, so compare structure
+fn assert_matches_full_compile(result: &CompiledProgram, graph: &Graph, root: NodeId) {
+    let full = compile_graph(graph, &[root]).unwrap();
+    assert_eq!(
+        result.program.instructions.len(),
+        full.program.instructions.len()
+    );
+    assert_eq!(result.stats.counts, full.stats.counts);
+    assert_eq!(result.stats.estimated_cost, full.stats.estimated_cost);
+    assert!(validate_ir(&result.program).is_ok());
+    #[cfg(feature = "naga")]
+    assert!(crate::validate_wgsl(&result.wgsl).is_ok());
+}
+
+fn sorted(nodes: &[NodeId]) -> Vec<NodeId> {
+    let mut nodes = nodes.to_vec();
+    nodes.sort_by_key(|node| node.0);
+    nodes
+}
+
+#[test]
+fn test_first_compile_lowers_every_reachable_node() {
+    let mut graph = two_branches();
+    let unreachable = value(&mut graph.graph);
+    let mut compiler = IncrementalCompiler::new();
+
+    let result = compiler.compile(&graph.graph, &[graph.join]).unwrap();
+
+    assert_eq!(compiler.relowered_nodes().len(), 5);
+    assert!(!compiler.relowered_nodes().contains(&unreachable));
+    assert_matches_full_compile(&result, &graph.graph, graph.join);
+}
+
+#[test]
+fn test_unchanged_graph_reuses_everything() {
+    let graph = two_branches();
+    let mut compiler = IncrementalCompiler::new();
+
+    let first = compiler.compile(&graph.graph, &[graph.join]).unwrap();
+    let second = compiler.compile(&graph.graph, &[graph.join]).unwrap();
+
+    assert!(compiler.relowered_nodes().is_empty());
+    assert_eq!(first.wgsl, second.wgsl);
+}
+
+#[test]
+fn test_clone_at_same_revision_is_not_reused() {
+    let mut graph = two_branches();
+    let mut copy = graph.graph.clone();
+    let mut compiler = IncrementalCompiler::new();
+
+    let factor = graph.graph.node(graph.left[1]).unwrap().inputs[1];
+    copy.set_input_default(factor, Literal::Float(5.0)).unwrap();
+    graph
+        .graph
+        .set_input_default(factor, Literal::Float(7.0))
+        .unwrap();
+    assert_eq!(copy.revision(), graph.graph.revision());
+
+    compiler.compile(&copy, &[graph.join]).unwrap();
+    let result = compiler.compile(&graph.graph, &[graph.join]).unwrap();
+
+    assert!(result.wgsl.contains(" = 7;"));
+    assert!(!result.wgsl.contains(" = 5;"));
+    assert_matches_full_compile(&result, &graph.graph, graph.join);
+}
+
+#[test]
+fn test_editing_one_branch_relowers_only_that_branch() {
+    let mut graph = two_branches();
+    let mut compiler = IncrementalCompiler::new();
+    compiler.compile(&graph.graph, &[graph.join]).unwrap();
+
+    let factor = graph.graph.node(graph.left[1]).unwrap().inputs[1];
+    graph
+        .graph
+        .set_input_default(factor, Literal::Float(5.0))
+        .unwrap();
+    let result = compiler.compile(&graph.graph, &[graph.join]).unwrap();
+
+    // The edited node and the join downstream of it; the right branch and
+    // the left value feeding the edit are reused
+    assert_eq!(
+        sorted(compiler.relowered_nodes()),
+        sorted(&[graph.left[1], graph.join])
+    );
+    for node in graph.right.iter().chain(&graph.left[..1]) {
+        assert!(!compiler.relowered_nodes().contains(node));
+    }
+    assert!(result.wgsl.contains(" = 5;"));
+    assert_matches_full_compile(&result, &graph.graph, graph.join);
+}
+
+#[test]
+fn test_relinking_matches_full_compile() {
+    let mut graph = two_branches();
+    let mut compiler = IncrementalCompiler::new();
+    compiler.compile(&graph.graph, &[graph.join]).unwrap();
+
+    // Feed the right branch from the left value instead
+    let right_input = graph.graph.node(graph.right[1]).unwrap().inputs[0];
+    let old_link = graph.graph.links_into(right_input).next().unwrap().id;
+    graph.graph.disconnect(old_link);
+    link(&mut graph.graph, graph.left[0], graph.right[1], 0);
+
+    let result = compiler.compile(&graph.graph, &[graph.join]).unwrap();
+
+    // The right value node is no longer reachable, nothing else upstream
+    // of the right branch changed structurally
+    assert!(!compiler.relowered_nodes().contains(&graph.right[0]));
+    assert!(!compiler.relowered_nodes().contains(&graph.left[0]));
+    assert_matches_full_compile(&result, &graph.graph, graph.join);
+}
@@ -51,6 +51,7 @@ fn test_binary_instruction_references_value_ids() {
 fn test_ir_program_preserves_insertion_order() {
     let mut program = IrProgram {
         instructions: Vec::new(),
+        ..Default::default()
     };
 
     program.instructions.push(IrInst::Constant {
@@ -21,6 +21,7 @@ fn test_float_to_vec3_conversion() {
                 ty: IrType::Vec3,
             },
         ],
+        ..Default::default()
     };
 
     let result = insert_conversions(ir).unwrap();
@@ -68,6 +69,7 @@ fn test_vec3_to_color_conversion() {
                 ty: IrType::Color,
             },
         ],
+        ..Default::default()
     };
 
     let result = insert_conversions(ir).unwrap();
@@ -107,6 +109,7 @@ fn test_unsupported_conversion_errors() {
                 ty: IrType::Float,
             },
         ],
+        ..Default::default()
     };
 
     let result = insert_conversions(ir);
@@ -139,6 +142,7 @@ fn test_no_conversion_needed() {
                 ty: IrType::Vec3,
             },
         ],
+        ..Default::default()
     };
 
     let result = insert_conversions(ir).unwrap();
@@ -161,6 +165,7 @@ fn test_instruction_order_valid() {
                 ty: IrType::Float,
             },
         ],
+        ..Default::default()
     };
 
     let result = insert_conversions(ir).unwrap();
@@ -198,6 +203,7 @@ fn test_both_operands_need_conversion() {
                 ty: IrType::Vec3,
             },
         ],
+        ..Default::default()
     };
 
     let result = insert_conversions(ir).unwrap();
@@ -240,6 +246,7 @@ fn test_float_to_vec2_conversion() {
                 ty: IrType::Vec2,
             },
         ],
+        ..Default::default()
     };
 
     let result = insert_conversions(ir).unwrap();
@@ -279,6 +286,7 @@ fn test_float_to_vec4_conversion() {
                 ty: IrType::Vec4,
             },
         ],
+        ..Default::default()
     };
 
     let result = insert_conversions(ir).unwrap();
@@ -318,6 +326,7 @@ fn test_float_to_color_conversion() {
                 ty: IrType::Color,
             },
         ],
+        ..Default::default()
     };
 
     let result = insert_conversions(ir).unwrap();
@@ -337,3 +346,117 @@ fn test_float_to_color_conversion() {
         _ => panic!("Expected Convert instruction"),
     }
 }
+
+#[test]
+fn test_matrix_vector_product_keeps_operand_types() {
+    let ir = IrProgram {
+        instructions: vec![
+            IrInst::Constant {
+                value: Literal::identity(crate::ValueType::Mat3).unwrap(),
+                ty: IrType::Mat3,
+            },
+            IrInst::Constant {
+                value: Literal::Vec3([1.0, 2.0, 3.0]),
+                ty: IrType::Vec3,
+            },
+            IrInst::Binary {
+                op: BinaryOp::Mul,
+                lhs: ValueId(0),
+                rhs: ValueId(1),
+                ty: IrType::Vec3,
+            },
+        ],
+        ..Default::default()
+    };
+
+    let result = insert_conversions(ir.clone()).unwrap();
+    assert_eq!(result.instructions, ir.instructions);
+}
+
+#[test]
+fn test_matrix_resize_conversion() {
+    let ir = IrProgram {
+        instructions: vec![
+            IrInst::Constant {
+                value: Literal::identity(crate::ValueType::Mat3).unwrap(),
+                ty: IrType::Mat3,
+            },
+            IrInst::Constant {
+                value: Literal::identity(crate::ValueType::Mat4).unwrap(),
+                ty: IrType::Mat4,
+            },
+            IrInst::Binary {
+                op: BinaryOp::Mul,
+                lhs: ValueId(0),
+                rhs: ValueId(1),
+                ty: IrType::Mat4,
+            },
+        ],
+        ..Default::default()
+    };
+
+    let result = insert_conversions(ir).unwrap();
+
+    assert_eq!(
+        result.instructions[2],
+        IrInst::Convert {
+            from: ValueId(0),
+            from_ty: IrType::Mat3,
+            to_ty: IrType::Mat4,
+        }
+    );
+}
+
+#[test]
+fn test_outputs_follow_inserted_conversions() {
+    let ir = IrProgram {
+        instructions: vec![
+            IrInst::Constant {
+                value: Literal::Float(2.0),
+                ty: IrType::Float,
+            },
+            IrInst::Constant {
+                value: Literal::Vec3([1.0, 2.0, 3.0]),
+                ty: IrType::Vec3,
+            },
+            IrInst::Binary {
+                op: BinaryOp::Mul,
+                lhs: ValueId(0),
+                rhs: ValueId(1),
+                ty: IrType::Vec3,
+            },
+            IrInst::Binary {
+                op: BinaryOp::Add,
+                lhs: ValueId(2),
+                rhs: ValueId(1),
+                ty: IrType::Vec3,
+            },
+        ],
+        outputs: vec![
+            crate::IrOutput {
+                name: "scaled".to_string(),
+                value: ValueId(2),
+            },
+            crate::IrOutput {
+                name: "scale".to_string(),
+                value: ValueId(0),
+            },
+        ],
+        ..Default::default()
+    };
+
+    let result = insert_conversions(ir).unwrap();
+
+    // v0 is splatted into v2, shifting the product to v3
+    assert_eq!(result.outputs[0].value, ValueId(3));
+    assert_eq!(result.outputs[1].value, ValueId(0));
+    assert_eq!(
+        result.instructions[4],
+        IrInst::Binary {
+            op: BinaryOp::Add,
+            lhs: ValueId(3),
+            rhs: ValueId(1),
+            ty: IrType::Vec3,
+        }
+    );
+}
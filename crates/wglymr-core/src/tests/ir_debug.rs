@@ -20,6 +20,7 @@ fn pretty_print_simple_program() {
                 ty: IrType::Float,
             },
         ],
+        ..Default::default()
     };
 
     let output = pretty_print(&ir);
@@ -45,6 +46,7 @@ fn pretty_print_with_different_types() {
                 ty: IrType::Int,
             },
         ],
+        ..Default::default()
     };
 
     let output = pretty_print(&ir);
@@ -72,6 +74,7 @@ fn validator_passes_valid_ir() {
                 ty: IrType::Float,
             },
         ],
+        ..Default::default()
     };
 
     assert!(validate_ir(&ir).is_ok());
@@ -90,6 +93,7 @@ fn validator_passes_constants_only() {
                 ty: IrType::Float,
             },
         ],
+        ..Default::default()
     };
 
     assert!(validate_ir(&ir).is_ok());
@@ -110,6 +114,7 @@ fn validator_fails_invalid_value_ref() {
                 ty: IrType::Float,
             },
         ],
+        ..Default::default()
     };
 
     let result = validate_ir(&ir);
@@ -139,6 +144,7 @@ fn validator_fails_future_reference() {
                 ty: IrType::Float,
             },
         ],
+        ..Default::default()
     };
 
     let result = validate_ir(&ir);
@@ -164,6 +170,7 @@ fn validator_fails_self_reference() {
                 ty: IrType::Float,
             },
         ],
+        ..Default::default()
     };
 
     let result = validate_ir(&ir);
@@ -211,6 +218,7 @@ fn pretty_print_all_binary_ops() {
                 ty: IrType::Float,
             },
         ],
+        ..Default::default()
     };
 
     let output = pretty_print(&ir);
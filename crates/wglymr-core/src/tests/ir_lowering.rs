@@ -107,7 +107,7 @@ fn test_pass_through_node() {
     );
 
     let pass_node = graph.add_node(
-        NodeKind::Generic("pass".to_string()),
+        NodeKind::Registered("utility.reroute".to_string()),
         Vec2::ZERO,
         vec![("input".to_string(), ValueType::Float)],
         vec![("output".to_string(), ValueType::Float)],
@@ -168,14 +168,14 @@ fn test_value_ids_reused_correctly() {
     );
 
     let pass1 = graph.add_node(
-        NodeKind::Generic("pass".to_string()),
+        NodeKind::Registered("utility.reroute".to_string()),
         Vec2::ZERO,
         vec![("input".to_string(), ValueType::Float)],
         vec![("output".to_string(), ValueType::Float)],
     );
 
     let pass2 = graph.add_node(
-        NodeKind::Generic("pass".to_string()),
+        NodeKind::Registered("utility.reroute".to_string()),
         Vec2::ZERO,
         vec![("input".to_string(), ValueType::Float)],
         vec![("output".to_string(), ValueType::Float)],
@@ -204,7 +204,7 @@ fn test_optional_input_emits_constant_in_ir() {
     let mut graph = Graph::new();
 
     let node = graph.add_node_with_config(
-        NodeKind::Generic("pass".to_string()),
+        NodeKind::Registered("utility.reroute".to_string()),
         Vec2::ZERO,
         vec![InputDef::optional(
             "in",
@@ -342,3 +342,39 @@ fn test_mixed_connected_and_optional_inputs() {
         _ => panic!("expected Binary instruction"),
     }
 }
+
+#[test]
+fn test_each_root_output_keeps_its_value() {
+    let mut graph = Graph::new();
+    let color = graph.add_node(
+        NodeKind::Value(ValueType::Color),
+        Vec2::ZERO,
+        vec![],
+        vec![("Base Color".to_string(), ValueType::Color)],
+    );
+    let alpha = graph.add_node(
+        NodeKind::Value(ValueType::Float),
+        Vec2::ZERO,
+        vec![],
+        vec![("Alpha".to_string(), ValueType::Float)],
+    );
+
+    let view = build_graph_view(&graph, &[color, alpha]).unwrap();
+    let types = propagate_types(&view).unwrap();
+    let program = lower_to_ir(&view, &types).unwrap();
+
+    let names: Vec<&str> = program
+        .outputs
+        .iter()
+        .map(|output| output.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["Base Color", "Alpha"]);
+    assert_eq!(
+        program.value_type(program.outputs[0].value),
+        Some(IrType::Color)
+    );
+    assert_eq!(
+        program.value_type(program.outputs[1].value),
+        Some(IrType::Float)
+    );
+}
@@ -1,8 +1,14 @@
 #[cfg(test)]
+mod code;
+#[cfg(test)]
 mod diagnostics;
 #[cfg(test)]
 mod graph;
 #[cfg(test)]
+mod group;
+#[cfg(test)]
+mod incremental;
+#[cfg(test)]
 mod ir;
 #[cfg(test)]
 mod ir_conversion;
@@ -13,8 +19,20 @@ mod ir_lowering;
 #[cfg(test)]
 mod passes;
 #[cfg(test)]
+mod preview;
+#[cfg(test)]
+mod range_analysis;
+#[cfg(test)]
+mod registry;
+#[cfg(test)]
+mod simplify;
+#[cfg(test)]
+mod stats;
+#[cfg(test)]
 mod type_propagation;
 #[cfg(test)]
 mod types;
 #[cfg(test)]
 mod wgsl;
+#[cfg(all(test, feature = "naga"))]
+mod wgsl_import;
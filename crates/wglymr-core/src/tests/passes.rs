@@ -3,8 +3,8 @@ use std::collections::HashSet;
 use glam::Vec2;
 
 use crate::{
-    passes::{build_graph_view, detect_cycles, reachable_from, topological_sort},
-    Graph, GraphError, NodeId, NodeKind, ValueType,
+    passes::{analyze_graph, build_graph_view, detect_cycles, reachable_from, topological_sort},
+    Graph, GraphError, LinkId, NodeId, NodeKind, ValueType,
 };
 
 #[test]
@@ -12,21 +12,21 @@ fn test_simple_dag_has_no_cycles() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::ZERO,
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node3 = graph.add_node(
-        NodeKind::Generic("Node3".to_string()),
+        NodeKind::Registered("Node3".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![],
@@ -49,7 +49,7 @@ fn test_self_cycle_detected() {
     let mut graph = Graph::new();
 
     let node = graph.add_node(
-        NodeKind::Generic("SelfLoop".to_string()),
+        NodeKind::Registered("SelfLoop".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
@@ -70,21 +70,21 @@ fn test_multi_node_cycle_detected() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node3 = graph.add_node(
-        NodeKind::Generic("Node3".to_string()),
+        NodeKind::Registered("Node3".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
@@ -111,21 +111,21 @@ fn test_topological_sort_includes_disconnected_nodes() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::ZERO,
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![],
     );
 
     let disconnected = graph.add_node(
-        NodeKind::Generic("Disconnected".to_string()),
+        NodeKind::Registered("Disconnected".to_string()),
         Vec2::ZERO,
         vec![],
         vec![],
@@ -151,14 +151,14 @@ fn test_topological_sort_fails_on_cycle() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
@@ -173,7 +173,7 @@ fn test_topological_sort_fails_on_cycle() {
     graph.connect(out2, in1).unwrap();
 
     let result = topological_sort(&graph);
-    assert!(matches!(result, Err(GraphError::CycleDetected)));
+    assert!(matches!(result, Err(GraphError::CycleDetected { .. })));
 }
 
 #[test]
@@ -181,28 +181,28 @@ fn test_reachability_includes_only_contributing_nodes() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::ZERO,
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node3 = graph.add_node(
-        NodeKind::Generic("Node3".to_string()),
+        NodeKind::Registered("Node3".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![],
     );
 
     let disconnected = graph.add_node(
-        NodeKind::Generic("Disconnected".to_string()),
+        NodeKind::Registered("Disconnected".to_string()),
         Vec2::ZERO,
         vec![],
         vec![],
@@ -230,14 +230,14 @@ fn test_graph_remains_unchanged_after_analysis() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::ZERO,
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![],
@@ -266,21 +266,21 @@ fn test_build_graph_view() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::ZERO,
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node3 = graph.add_node(
-        NodeKind::Generic("Node3".to_string()),
+        NodeKind::Registered("Node3".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![],
@@ -314,14 +314,14 @@ fn test_build_graph_view_fails_on_cycle() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
@@ -336,7 +336,7 @@ fn test_build_graph_view_fails_on_cycle() {
     graph.connect(out2, in1).unwrap();
 
     let result = build_graph_view(&graph, &[node1]);
-    assert!(matches!(result, Err(GraphError::CycleDetected)));
+    assert!(matches!(result, Err(GraphError::CycleDetected { .. })));
 }
 
 #[test]
@@ -344,35 +344,35 @@ fn test_multiple_roots_work_correctly() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::ZERO,
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node3 = graph.add_node(
-        NodeKind::Generic("Node3".to_string()),
+        NodeKind::Registered("Node3".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![],
     );
 
     let node4 = graph.add_node(
-        NodeKind::Generic("Node4".to_string()),
+        NodeKind::Registered("Node4".to_string()),
         Vec2::ZERO,
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node5 = graph.add_node(
-        NodeKind::Generic("Node5".to_string()),
+        NodeKind::Registered("Node5".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![],
@@ -408,14 +408,14 @@ fn test_empty_roots_results_in_empty_reachable_set() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::ZERO,
         vec![],
         vec![("out".to_string(), ValueType::Float)],
     );
 
     let node2 = graph.add_node(
-        NodeKind::Generic("Node2".to_string()),
+        NodeKind::Registered("Node2".to_string()),
         Vec2::ZERO,
         vec![("in".to_string(), ValueType::Float)],
         vec![],
@@ -429,7 +429,7 @@ fn test_empty_roots_results_in_empty_reachable_set() {
 
     assert_eq!(view.roots.len(), 0);
     assert_eq!(view.reachable.len(), 0);
-    assert_eq!(view.topo_order.len(), 2);
+    assert!(view.topo_order.is_empty());
 }
 
 #[test]
@@ -437,7 +437,7 @@ fn test_invalid_root_node_returns_error() {
     let mut graph = Graph::new();
 
     let node1 = graph.add_node(
-        NodeKind::Generic("Node1".to_string()),
+        NodeKind::Registered("Node1".to_string()),
         Vec2::ZERO,
         vec![],
         vec![("out".to_string(), ValueType::Float)],
@@ -448,3 +448,156 @@ fn test_invalid_root_node_returns_error() {
     let result = build_graph_view(&graph, &[node1, invalid_node]);
     assert!(matches!(result, Err(GraphError::NodeNotFound { .. })));
 }
+
+/// Pass-through node with one float input and output
+fn add_relay(graph: &mut Graph, name: &str) -> NodeId {
+    graph.add_node(
+        NodeKind::Registered(name.to_string()),
+        Vec2::ZERO,
+        vec![("in".to_string(), ValueType::Float)],
+        vec![("out".to_string(), ValueType::Float)],
+    )
+}
+
+fn link(graph: &mut Graph, from: NodeId, to: NodeId) -> LinkId {
+    let out = graph.node(from).unwrap().outputs[0];
+    let input = graph.node(to).unwrap().inputs[0];
+    graph.connect(out, input).unwrap()
+}
+
+#[test]
+fn test_view_topo_order_only_lists_reachable_nodes() {
+    let mut graph = Graph::new();
+    let a = add_relay(&mut graph, "A");
+    let b = add_relay(&mut graph, "B");
+    let c = add_relay(&mut graph, "C");
+    let unrelated = add_relay(&mut graph, "Unrelated");
+    link(&mut graph, a, b);
+    link(&mut graph, b, c);
+    link(&mut graph, unrelated, unrelated);
+
+    let view = build_graph_view(&graph, &[c]).unwrap();
+
+    assert_eq!(view.topo_order, vec![a, b, c]);
+    assert!(!view.reachable.contains(&unrelated));
+}
+
+#[test]
+fn test_topological_order_is_deterministic() {
+    let mut graph = Graph::new();
+    let sum = graph.add_node(
+        NodeKind::Registered("Sum".to_string()),
+        Vec2::ZERO,
+        vec![
+            ("a".to_string(), ValueType::Float),
+            ("b".to_string(), ValueType::Float),
+        ],
+        vec![("out".to_string(), ValueType::Float)],
+    );
+    let sources: Vec<NodeId> = (0..8)
+        .map(|i| add_relay(&mut graph, &format!("Source{i}")))
+        .collect();
+    let a = graph.node(sum).unwrap().inputs[0];
+    let b = graph.node(sum).unwrap().inputs[1];
+    graph
+        .connect(graph.node(sources[5]).unwrap().outputs[0], b)
+        .unwrap();
+    graph
+        .connect(graph.node(sources[2]).unwrap().outputs[0], a)
+        .unwrap();
+
+    let first = build_graph_view(&graph, &[sum]).unwrap().topo_order;
+    assert_eq!(first, vec![sources[2], sources[5], sum]);
+    for _ in 0..10 {
+        assert_eq!(build_graph_view(&graph, &[sum]).unwrap().topo_order, first);
+        assert_eq!(
+            topological_sort(&graph).unwrap(),
+            topological_sort(&graph).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_cycle_report_lists_nodes_and_links() {
+    let mut graph = Graph::new();
+    let source = add_relay(&mut graph, "Source");
+    let a = add_relay(&mut graph, "A");
+    let b = graph.add_node(
+        NodeKind::Registered("B".to_string()),
+        Vec2::ZERO,
+        vec![
+            ("in".to_string(), ValueType::Float),
+            ("extra".to_string(), ValueType::Float),
+        ],
+        vec![("out".to_string(), ValueType::Float)],
+    );
+    let c = add_relay(&mut graph, "C");
+    let sink = add_relay(&mut graph, "Sink");
+
+    let a_to_b = link(&mut graph, a, b);
+    let b_to_c = link(&mut graph, b, c);
+    let c_to_a = link(&mut graph, c, a);
+    let extra = graph.node(b).unwrap().inputs[1];
+    graph
+        .connect(graph.node(source).unwrap().outputs[0], extra)
+        .unwrap();
+    link(&mut graph, c, sink);
+
+    let analysis = analyze_graph(&graph, &[sink]);
+
+    assert_eq!(analysis.cycles.len(), 1);
+    let cycle = &analysis.cycles[0];
+    assert_eq!(cycle.nodes, vec![a, b, c]);
+    assert_eq!(cycle.links, vec![a_to_b, b_to_c, c_to_a]);
+    assert_eq!(cycle.path, vec![a_to_b, b_to_c, c_to_a]);
+    assert_eq!(analysis.cycle_of(b), Some(cycle));
+    assert!(analysis.cycle_of(source).is_none());
+    assert_eq!(analysis.reachable.len(), 5);
+}
+
+#[test]
+fn test_self_loop_cycle_path() {
+    let mut graph = Graph::new();
+    let node = add_relay(&mut graph, "Loop");
+    let self_link = link(&mut graph, node, node);
+
+    let analysis = analyze_graph(&graph, &[node]);
+
+    assert_eq!(analysis.cycles.len(), 1);
+    assert_eq!(analysis.cycles[0].nodes, vec![node]);
+    assert_eq!(analysis.cycles[0].path, vec![self_link]);
+}
+
+#[test]
+fn test_unreachable_cycle_does_not_fail_view() {
+    let mut graph = Graph::new();
+    let source = add_relay(&mut graph, "Source");
+    let sink = add_relay(&mut graph, "Sink");
+    let a = add_relay(&mut graph, "A");
+    let b = add_relay(&mut graph, "B");
+    link(&mut graph, source, sink);
+    link(&mut graph, a, b);
+    link(&mut graph, b, a);
+
+    let view = build_graph_view(&graph, &[sink]).unwrap();
+    assert_eq!(view.topo_order, vec![source, sink]);
+
+    assert!(matches!(
+        build_graph_view(&graph, &[a]),
+        Err(GraphError::CycleDetected { .. })
+    ));
+}
+
+#[test]
+fn test_long_chain_analysis() {
+    let mut graph = Graph::new();
+    let nodes: Vec<NodeId> = (0..5_000)
+        .map(|i| add_relay(&mut graph, &format!("N{i}")))
+        .collect();
+    for pair in nodes.windows(2) {
+        link(&mut graph, pair[0], pair[1]);
+    }
+
+    let view = build_graph_view(&graph, &[*nodes.last().unwrap()]).unwrap();
+    assert_eq!(view.topo_order, nodes);
+}
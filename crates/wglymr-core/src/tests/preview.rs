@@ -0,0 +1,147 @@
+use glam::Vec2;
+
+use crate::{
+    compile_preview, compile_preview_with, emit_wgsl, CompileOptions, Graph, IrProgram, IrType,
+    MathOp, NodeId, NodeKind, NodeRegistry, PreviewError, SocketId, ValueType,
+};
+
+fn value_node(graph: &mut Graph, ty: ValueType) -> NodeId {
+    graph.add_node(
+        NodeKind::Value(ty),
+        Vec2::ZERO,
+        vec![],
+        vec![("out".to_string(), ty)],
+    )
+}
+
+/// Preview without simplification, so the program mirrors the graph
+fn unsimplified_preview(graph: &Graph, socket: SocketId) -> IrProgram {
+    compile_preview_with(
+        graph,
+        socket,
+        NodeRegistry::builtin(),
+        CompileOptions { simplify: false },
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_scalar_previews_as_gray() {
+    let mut graph = Graph::new();
+    let value = value_node(&mut graph, ValueType::Float);
+    let socket = graph.node(value).unwrap().outputs[0];
+
+    let program = compile_preview(&graph, socket).unwrap();
+
+    assert_eq!(program.outputs.len(), 1);
+    assert_eq!(
+        program.value_type(program.outputs[0].value),
+        Some(IrType::Color)
+    );
+
+    let wgsl = emit_wgsl(&program);
+    assert!(wgsl.contains("fn main() -> vec4<f32>"));
+    assert!(wgsl.contains("vec3<f32>(v0)"));
+    assert!(wgsl.contains(".z, 1.0)"));
+}
+
+#[test]
+fn test_vec2_previews_as_red_green() {
+    let mut graph = Graph::new();
+    let value = value_node(&mut graph, ValueType::Vec2);
+    let socket = graph.node(value).unwrap().outputs[0];
+
+    let wgsl = emit_wgsl(&compile_preview(&graph, socket).unwrap());
+
+    assert!(wgsl.contains("vec4<f32>(v0, v1, v2)"));
+    #[cfg(feature = "naga")]
+    assert!(crate::validate_wgsl(&wgsl).is_ok());
+}
+
+#[test]
+fn test_preview_stops_at_probed_node() {
+    let mut graph = Graph::new();
+    let a = value_node(&mut graph, ValueType::Vec3);
+    let b = value_node(&mut graph, ValueType::Vec3);
+    let add = graph.add_node(
+        NodeKind::Math(MathOp::Add),
+        Vec2::ZERO,
+        vec![
+            ("a".to_string(), ValueType::Vec3),
+            ("b".to_string(), ValueType::Vec3),
+        ],
+        vec![("result".to_string(), ValueType::Vec3)],
+    );
+    let multiply = graph.add_node(
+        NodeKind::Math(MathOp::Multiply),
+        Vec2::ZERO,
+        vec![
+            ("a".to_string(), ValueType::Vec3),
+            ("b".to_string(), ValueType::Vec3),
+        ],
+        vec![("result".to_string(), ValueType::Vec3)],
+    );
+
+    let a_out = graph.node(a).unwrap().outputs[0];
+    let b_out = graph.node(b).unwrap().outputs[0];
+    let add_node = graph.node(add).unwrap().clone();
+    let multiply_inputs = graph.node(multiply).unwrap().inputs.clone();
+    graph.connect(a_out, add_node.inputs[0]).unwrap();
+    graph.connect(b_out, add_node.inputs[1]).unwrap();
+    graph
+        .connect(add_node.outputs[0], multiply_inputs[0])
+        .unwrap();
+    graph.connect(a_out, multiply_inputs[1]).unwrap();
+
+    let program = unsimplified_preview(&graph, add_node.outputs[0]);
+    let wgsl = emit_wgsl(&program);
+
+    assert!(wgsl.contains(" + "));
+    assert!(!wgsl.contains(" * "));
+}
+
+#[test]
+fn test_preview_rejects_input_sockets_and_matrices() {
+    let mut graph = Graph::new();
+    let add = graph.add_node(
+        NodeKind::Math(MathOp::Add),
+        Vec2::ZERO,
+        vec![
+            ("a".to_string(), ValueType::Float),
+            ("b".to_string(), ValueType::Float),
+        ],
+        vec![("result".to_string(), ValueType::Float)],
+    );
+    let input = graph.node(add).unwrap().inputs[0];
+    assert!(matches!(
+        compile_preview(&graph, input),
+        Err(PreviewError::NotAnOutput(_))
+    ));
+
+    let matrix = value_node(&mut graph, ValueType::Mat3);
+    let matrix_out = graph.node(matrix).unwrap().outputs[0];
+    assert!(matches!(
+        compile_preview(&graph, matrix_out),
+        Err(PreviewError::UnsupportedType(ValueType::Mat3))
+    ));
+}
+
+#[test]
+fn test_preview_uses_probed_socket_not_first_output() {
+    let mut graph = Graph::new();
+    let code = graph.add_node(
+        NodeKind::Code("sum = 1.0; diff = 2.0;".to_string()),
+        Vec2::ZERO,
+        vec![],
+        vec![
+            ("sum".to_string(), ValueType::Float),
+            ("diff".to_string(), ValueType::Float),
+        ],
+    );
+    let diff = graph.node(code).unwrap().outputs[1];
+
+    let wgsl = emit_wgsl(&unsimplified_preview(&graph, diff));
+
+    assert!(wgsl.contains("vec3<f32>(v1)"));
+    assert!(wgsl.contains(&format!("code_node_{}_diff()", code.0)));
+}
@@ -0,0 +1,307 @@
+use glam::Vec2;
+
+use crate::{
+    analyze_ranges, build_graph_view, insert_conversions, lower_to_ir, propagate_types, BinaryOp,
+    DiagnosticSeverity, Graph, Interval, IrInst, IrOutput, IrProgram, IrType, Literal, MathOp,
+    NodeId, NodeKind, NodeRegistry, RangeHints, ValueId, ValueRange, ValueType,
+};
+
+/// `value / uv` with the divide node returned alongside the graph
+fn divide_by_uv() -> (Graph, NodeId) {
+    let registry = NodeRegistry::builtin();
+    let mut graph = Graph::new();
+    let uv = registry
+        .instantiate(&mut graph, "input.uv", Vec2::ZERO)
+        .unwrap();
+    let value = registry
+        .instantiate(&mut graph, "value.vec2", Vec2::ZERO)
+        .unwrap();
+    let divide = graph.add_node(
+        NodeKind::Math(MathOp::Divide),
+        Vec2::ZERO,
+        vec![
+            ("a".to_string(), ValueType::Vec2),
+            ("b".to_string(), ValueType::Vec2),
+        ],
+        vec![("result".to_string(), ValueType::Vec2)],
+    );
+
+    let uv_out = graph.node(uv).unwrap().outputs[0];
+    let value_out = graph.node(value).unwrap().outputs[0];
+    let inputs = graph.node(divide).unwrap().inputs.clone();
+    graph.connect(value_out, inputs[0]).unwrap();
+    graph.connect(uv_out, inputs[1]).unwrap();
+
+    (graph, divide)
+}
+
+fn compile(graph: &Graph, root: NodeId) -> IrProgram {
+    let view = build_graph_view(graph, &[root]).unwrap();
+    let types = propagate_types(&view).unwrap();
+    insert_conversions(lower_to_ir(&view, &types).unwrap()).unwrap()
+}
+
+#[test]
+fn test_division_by_uv_warns_on_node() {
+    let (graph, divide) = divide_by_uv();
+    let program = compile(&graph, divide);
+
+    let analysis = analyze_ranges(&program, &RangeHints::default());
+
+    assert_eq!(analysis.diagnostics.len(), 1);
+    let warning = &analysis.diagnostics[0];
+    assert_eq!(warning.severity, DiagnosticSeverity::Warning);
+    assert_eq!(warning.node, Some(divide));
+    assert_eq!(warning.message, "Possible division by zero");
+}
+
+#[test]
+fn test_input_hints_narrow_ranges() {
+    let (graph, divide) = divide_by_uv();
+    let program = compile(&graph, divide);
+
+    let hints =
+        RangeHints::default().with_input("uv", ValueRange::splat(Interval::new(0.5, 1.0), 2));
+    let analysis = analyze_ranges(&program, &hints);
+
+    assert!(analysis.diagnostics.is_empty());
+}
+
+#[test]
+fn test_constant_ranges_propagate() {
+    let program = IrProgram {
+        instructions: vec![
+            IrInst::Constant {
+                value: Literal::Float(2.0),
+                ty: IrType::Float,
+            },
+            IrInst::Constant {
+                value: Literal::Float(-3.0),
+                ty: IrType::Float,
+            },
+            IrInst::Binary {
+                op: BinaryOp::Mul,
+                lhs: ValueId(0),
+                rhs: ValueId(1),
+                ty: IrType::Float,
+            },
+            IrInst::Binary {
+                op: BinaryOp::Div,
+                lhs: ValueId(2),
+                rhs: ValueId(0),
+                ty: IrType::Float,
+            },
+        ],
+        ..Default::default()
+    };
+
+    let analysis = analyze_ranges(&program, &RangeHints::default());
+
+    assert!(analysis.diagnostics.is_empty());
+    assert_eq!(
+        analysis.range(ValueId(3)).unwrap().components,
+        vec![Interval::point(-3.0)]
+    );
+}
+
+/// Range of `clamp(x, low, high)` for the given argument ranges
+fn clamp_range(x: Interval, low: Interval, high: Interval) -> Interval {
+    let input = |name: &str| IrInst::Input {
+        name: name.to_string(),
+        ty: IrType::Float,
+    };
+    let program = IrProgram {
+        instructions: vec![
+            input("x"),
+            input("low"),
+            input("high"),
+            IrInst::Call {
+                function: "clamp".to_string(),
+                args: vec![ValueId(0), ValueId(1), ValueId(2)],
+                ty: IrType::Float,
+            },
+        ],
+        ..Default::default()
+    };
+    let hints = RangeHints::default()
+        .with_input("x", ValueRange::splat(x, 1))
+        .with_input("low", ValueRange::splat(low, 1))
+        .with_input("high", ValueRange::splat(high, 1));
+
+    let analysis = analyze_ranges(&program, &hints);
+    analysis.range(ValueId(3)).unwrap().components[0]
+}
+
+#[test]
+fn test_clamp_ranges() {
+    // Constant bounds
+    assert_eq!(
+        clamp_range(
+            Interval::new(-5.0, 5.0),
+            Interval::point(0.0),
+            Interval::point(1.0)
+        ),
+        Interval::new(0.0, 1.0)
+    );
+    // Input already inside the bounds
+    assert_eq!(
+        clamp_range(
+            Interval::new(0.25, 0.5),
+            Interval::point(0.0),
+            Interval::point(1.0)
+        ),
+        Interval::new(0.25, 0.5)
+    );
+    // A low bound above the input raises the result up to the high bound
+    assert_eq!(
+        clamp_range(
+            Interval::new(0.0, 1.0),
+            Interval::new(0.0, 5.0),
+            Interval::point(2.0)
+        ),
+        Interval::new(0.0, 2.0)
+    );
+    // A high bound below the low bound wins
+    assert_eq!(
+        clamp_range(
+            Interval::new(0.0, 1.0),
+            Interval::point(3.0),
+            Interval::point(2.0)
+        ),
+        Interval::point(2.0)
+    );
+}
+
+#[test]
+fn test_mat2_products_are_not_elementwise() {
+    // identity * uv and uv * identity, with uv in [0, 1]
+    let program = IrProgram {
+        instructions: vec![
+            IrInst::Constant {
+                value: Literal::Mat2([1.0, 0.0, 0.0, 1.0]),
+                ty: IrType::Mat2,
+            },
+            IrInst::Input {
+                name: "uv".to_string(),
+                ty: IrType::Vec2,
+            },
+            IrInst::Binary {
+                op: BinaryOp::Mul,
+                lhs: ValueId(0),
+                rhs: ValueId(1),
+                ty: IrType::Vec2,
+            },
+            IrInst::Binary {
+                op: BinaryOp::Mul,
+                lhs: ValueId(1),
+                rhs: ValueId(0),
+                ty: IrType::Vec2,
+            },
+        ],
+        ..Default::default()
+    };
+    let hints =
+        RangeHints::default().with_input("uv", ValueRange::splat(Interval::new(0.0, 1.0), 2));
+
+    let analysis = analyze_ranges(&program, &hints);
+
+    for value in [ValueId(2), ValueId(3)] {
+        let range = analysis.range(value).unwrap();
+        assert!(range.components.iter().all(|c| !c.is_bounded()));
+    }
+}
+
+#[test]
+fn test_constant_zero_divisor_is_reported() {
+    let program = IrProgram {
+        instructions: vec![
+            IrInst::Constant {
+                value: Literal::Float(1.0),
+                ty: IrType::Float,
+            },
+            IrInst::Constant {
+                value: Literal::Float(0.0),
+                ty: IrType::Float,
+            },
+            IrInst::Binary {
+                op: BinaryOp::Div,
+                lhs: ValueId(0),
+                rhs: ValueId(1),
+                ty: IrType::Float,
+            },
+        ],
+        ..Default::default()
+    };
+
+    let analysis = analyze_ranges(&program, &RangeHints::default());
+
+    assert_eq!(analysis.diagnostics.len(), 1);
+    assert_eq!(analysis.diagnostics[0].message, "Division by zero");
+    assert_eq!(analysis.diagnostics[0].node, None);
+}
+
+#[test]
+fn test_pow_with_negative_base_is_reported() {
+    let program = IrProgram {
+        instructions: vec![
+            IrInst::Input {
+                name: "uv".to_string(),
+                ty: IrType::Vec2,
+            },
+            IrInst::Constant {
+                value: Literal::Vec2([0.5, 0.5]),
+                ty: IrType::Vec2,
+            },
+            IrInst::Binary {
+                op: BinaryOp::Sub,
+                lhs: ValueId(0),
+                rhs: ValueId(1),
+                ty: IrType::Vec2,
+            },
+            IrInst::Call {
+                function: "pow".to_string(),
+                args: vec![ValueId(2), ValueId(1)],
+                ty: IrType::Vec2,
+            },
+        ],
+        ..Default::default()
+    };
+
+    let analysis = analyze_ranges(&program, &RangeHints::default());
+
+    assert_eq!(
+        analysis.range(ValueId(2)).unwrap().components,
+        vec![Interval::new(-0.5, 0.5); 2]
+    );
+    assert_eq!(analysis.diagnostics.len(), 1);
+    assert!(analysis.diagnostics[0].message.contains("pow"));
+}
+
+#[test]
+fn test_out_of_gamut_color_output_is_reported() {
+    let program = IrProgram {
+        instructions: vec![
+            IrInst::Constant {
+                value: Literal::Vec3([1.5, 0.2, 0.0]),
+                ty: IrType::Vec3,
+            },
+            IrInst::Convert {
+                from: ValueId(0),
+                from_ty: IrType::Vec3,
+                to_ty: IrType::Color,
+            },
+        ],
+        outputs: vec![IrOutput {
+            name: "Base Color".to_string(),
+            value: ValueId(1),
+        }],
+        origins: vec![Some(NodeId(4)), Some(NodeId(4))],
+        ..Default::default()
+    };
+
+    let analysis = analyze_ranges(&program, &RangeHints::default());
+
+    assert_eq!(analysis.diagnostics.len(), 1);
+    assert_eq!(analysis.diagnostics[0].node, Some(NodeId(4)));
+    assert!(analysis.diagnostics[0].message.contains("Base Color"));
+}
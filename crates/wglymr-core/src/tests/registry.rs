@@ -0,0 +1,396 @@
+use glam::Vec2;
+
+use crate::{
+    build_graph_view, lower_to_ir_with_registry, propagate_types_with_registry, BinaryOp, Graph,
+    IrInst, Literal, LoweringRule, MathOp, NodeDefinition, NodeKind, NodeRegistry, RegistryError,
+    SocketSpec, TypeError, TypeRule, ValueType,
+};
+
+fn lerp_like_definition() -> NodeDefinition {
+    NodeDefinition {
+        id: "custom.scale".to_string(),
+        category: "Custom".to_string(),
+        display_name: "Scale".to_string(),
+        inputs: vec![
+            SocketSpec::required("Value", ValueType::Vec3),
+            SocketSpec::optional("Factor", ValueType::Vec3, Literal::Vec3([2.0, 2.0, 2.0])),
+        ],
+        outputs: vec![SocketSpec::output("Result", ValueType::Vec3)],
+        properties: vec![],
+        variants: vec![],
+        type_rule: TypeRule::Unify,
+        lowering: LoweringRule::Binary(BinaryOp::Mul),
+    }
+}
+
+#[test]
+fn test_builtins_are_registered_by_category() {
+    let registry = NodeRegistry::with_builtins();
+
+    assert!(registry.get("math.add").is_some());
+    assert!(registry.get("value.color").is_some());
+    assert!(registry.get("utility.reroute").is_some());
+    assert_eq!(
+        registry.categories(),
+        vec!["Input", "Math", "Matrix", "Layout"]
+    );
+    assert!(registry.get("math.math").is_some());
+    assert_eq!(registry.definitions_in_category("Math").count(), 5);
+}
+
+#[test]
+fn test_duplicate_definition_is_rejected() {
+    let mut registry = NodeRegistry::new();
+    registry.register(lerp_like_definition()).unwrap();
+
+    let result = registry.register(lerp_like_definition());
+    assert!(matches!(result, Err(RegistryError::DuplicateDefinition(id)) if id == "custom.scale"));
+}
+
+#[test]
+fn test_definition_ids_round_trip_through_node_kind() {
+    assert_eq!(
+        NodeKind::from_definition_id("math.divide"),
+        NodeKind::Math(MathOp::Divide)
+    );
+    assert_eq!(
+        NodeKind::from_definition_id("value.vec3"),
+        NodeKind::Value(ValueType::Vec3)
+    );
+    assert_eq!(
+        NodeKind::from_definition_id("custom.scale"),
+        NodeKind::Registered("custom.scale".to_string())
+    );
+    assert_eq!(
+        NodeKind::Math(MathOp::Subtract).definition_id().as_deref(),
+        Some("math.subtract")
+    );
+}
+
+#[test]
+fn test_instantiate_builds_sockets_from_definition() {
+    let registry = NodeRegistry::with_builtins();
+    let mut graph = Graph::new();
+
+    let node_id = registry
+        .instantiate(&mut graph, "math.multiply", Vec2::ZERO)
+        .unwrap();
+
+    let node = graph.node(node_id).unwrap();
+    assert_eq!(node.kind, NodeKind::Math(MathOp::Multiply));
+    assert_eq!(node.inputs.len(), 2);
+    assert_eq!(node.outputs.len(), 1);
+
+    let b = graph.socket(node.inputs[1]).unwrap();
+    assert_eq!(b.name, "B");
+    let config = b.input_config.as_ref().unwrap();
+    assert!(config.optional);
+    assert_eq!(config.default, Some(Literal::Float(1.0)));
+}
+
+#[test]
+fn test_instantiate_unknown_definition_fails() {
+    let registry = NodeRegistry::with_builtins();
+    let mut graph = Graph::new();
+
+    let result = registry.instantiate(&mut graph, "missing.node", Vec2::ZERO);
+    assert!(matches!(result, Err(RegistryError::UnknownDefinition(_))));
+}
+
+#[test]
+fn test_custom_definition_compiles_through_registry() {
+    let mut registry = NodeRegistry::with_builtins();
+    registry.register(lerp_like_definition()).unwrap();
+
+    let mut graph = Graph::new();
+    let value = registry
+        .instantiate(&mut graph, "value.vec3", Vec2::ZERO)
+        .unwrap();
+    let scale = registry
+        .instantiate(&mut graph, "custom.scale", Vec2::ZERO)
+        .unwrap();
+
+    let value_out = graph.node(value).unwrap().outputs[0];
+    let scale_in = graph.node(scale).unwrap().inputs[0];
+    graph.connect(value_out, scale_in).unwrap();
+
+    let view = build_graph_view(&graph, &[scale]).unwrap();
+    let types = propagate_types_with_registry(&view, &registry).unwrap();
+    let program = lower_to_ir_with_registry(&view, &types, &registry).unwrap();
+
+    assert_eq!(program.instructions.len(), 3);
+    assert!(matches!(
+        program.instructions[2],
+        IrInst::Binary {
+            op: BinaryOp::Mul,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_reroute_exists_for_every_value_type() {
+    let registry = NodeRegistry::with_builtins();
+    let mut graph = Graph::new();
+    let value = registry
+        .instantiate(&mut graph, "value.color", Vec2::ZERO)
+        .unwrap();
+    let reroute = registry
+        .instantiate(&mut graph, "utility.reroute.color", Vec2::ZERO)
+        .unwrap();
+
+    let value_out = graph.node(value).unwrap().outputs[0];
+    let reroute_in = graph.node(reroute).unwrap().inputs[0];
+    let reroute_out = graph.node(reroute).unwrap().outputs[0];
+    graph.connect(value_out, reroute_in).unwrap();
+
+    let view = build_graph_view(&graph, &[reroute]).unwrap();
+    let types = propagate_types_with_registry(&view, &registry).unwrap();
+    assert_eq!(types.get(reroute_out), Some(ValueType::Color));
+    assert!(lower_to_ir_with_registry(&view, &types, &registry).is_ok());
+    assert_eq!(
+        registry.get("utility.reroute").unwrap().inputs[0].value_type,
+        ValueType::Float
+    );
+}
+
+#[test]
+fn test_unregistered_kind_fails_type_propagation() {
+    let registry = NodeRegistry::with_builtins();
+    let mut graph = Graph::new();
+    let node = graph.add_node(
+        NodeKind::Registered("custom.scale".to_string()),
+        Vec2::ZERO,
+        vec![],
+        vec![("Result".to_string(), ValueType::Vec3)],
+    );
+
+    let view = build_graph_view(&graph, &[node]).unwrap();
+    let result = propagate_types_with_registry(&view, &registry);
+
+    assert!(matches!(
+        result,
+        Err(TypeError::UnknownNodeDefinition(id)) if id == "custom.scale"
+    ));
+}
+
+#[test]
+fn test_wrong_input_count_reports_arity() {
+    let mut graph = Graph::new();
+    let node = graph.add_node(
+        NodeKind::Math(MathOp::Add),
+        Vec2::ZERO,
+        vec![],
+        vec![("Result".to_string(), ValueType::Float)],
+    );
+
+    let view = build_graph_view(&graph, &[node]).unwrap();
+    let result = crate::propagate_types(&view);
+
+    assert!(matches!(
+        result,
+        Err(TypeError::ArityMismatch {
+            expected: 2,
+            found: 0
+        })
+    ));
+}
+
+#[test]
+fn test_matrix_nodes_compile_to_valid_wgsl() {
+    let registry = NodeRegistry::with_builtins();
+    let mut graph = Graph::new();
+
+    let vector = registry
+        .instantiate(&mut graph, "value.vec3", Vec2::ZERO)
+        .unwrap();
+    let inverse = registry
+        .instantiate(&mut graph, "matrix.inverse_mat3", Vec2::ZERO)
+        .unwrap();
+    let transpose = registry
+        .instantiate(&mut graph, "matrix.transpose_mat3", Vec2::ZERO)
+        .unwrap();
+    let transform = registry
+        .instantiate(&mut graph, "matrix.transform_vec3", Vec2::ZERO)
+        .unwrap();
+
+    let inverse_out = graph.node(inverse).unwrap().outputs[0];
+    let transpose_node = graph.node(transpose).unwrap().clone();
+    let transform_inputs = graph.node(transform).unwrap().inputs.clone();
+    let vector_out = graph.node(vector).unwrap().outputs[0];
+    graph
+        .connect(inverse_out, transpose_node.inputs[0])
+        .unwrap();
+    graph
+        .connect(transpose_node.outputs[0], transform_inputs[0])
+        .unwrap();
+    graph.connect(vector_out, transform_inputs[1]).unwrap();
+
+    let view = build_graph_view(&graph, &[transform]).unwrap();
+    let types = propagate_types_with_registry(&view, &registry).unwrap();
+    assert_eq!(
+        types.get(graph.node(transform).unwrap().outputs[0]),
+        Some(ValueType::Vec3)
+    );
+
+    let program = lower_to_ir_with_registry(&view, &types, &registry).unwrap();
+    let program = crate::insert_conversions(program).unwrap();
+    let wgsl = crate::emit_wgsl(&program);
+
+    assert!(wgsl.contains("fn inverse_mat3"));
+    assert!(wgsl.contains("transpose("));
+    #[cfg(feature = "naga")]
+    assert!(crate::validate_wgsl(&wgsl).is_ok(), "{}", wgsl);
+}
+
+#[test]
+fn test_instantiate_carries_socket_metadata() {
+    use crate::{SocketMetadata, SocketSubtype};
+
+    let mut definition = lerp_like_definition();
+    definition.inputs[1] = definition.inputs[1].clone().with_metadata(
+        SocketMetadata::default()
+            .with_soft_range(0.0, 4.0)
+            .with_step(0.1)
+            .with_subtype(SocketSubtype::Factor)
+            .with_description("Amount to scale by"),
+    );
+    let mut registry = NodeRegistry::new();
+    registry.register(definition).unwrap();
+
+    let mut graph = Graph::new();
+    let node = registry
+        .instantiate(&mut graph, "custom.scale", Vec2::ZERO)
+        .unwrap();
+    let factor = graph.node(node).unwrap().inputs[1];
+    let metadata = &graph
+        .socket(factor)
+        .unwrap()
+        .input_config
+        .as_ref()
+        .unwrap()
+        .metadata;
+
+    assert_eq!(metadata.soft_max, Some(4.0));
+    assert_eq!(metadata.step, Some(0.1));
+    assert_eq!(metadata.subtype, SocketSubtype::Factor);
+    assert_eq!(metadata.description.as_deref(), Some("Amount to scale by"));
+    assert_eq!(metadata.hard_min, None);
+}
+
+fn math_node(graph: &mut Graph, registry: &NodeRegistry) -> crate::NodeId {
+    registry
+        .instantiate(graph, "math.math", Vec2::ZERO)
+        .unwrap()
+}
+
+#[test]
+fn test_math_operation_switches_in_place() {
+    use crate::{analyze_ranges, compile_graph, PropertyValue, RangeHints};
+
+    let registry = NodeRegistry::builtin();
+    let mut graph = Graph::new();
+    let node = math_node(&mut graph, registry);
+    let inputs = graph.node(node).unwrap().inputs.clone();
+    graph
+        .set_input_default(inputs[0], Literal::Float(3.0))
+        .unwrap();
+    graph
+        .set_input_default(inputs[1], Literal::Float(2.0))
+        .unwrap();
+
+    let wgsl = compile_graph(&graph, &[node]).unwrap().wgsl;
+    assert!(wgsl.contains(" + "));
+
+    let previous = graph
+        .set_node_property(node, "operation", PropertyValue::Enum("multiply".into()))
+        .unwrap();
+    assert_eq!(previous, None);
+    let wgsl = compile_graph(&graph, &[node]).unwrap().wgsl;
+    assert!(wgsl.contains(" * "));
+    assert!(!wgsl.contains(" + "));
+
+    graph
+        .set_node_property(node, "operation", PropertyValue::Enum("power".into()))
+        .unwrap();
+    let wgsl = compile_graph(&graph, &[node]).unwrap().wgsl;
+    assert!(wgsl.contains("pow("));
+    assert_eq!(graph.node(node).unwrap().inputs, inputs);
+
+    // Untouched defaults stay valid for every operation
+    let fresh = math_node(&mut graph, registry);
+    graph
+        .set_node_property(fresh, "operation", PropertyValue::Enum("divide".into()))
+        .unwrap();
+    let divisor = graph.node(fresh).unwrap().inputs[1];
+    let config = graph
+        .socket(divisor)
+        .unwrap()
+        .input_config
+        .as_ref()
+        .unwrap();
+    assert_eq!(config.default, Some(Literal::Float(0.5)));
+    let program = compile_graph(&graph, &[fresh]).unwrap().program;
+    let analysis = analyze_ranges(&program, &RangeHints::default());
+    assert!(analysis.diagnostics.is_empty());
+}
+
+#[test]
+fn test_single_input_operation_hides_second_input() {
+    use crate::{compile_graph, PropertyValue};
+
+    let registry = NodeRegistry::builtin();
+    let mut graph = Graph::new();
+    let node = math_node(&mut graph, registry);
+    graph
+        .set_node_property(node, "operation", PropertyValue::Enum("sqrt".into()))
+        .unwrap();
+
+    let definition = registry.get("math.math").unwrap();
+    let math = graph.node(node).unwrap();
+    assert_eq!(definition.active_inputs(&math.properties), vec![0]);
+    assert_eq!(registry.active_input_sockets(math), vec![math.inputs[0]]);
+
+    // A link into the hidden input is ignored
+    let source = registry
+        .instantiate(&mut graph, "math.math", Vec2::ZERO)
+        .unwrap();
+    let source_out = graph.node(source).unwrap().outputs[0];
+    let hidden = graph.node(node).unwrap().inputs[1];
+    graph.connect(source_out, hidden).unwrap();
+
+    let compiled = compile_graph(&graph, &[node]).unwrap();
+    assert!(compiled.wgsl.contains("sqrt("));
+    assert!(!compiled.program.origins.contains(&Some(source)));
+}
+
+#[test]
+fn test_node_property_values_are_checked_against_definition() {
+    use crate::{PropertyError, PropertySpec, PropertyValue};
+
+    let registry = NodeRegistry::builtin();
+    let definition = registry.get("math.math").unwrap();
+
+    assert!(definition
+        .check_property("operation", &PropertyValue::Enum("divide".into()))
+        .is_ok());
+    assert!(matches!(
+        definition.check_property("operation", &PropertyValue::Enum("modulo".into())),
+        Err(PropertyError::UnknownOption { .. })
+    ));
+    assert!(matches!(
+        definition.check_property("operation", &PropertyValue::Bool(true)),
+        Err(PropertyError::WrongKind { .. })
+    ));
+    assert!(matches!(
+        definition.check_property("clamp", &PropertyValue::Bool(true)),
+        Err(PropertyError::UnknownProperty(_))
+    ));
+
+    let steps = PropertySpec::int("steps", 4).with_range(1, 8);
+    assert!(steps.check(&PropertyValue::Int(8)).is_ok());
+    assert!(matches!(
+        steps.check(&PropertyValue::Int(9)),
+        Err(PropertyError::OutOfRange { .. })
+    ));
+}
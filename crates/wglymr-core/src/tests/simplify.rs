@@ -0,0 +1,217 @@
+use glam::Vec2;
+
+use crate::{
+    compile_graph, compile_graph_with, emit_wgsl, simplify, BinaryOp, CompileOptions, Graph,
+    InputDef, IrInst, IrOutput, IrProgram, IrType, Literal, MathOp, NodeId, NodeKind, NodeRegistry,
+    ValueId, ValueType,
+};
+
+fn input(name: &str, ty: IrType) -> IrInst {
+    IrInst::Input {
+        name: name.to_string(),
+        ty,
+    }
+}
+
+fn constant(value: Literal, ty: IrType) -> IrInst {
+    IrInst::Constant { value, ty }
+}
+
+fn binary(op: BinaryOp, lhs: u32, rhs: u32, ty: IrType) -> IrInst {
+    IrInst::Binary {
+        op,
+        lhs: ValueId(lhs),
+        rhs: ValueId(rhs),
+        ty,
+    }
+}
+
+fn program(instructions: Vec<IrInst>, output: u32) -> IrProgram {
+    IrProgram {
+        instructions,
+        outputs: vec![IrOutput {
+            name: "out".to_string(),
+            value: ValueId(output),
+        }],
+        ..Default::default()
+    }
+}
+
+/// `((x * 1) + 0) / 1` where `x` is a Value node
+fn identity_chain(graph: &mut Graph) -> NodeId {
+    let x = graph.add_node(
+        NodeKind::Value(ValueType::Float),
+        Vec2::ZERO,
+        vec![],
+        vec![("out".to_string(), ValueType::Float)],
+    );
+    let mut previous = graph.node(x).unwrap().outputs[0];
+    let mut last = x;
+
+    for (op, identity) in [
+        (MathOp::Multiply, 1.0),
+        (MathOp::Add, 0.0),
+        (MathOp::Divide, 1.0),
+    ] {
+        last = graph.add_node_with_config(
+            NodeKind::Math(op),
+            Vec2::ZERO,
+            vec![
+                InputDef::required("a", ValueType::Float),
+                InputDef::optional("b", ValueType::Float, Literal::Float(identity)),
+            ],
+            vec![("result".to_string(), ValueType::Float)],
+        );
+        let node = graph.node(last).unwrap();
+        let (a, result) = (node.inputs[0], node.outputs[0]);
+        graph.connect(previous, a).unwrap();
+        previous = result;
+    }
+
+    last
+}
+
+#[test]
+fn test_identity_chain_shrinks_wgsl() {
+    let mut graph = Graph::new();
+    let root = identity_chain(&mut graph);
+
+    let plain = compile_graph_with(
+        &graph,
+        &[root],
+        NodeRegistry::builtin(),
+        CompileOptions { simplify: false },
+    )
+    .unwrap();
+    let simplified = compile_graph(&graph, &[root]).unwrap();
+
+    assert_eq!(plain.program.instructions.len(), 7);
+    assert_eq!(simplified.program.instructions.len(), 1);
+    assert!(simplified.wgsl.len() < plain.wgsl.len());
+    assert!(simplified.wgsl.contains("return v0;"));
+}
+
+#[test]
+fn test_division_by_constant_becomes_multiplication() {
+    let ir = program(
+        vec![
+            input("uv", IrType::Vec2),
+            constant(Literal::Vec2([2.0, 4.0]), IrType::Vec2),
+            binary(BinaryOp::Div, 0, 1, IrType::Vec2),
+        ],
+        2,
+    );
+
+    let result = simplify(ir);
+
+    assert!(result
+        .instructions
+        .contains(&constant(Literal::Vec2([0.5, 0.25]), IrType::Vec2)));
+    let output = result.outputs[0].value;
+    assert!(matches!(
+        result.instructions[output.0 as usize],
+        IrInst::Binary {
+            op: BinaryOp::Mul,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_division_by_zero_and_int_is_kept() {
+    for (divisor, ty) in [
+        (Literal::Float(0.0), IrType::Float),
+        (Literal::Int(2), IrType::Int),
+    ] {
+        let ir = program(
+            vec![
+                input("x", ty),
+                constant(divisor, ty),
+                binary(BinaryOp::Div, 0, 1, ty),
+            ],
+            2,
+        );
+
+        let result = simplify(ir.clone());
+
+        assert_eq!(result.instructions, ir.instructions);
+    }
+}
+
+#[test]
+fn test_double_negation_is_removed() {
+    let ir = program(
+        vec![
+            input("x", IrType::Float),
+            constant(Literal::Float(0.0), IrType::Float),
+            binary(BinaryOp::Sub, 1, 0, IrType::Float),
+            constant(Literal::Float(-1.0), IrType::Float),
+            binary(BinaryOp::Mul, 2, 3, IrType::Float),
+        ],
+        4,
+    );
+    let before = emit_wgsl(&ir);
+
+    let result = simplify(ir);
+
+    assert_eq!(result.instructions, vec![input("x", IrType::Float)]);
+    assert_eq!(result.outputs[0].value, ValueId(0));
+    assert!(emit_wgsl(&result).len() < before.len());
+}
+
+#[test]
+fn test_matrix_resize_chain_is_fused() {
+    let convert = |from: u32, from_ty: IrType, to_ty: IrType| IrInst::Convert {
+        from: ValueId(from),
+        from_ty,
+        to_ty,
+    };
+    let ir = program(
+        vec![
+            input("m", IrType::Mat2),
+            convert(0, IrType::Mat2, IrType::Mat4),
+            convert(1, IrType::Mat4, IrType::Mat3),
+        ],
+        2,
+    );
+
+    let result = simplify(ir);
+
+    assert_eq!(
+        result.instructions,
+        vec![
+            input("m", IrType::Mat2),
+            convert(0, IrType::Mat2, IrType::Mat3)
+        ]
+    );
+
+    // Truncating first loses columns, so the chain must stay
+    let lossy = program(
+        vec![
+            input("m", IrType::Mat4),
+            convert(0, IrType::Mat4, IrType::Mat2),
+            convert(1, IrType::Mat2, IrType::Mat3),
+        ],
+        2,
+    );
+    assert_eq!(simplify(lossy.clone()).instructions, lossy.instructions);
+}
+
+#[test]
+fn test_simplify_keeps_origins_and_unused_code_without_outputs() {
+    let mut graph = Graph::new();
+    let root = identity_chain(&mut graph);
+    let simplified = compile_graph(&graph, &[root]).unwrap();
+    assert_eq!(simplified.program.origins.len(), 1);
+    assert!(simplified.program.origins[0].is_some());
+
+    let ir = IrProgram {
+        instructions: vec![
+            input("x", IrType::Float),
+            constant(Literal::Float(0.0), IrType::Float),
+            binary(BinaryOp::Add, 0, 1, IrType::Float),
+        ],
+        ..Default::default()
+    };
+    assert_eq!(simplify(ir).instructions.len(), 2);
+}
@@ -0,0 +1,161 @@
+use glam::Vec2;
+
+use crate::{
+    compile_graph, compile_stats, BinaryOp, Graph, InputDef, IrInst, IrOutput, IrProgram, IrType,
+    Literal, MathOp, NodeId, NodeKind, ValueId, ValueType,
+};
+
+fn call(function: &str, args: Vec<u32>, ty: IrType) -> IrInst {
+    IrInst::Call {
+        function: function.to_string(),
+        args: args.into_iter().map(ValueId).collect(),
+        ty,
+    }
+}
+
+fn program(instructions: Vec<IrInst>, outputs: Vec<u32>) -> IrProgram {
+    IrProgram {
+        instructions,
+        outputs: outputs
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| IrOutput {
+                name: format!("out{}", index),
+                value: ValueId(value),
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_instructions_are_counted_by_category() {
+    let ir = program(
+        vec![
+            IrInst::Input {
+                name: "uv".to_string(),
+                ty: IrType::Vec2,
+            },
+            call("sin", vec![0], IrType::Vec2),
+            call("textureSample", vec![0], IrType::Vec4),
+            call("code_node_7", vec![1], IrType::Float),
+            IrInst::Convert {
+                from: ValueId(3),
+                from_ty: IrType::Float,
+                to_ty: IrType::Vec4,
+            },
+            IrInst::Binary {
+                op: BinaryOp::Add,
+                lhs: ValueId(2),
+                rhs: ValueId(4),
+                ty: IrType::Vec4,
+            },
+        ],
+        vec![5],
+    );
+
+    let stats = compile_stats(&ir);
+
+    assert_eq!(stats.counts.alu, 2);
+    assert_eq!(stats.counts.conversions, 1);
+    assert_eq!(stats.counts.texture_samples, 1);
+    assert_eq!(stats.counts.helper_calls, 1);
+    // sin: 4 * 2, texture: 16, helper: 8, convert: 1, add: 4
+    assert_eq!(stats.estimated_cost, 37);
+}
+
+#[test]
+fn test_matrix_products_cost_more_than_componentwise_ops() {
+    let matrix = |value| IrInst::Constant {
+        value,
+        ty: IrType::Mat4,
+    };
+    let identity = Literal::Mat4(std::array::from_fn(|i| if i % 5 == 0 { 1.0 } else { 0.0 }));
+    let product = |op| {
+        program(
+            vec![
+                matrix(identity.clone()),
+                matrix(identity.clone()),
+                IrInst::Binary {
+                    op,
+                    lhs: ValueId(0),
+                    rhs: ValueId(1),
+                    ty: IrType::Mat4,
+                },
+            ],
+            vec![2],
+        )
+    };
+
+    assert_eq!(compile_stats(&product(BinaryOp::Mul)).estimated_cost, 64);
+    assert_eq!(compile_stats(&product(BinaryOp::Add)).estimated_cost, 16);
+}
+
+#[test]
+fn test_live_values_track_longest_overlap() {
+    let constant = |v| IrInst::Constant {
+        value: Literal::Float(v),
+        ty: IrType::Float,
+    };
+    let add = |lhs, rhs| IrInst::Binary {
+        op: BinaryOp::Add,
+        lhs: ValueId(lhs),
+        rhs: ValueId(rhs),
+        ty: IrType::Float,
+    };
+
+    // Three constants alive together, then folded into one sum
+    let wide = program(
+        vec![
+            constant(1.0),
+            constant(2.0),
+            constant(3.0),
+            add(0, 1),
+            add(3, 2),
+        ],
+        vec![4],
+    );
+    assert_eq!(compile_stats(&wide).max_live_values, 4);
+
+    // Each value dies as soon as the next one is computed
+    let chain = program(
+        vec![constant(1.0), call("sin", vec![0], IrType::Float)],
+        vec![1],
+    );
+    assert_eq!(compile_stats(&chain).max_live_values, 2);
+}
+
+#[test]
+fn test_top_nodes_rank_graph_nodes_by_cost() {
+    let mut graph = Graph::new();
+    let vector_node = graph.add_node_with_config(
+        NodeKind::Math(MathOp::Multiply),
+        Vec2::ZERO,
+        vec![
+            InputDef::optional("a", ValueType::Vec4, Literal::Vec4([1.0, 2.0, 3.0, 4.0])),
+            InputDef::optional("b", ValueType::Vec4, Literal::Vec4([2.0; 4])),
+        ],
+        vec![("result".to_string(), ValueType::Vec4)],
+    );
+    let scalar_node = graph.add_node_with_config(
+        NodeKind::Math(MathOp::Add),
+        Vec2::ZERO,
+        vec![
+            InputDef::optional("a", ValueType::Float, Literal::Float(1.0)),
+            InputDef::optional("b", ValueType::Float, Literal::Float(2.0)),
+        ],
+        vec![("result".to_string(), ValueType::Float)],
+    );
+
+    let compiled = compile_graph(&graph, &[scalar_node, vector_node]).unwrap();
+    let top: Vec<NodeId> = compiled
+        .stats
+        .top_nodes
+        .iter()
+        .map(|entry| entry.node)
+        .collect();
+
+    assert_eq!(top, vec![vector_node, scalar_node]);
+    assert_eq!(compiled.stats.top_nodes[0].cost, 4);
+    assert_eq!(compiled.stats.estimated_cost, 5);
+}
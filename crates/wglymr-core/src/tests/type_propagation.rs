@@ -14,7 +14,7 @@ fn test_simple_value_chain() {
     );
 
     let pass_node = graph.add_node(
-        NodeKind::Generic("pass".to_string()),
+        NodeKind::Registered("utility.reroute".to_string()),
         Vec2::new(100.0, 0.0),
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
@@ -146,7 +146,7 @@ fn test_unreachable_nodes_ignored() {
     );
 
     let pass_node = graph.add_node(
-        NodeKind::Generic("pass".to_string()),
+        NodeKind::Registered("utility.reroute".to_string()),
         Vec2::new(200.0, 0.0),
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
@@ -177,7 +177,7 @@ fn test_multiple_roots() {
     );
 
     let pass1 = graph.add_node(
-        NodeKind::Generic("pass".to_string()),
+        NodeKind::Registered("utility.reroute".to_string()),
         Vec2::new(100.0, 0.0),
         vec![("in".to_string(), ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
@@ -191,7 +191,7 @@ fn test_multiple_roots() {
     );
 
     let pass2 = graph.add_node(
-        NodeKind::Generic("pass".to_string()),
+        NodeKind::Registered("utility.reroute".to_string()),
         Vec2::new(100.0, 100.0),
         vec![("in".to_string(), ValueType::Vec3)],
         vec![("out".to_string(), ValueType::Vec3)],
@@ -222,7 +222,7 @@ fn test_optional_input_with_default_succeeds() {
     let mut graph = Graph::new();
 
     let node = graph.add_node_with_config(
-        NodeKind::Generic("pass".to_string()),
+        NodeKind::Registered("utility.reroute".to_string()),
         Vec2::ZERO,
         vec![InputDef::optional(
             "in",
@@ -247,7 +247,7 @@ fn test_required_input_unconnected_errors() {
     let mut graph = Graph::new();
 
     let node = graph.add_node_with_config(
-        NodeKind::Generic("pass".to_string()),
+        NodeKind::Registered("utility.reroute".to_string()),
         Vec2::ZERO,
         vec![InputDef::required("in", ValueType::Float)],
         vec![("out".to_string(), ValueType::Float)],
@@ -270,7 +270,7 @@ fn test_default_literal_type_mismatch_errors() {
     let mut graph = Graph::new();
 
     let node = graph.add_node_with_config(
-        NodeKind::Generic("pass".to_string()),
+        NodeKind::Registered("utility.reroute".to_string()),
         Vec2::ZERO,
         vec![InputDef::optional(
             "in",
@@ -309,7 +309,7 @@ fn test_optional_connected_uses_upstream_type() {
     );
 
     let pass_node = graph.add_node_with_config(
-        NodeKind::Generic("pass".to_string()),
+        NodeKind::Registered("utility.reroute".to_string()),
         Vec2::new(100.0, 0.0),
         vec![InputDef::optional(
             "in",
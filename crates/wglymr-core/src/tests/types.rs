@@ -1,4 +1,4 @@
-use crate::{types::*, TypeError, ValueType};
+use crate::{types::*, BinaryOp, TypeError, ValueType};
 
 #[test]
 fn test_scalar_classification() {
@@ -97,3 +97,55 @@ fn test_unification_empty_fails() {
     assert!(result.is_err());
     assert!(matches!(result.unwrap_err(), TypeError::EmptyUnification));
 }
+
+#[test]
+fn test_matrix_classification() {
+    assert!(ValueType::Mat3.is_matrix());
+    assert!(!ValueType::Mat3.is_vector());
+    assert_eq!(ValueType::Mat4.matrix_size(), Some(4));
+    assert_eq!(ValueType::Vec4.matrix_size(), None);
+}
+
+#[test]
+fn test_matrix_products() {
+    assert_eq!(
+        binary_result_type(BinaryOp::Mul, ValueType::Mat3, ValueType::Vec3).unwrap(),
+        ValueType::Vec3
+    );
+    assert_eq!(
+        binary_result_type(BinaryOp::Mul, ValueType::Vec2, ValueType::Mat2).unwrap(),
+        ValueType::Vec2
+    );
+    assert_eq!(
+        binary_result_type(BinaryOp::Mul, ValueType::Mat4, ValueType::Mat4).unwrap(),
+        ValueType::Mat4
+    );
+    assert_eq!(
+        binary_result_type(BinaryOp::Mul, ValueType::Float, ValueType::Mat3).unwrap(),
+        ValueType::Mat3
+    );
+    assert_eq!(
+        binary_result_type(BinaryOp::Add, ValueType::Mat2, ValueType::Mat2).unwrap(),
+        ValueType::Mat2
+    );
+}
+
+#[test]
+fn test_invalid_matrix_operations_fail() {
+    for (op, lhs, rhs) in [
+        (BinaryOp::Mul, ValueType::Mat3, ValueType::Vec4),
+        (BinaryOp::Mul, ValueType::Mat3, ValueType::Mat4),
+        (BinaryOp::Mul, ValueType::Mat4, ValueType::Color),
+        (BinaryOp::Div, ValueType::Mat2, ValueType::Mat2),
+    ] {
+        assert!(matches!(
+            binary_result_type(op, lhs, rhs),
+            Err(TypeError::UnsupportedOperands { .. })
+        ));
+    }
+
+    assert!(matches!(
+        binary_result_type(BinaryOp::Add, ValueType::Mat3, ValueType::Vec3),
+        Err(TypeError::Mismatch { .. })
+    ));
+}
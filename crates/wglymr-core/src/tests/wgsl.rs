@@ -8,6 +8,11 @@ fn test_single_constant_emits_valid_wgsl() {
             value: Literal::Float(1.0),
             ty: IrType::Float,
         }],
+        outputs: vec![IrOutput {
+            name: "result".to_string(),
+            value: ValueId(0),
+        }],
+        ..Default::default()
     };
 
     let wgsl = emit_wgsl(&program);
@@ -36,6 +41,11 @@ fn test_binary_expression_emits_correct_wgsl() {
                 ty: IrType::Float,
             },
         ],
+        outputs: vec![IrOutput {
+            name: "result".to_string(),
+            value: ValueId(2),
+        }],
+        ..Default::default()
     };
 
     let wgsl = emit_wgsl(&program);
@@ -84,6 +94,7 @@ fn test_all_binary_ops() {
                 ty: IrType::Float,
             },
         ],
+        ..Default::default()
     };
 
     let wgsl = emit_wgsl(&program);
@@ -97,6 +108,11 @@ fn test_vector_types_emit_correctly() {
             value: Literal::Vec3([1.0, 2.0, 3.0]),
             ty: IrType::Vec3,
         }],
+        outputs: vec![IrOutput {
+            name: "result".to_string(),
+            value: ValueId(0),
+        }],
+        ..Default::default()
     };
 
     let wgsl = emit_wgsl(&program);
@@ -112,6 +128,11 @@ fn test_color_type_emits_as_vec4() {
             value: Literal::Vec4([1.0, 0.5, 0.0, 1.0]),
             ty: IrType::Color,
         }],
+        outputs: vec![IrOutput {
+            name: "result".to_string(),
+            value: ValueId(0),
+        }],
+        ..Default::default()
     };
 
     let wgsl = emit_wgsl(&program);
@@ -134,6 +155,11 @@ fn test_conversion_float_to_vec3() {
                 to_ty: IrType::Vec3,
             },
         ],
+        outputs: vec![IrOutput {
+            name: "result".to_string(),
+            value: ValueId(1),
+        }],
+        ..Default::default()
     };
 
     let wgsl = emit_wgsl(&program);
@@ -158,6 +184,11 @@ fn test_conversion_vec3_to_color() {
                 to_ty: IrType::Color,
             },
         ],
+        outputs: vec![IrOutput {
+            name: "result".to_string(),
+            value: ValueId(1),
+        }],
+        ..Default::default()
     };
 
     let wgsl = emit_wgsl(&program);
@@ -167,3 +198,187 @@ fn test_conversion_vec3_to_color() {
     assert!(wgsl.contains("let v1: vec4<f32> = vec4<f32>(v0.x, v0.y, v0.z, 1.0);"));
     assert!(wgsl.contains("return v1;"));
 }
+
+#[test]
+fn test_matrix_types_and_literals() {
+    assert_eq!(type_to_wgsl(IrType::Mat2), "mat2x2<f32>");
+    assert_eq!(type_to_wgsl(IrType::Mat3), "mat3x3<f32>");
+    assert_eq!(type_to_wgsl(IrType::Mat4), "mat4x4<f32>");
+
+    let program = IrProgram {
+        instructions: vec![IrInst::Constant {
+            value: Literal::identity(crate::ValueType::Mat2).unwrap(),
+            ty: IrType::Mat2,
+        }],
+        ..Default::default()
+    };
+
+    let wgsl = emit_wgsl(&program);
+    assert!(wgsl.contains("let v0: mat2x2<f32> = mat2x2<f32>(1, 0, 0, 1);"));
+}
+
+#[test]
+fn test_matrix_resize_emits_columns() {
+    let program = IrProgram {
+        instructions: vec![
+            IrInst::Constant {
+                value: Literal::identity(crate::ValueType::Mat4).unwrap(),
+                ty: IrType::Mat4,
+            },
+            IrInst::Convert {
+                from: ValueId(0),
+                from_ty: IrType::Mat4,
+                to_ty: IrType::Mat3,
+            },
+            IrInst::Convert {
+                from: ValueId(1),
+                from_ty: IrType::Mat3,
+                to_ty: IrType::Mat4,
+            },
+        ],
+        ..Default::default()
+    };
+
+    let wgsl = emit_wgsl(&program);
+    assert!(wgsl.contains("mat3x3<f32>(v0[0].xyz, v0[1].xyz, v0[2].xyz)"));
+    assert!(wgsl.contains(
+        "mat4x4<f32>(vec4<f32>(v1[0], 0.0), vec4<f32>(v1[1], 0.0), vec4<f32>(v1[2], 0.0), vec4<f32>(0.0, 0.0, 0.0, 1.0))"
+    ));
+    #[cfg(feature = "naga")]
+    assert!(crate::validate_wgsl(&wgsl).is_ok());
+}
+
+#[test]
+fn test_multiple_outputs_emit_output_struct() {
+    let program = IrProgram {
+        instructions: vec![
+            IrInst::Constant {
+                value: Literal::Vec4([1.0, 0.5, 0.0, 1.0]),
+                ty: IrType::Color,
+            },
+            IrInst::Constant {
+                value: Literal::Float(0.5),
+                ty: IrType::Float,
+            },
+            IrInst::Constant {
+                value: Literal::Float(2.0),
+                ty: IrType::Float,
+            },
+        ],
+        outputs: vec![
+            IrOutput {
+                name: "Base Color".to_string(),
+                value: ValueId(0),
+            },
+            IrOutput {
+                name: "Alpha".to_string(),
+                value: ValueId(1),
+            },
+            IrOutput {
+                name: "Alpha".to_string(),
+                value: ValueId(2),
+            },
+        ],
+        ..Default::default()
+    };
+
+    let wgsl = emit_wgsl(&program);
+
+    assert!(wgsl.contains("struct Output {"));
+    assert!(wgsl.contains("    base_color: vec4<f32>,"));
+    assert!(wgsl.contains("    alpha: f32,"));
+    assert!(wgsl.contains("    alpha_1: f32,"));
+    assert!(wgsl.contains("fn main() -> Output {"));
+    assert!(wgsl.contains("return Output(v0, v1, v2);"));
+    #[cfg(feature = "naga")]
+    assert!(crate::validate_wgsl(&wgsl).is_ok());
+}
+
+#[test]
+fn test_reserved_output_names_are_prefixed() {
+    let program = IrProgram {
+        instructions: vec![
+            IrInst::Constant {
+                value: Literal::Float(1.0),
+                ty: IrType::Float,
+            },
+            IrInst::Constant {
+                value: Literal::Float(2.0),
+                ty: IrType::Float,
+            },
+            IrInst::Constant {
+                value: Literal::Float(3.0),
+                ty: IrType::Float,
+            },
+        ],
+        outputs: vec![
+            IrOutput {
+                name: "Loop".to_string(),
+                value: ValueId(0),
+            },
+            IrOutput {
+                name: "override".to_string(),
+                value: ValueId(1),
+            },
+            IrOutput {
+                name: "Var".to_string(),
+                value: ValueId(2),
+            },
+        ],
+        ..Default::default()
+    };
+
+    let wgsl = emit_wgsl(&program);
+
+    assert!(wgsl.contains("    out_loop: f32,"));
+    assert!(wgsl.contains("    out_override: f32,"));
+    assert!(wgsl.contains("    out_var: f32,"));
+    #[cfg(feature = "naga")]
+    assert!(crate::validate_wgsl(&wgsl).is_ok());
+}
+
+#[test]
+fn test_output_need_not_be_last_instruction() {
+    let program = IrProgram {
+        instructions: vec![
+            IrInst::Constant {
+                value: Literal::Float(1.0),
+                ty: IrType::Float,
+            },
+            IrInst::Constant {
+                value: Literal::Vec2([1.0, 2.0]),
+                ty: IrType::Vec2,
+            },
+        ],
+        outputs: vec![IrOutput {
+            name: "value".to_string(),
+            value: ValueId(0),
+        }],
+        ..Default::default()
+    };
+
+    let wgsl = emit_wgsl(&program);
+
+    assert!(wgsl.contains("fn main() -> f32 {"));
+    assert!(wgsl.contains("return v0;"));
+}
+
+#[test]
+fn test_inputs_become_main_parameters() {
+    let program = IrProgram {
+        instructions: vec![IrInst::Input {
+            name: "uv".to_string(),
+            ty: IrType::Vec2,
+        }],
+        outputs: vec![IrOutput {
+            name: "uv".to_string(),
+            value: ValueId(0),
+        }],
+        ..Default::default()
+    };
+
+    let wgsl = emit_wgsl(&program);
+
+    assert!(wgsl.contains("fn main(uv: vec2<f32>) -> vec2<f32> {"));
+    assert!(wgsl.contains("let v0: vec2<f32> = uv;"));
+}
@@ -0,0 +1,223 @@
+use glam::Vec2;
+
+use crate::{
+    build_graph_view, emit_wgsl, import_wgsl_functions, lower_to_ir_with_registry,
+    propagate_types_with_registry, validate_wgsl, Graph, ImportError, IrInst, NodeRegistry,
+    ValueType,
+};
+
+const LIBRARY: &str = r#"
+struct Polar {
+    radius: f32,
+    angle: f32,
+}
+
+fn square(x: f32) -> f32 {
+    return x * x;
+}
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+fn to_polar(p: vec2<f32>) -> Polar {
+    return Polar(length(p), atan2(p.y, p.x));
+}
+
+fn scaled_square(x: f32, k: f32) -> f32 {
+    return square(x) * k;
+}
+
+fn store(p: ptr<function, f32>) {
+    *p = 1.0;
+}
+
+fn sum_ints(v: vec2<i32>) -> i32 {
+    return v.x + v.y;
+}
+"#;
+
+#[test]
+fn test_functions_become_definitions() {
+    let import = import_wgsl_functions("util", LIBRARY).unwrap();
+
+    let ids: Vec<&str> = import
+        .definitions
+        .iter()
+        .map(|definition| definition.id.as_str())
+        .collect();
+    assert_eq!(
+        ids,
+        vec![
+            "util.square",
+            "util.luminance",
+            "util.to_polar",
+            "util.scaled_square"
+        ]
+    );
+
+    let luminance = &import.definitions[1];
+    assert_eq!(luminance.category, "util");
+    assert_eq!(luminance.inputs[0].name, "color");
+    assert_eq!(luminance.inputs[0].value_type, ValueType::Vec3);
+    assert_eq!(luminance.outputs[0].value_type, ValueType::Float);
+}
+
+#[test]
+fn test_struct_return_creates_output_per_field() {
+    let import = import_wgsl_functions("util", LIBRARY).unwrap();
+    let polar = &import.definitions[2];
+
+    let outputs: Vec<&str> = polar
+        .outputs
+        .iter()
+        .map(|spec| spec.name.as_str())
+        .collect();
+    assert_eq!(outputs, vec!["radius", "angle"]);
+}
+
+#[test]
+fn test_unsupported_signatures_are_skipped() {
+    let import = import_wgsl_functions("util", LIBRARY).unwrap();
+
+    let skipped: Vec<&str> = import
+        .skipped
+        .iter()
+        .map(|function| function.name.as_str())
+        .collect();
+    assert_eq!(skipped, vec!["store", "sum_ints"]);
+}
+
+#[test]
+fn test_invalid_module_is_rejected() {
+    let result = import_wgsl_functions("util", "fn broken( -> f32 {");
+    assert!(matches!(result, Err(ImportError::Parse(_))));
+
+    let result = import_wgsl_functions("my-lib", LIBRARY);
+    assert!(matches!(result, Err(ImportError::InvalidLibraryName(_))));
+}
+
+#[test]
+fn test_imported_node_compiles_with_library_helper() {
+    let mut registry = NodeRegistry::with_builtins();
+    registry.import_wgsl("util", LIBRARY).unwrap();
+
+    let mut graph = Graph::new();
+    let value = registry
+        .instantiate(&mut graph, "value.vec2", Vec2::ZERO)
+        .unwrap();
+    let polar = registry
+        .instantiate(&mut graph, "util.to_polar", Vec2::ZERO)
+        .unwrap();
+    let value_out = graph.node(value).unwrap().outputs[0];
+    let polar_in = graph.node(polar).unwrap().inputs[0];
+    graph.connect(value_out, polar_in).unwrap();
+
+    let view = build_graph_view(&graph, &[polar]).unwrap();
+    let types = propagate_types_with_registry(&view, &registry).unwrap();
+    let program = lower_to_ir_with_registry(&view, &types, &registry).unwrap();
+
+    assert!(matches!(
+        &program.instructions[1],
+        IrInst::Call { function, .. } if function == "util_to_polar__radius"
+    ));
+    assert_eq!(program.helpers[0].name, "wgsl_library_util");
+
+    let wgsl = emit_wgsl(&program);
+    assert!(wgsl.contains("fn util_scaled_square("));
+    assert!(validate_wgsl(&wgsl).is_ok());
+}
+
+#[test]
+fn test_libraries_with_the_same_names_compile_together() {
+    let noise = r#"
+const SEED: f32 = 17.0;
+
+fn hash(x: f32) -> f32 {
+    return fract(sin(x * SEED) * 43758.5453);
+}
+
+@fragment
+fn main() -> @location(0) vec4<f32> {
+    return vec4<f32>(hash(1.0));
+}
+"#;
+    let dither = r#"
+const SEED: f32 = 3.0;
+
+fn hash(x: f32) -> f32 {
+    return fract(x * SEED);
+}
+"#;
+
+    let mut registry = NodeRegistry::with_builtins();
+    registry.import_wgsl("noise", noise).unwrap();
+    registry.import_wgsl("dither", dither).unwrap();
+
+    let mut graph = Graph::new();
+    let value = registry
+        .instantiate(&mut graph, "value.float", Vec2::ZERO)
+        .unwrap();
+    let noise_hash = registry
+        .instantiate(&mut graph, "noise.hash", Vec2::ZERO)
+        .unwrap();
+    let dither_hash = registry
+        .instantiate(&mut graph, "dither.hash", Vec2::ZERO)
+        .unwrap();
+    let value_out = graph.node(value).unwrap().outputs[0];
+    let noise_in = graph.node(noise_hash).unwrap().inputs[0];
+    let noise_out = graph.node(noise_hash).unwrap().outputs[0];
+    let dither_in = graph.node(dither_hash).unwrap().inputs[0];
+    graph.connect(value_out, noise_in).unwrap();
+    graph.connect(noise_out, dither_in).unwrap();
+
+    let view = build_graph_view(&graph, &[dither_hash]).unwrap();
+    let types = propagate_types_with_registry(&view, &registry).unwrap();
+    let program = lower_to_ir_with_registry(&view, &types, &registry).unwrap();
+
+    let wgsl = emit_wgsl(&program);
+    assert!(wgsl.contains("fn noise_hash("));
+    assert!(wgsl.contains("fn dither_hash("));
+    assert!(!wgsl.contains("noise_main"));
+    assert_eq!(wgsl.matches("fn main(").count(), 1);
+    assert!(validate_wgsl(&wgsl).is_ok());
+}
+
+#[test]
+fn test_struct_accessors_do_not_collide_with_functions() {
+    let library = r#"
+struct Parts {
+    a: f32,
+}
+
+fn split(x: f32) -> Parts {
+    return Parts(x);
+}
+
+fn split_a(x: f32) -> f32 {
+    return -x;
+}
+"#;
+    let mut registry = NodeRegistry::with_builtins();
+    registry.import_wgsl("util", library).unwrap();
+
+    let mut graph = Graph::new();
+    let split = registry
+        .instantiate(&mut graph, "util.split", Vec2::ZERO)
+        .unwrap();
+    let split_a = registry
+        .instantiate(&mut graph, "util.split_a", Vec2::ZERO)
+        .unwrap();
+    let split_out = graph.node(split).unwrap().outputs[0];
+    let split_a_in = graph.node(split_a).unwrap().inputs[0];
+    graph.connect(split_out, split_a_in).unwrap();
+
+    let view = build_graph_view(&graph, &[split_a]).unwrap();
+    let types = propagate_types_with_registry(&view, &registry).unwrap();
+    let program = lower_to_ir_with_registry(&view, &types, &registry).unwrap();
+
+    let wgsl = emit_wgsl(&program);
+    assert!(wgsl.contains("fn util_split__a("));
+    assert!(wgsl.contains("fn util_split_a("));
+    assert!(validate_wgsl(&wgsl).is_ok());
+}
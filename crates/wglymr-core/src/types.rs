@@ -15,6 +15,10 @@ pub enum ValueType {
     Bool,
     Int,
     Color,
+    /// Square column-major matrices
+    Mat2,
+    Mat3,
+    Mat4,
 }
 
 impl ValueType {
@@ -31,6 +35,21 @@ impl ValueType {
         )
     }
 
+    /// Check if type is a matrix value
+    pub fn is_matrix(&self) -> bool {
+        matches!(self, ValueType::Mat2 | ValueType::Mat3 | ValueType::Mat4)
+    }
+
+    /// Get matrix dimension, None for non-matrix types
+    pub fn matrix_size(&self) -> Option<u8> {
+        match self {
+            ValueType::Mat2 => Some(2),
+            ValueType::Mat3 => Some(3),
+            ValueType::Mat4 => Some(4),
+            _ => None,
+        }
+    }
+
     /// Get vector width, None for scalars
     pub fn vector_width(&self) -> Option<u8> {
         match self {
@@ -73,7 +92,51 @@ pub fn unify(types: &[ValueType]) -> Result<ValueType, TypeError> {
     Ok(first)
 }
 
-use crate::SocketId;
+/// Result type of `lhs op rhs`
+///
+/// Operands must match, except for multiplication involving matrices:
+/// - MatN * MatN => MatN
+/// - MatN * VecN => VecN (and VecN * MatN => VecN)
+/// - Float * MatN => MatN (and MatN * Float => MatN)
+///
+/// Matrices cannot be divided.
+pub fn binary_result_type(
+    op: BinaryOp,
+    lhs: ValueType,
+    rhs: ValueType,
+) -> Result<ValueType, TypeError> {
+    let unsupported = || TypeError::UnsupportedOperands { op, lhs, rhs };
+
+    if op == BinaryOp::Mul {
+        match (lhs.matrix_size(), rhs.matrix_size()) {
+            (Some(_), Some(_)) if lhs == rhs => return Ok(lhs),
+            (Some(_), None) if rhs == ValueType::Float => return Ok(lhs),
+            (None, Some(_)) if lhs == ValueType::Float => return Ok(rhs),
+            (Some(size), None) | (None, Some(size)) => {
+                let vector = if lhs.is_matrix() { rhs } else { lhs };
+                // Color is not a plain vector and must be converted first
+                return match vector {
+                    ValueType::Vec2 | ValueType::Vec3 | ValueType::Vec4
+                        if vector.vector_width() == Some(size) =>
+                    {
+                        Ok(vector)
+                    }
+                    _ => Err(unsupported()),
+                };
+            }
+            (Some(_), Some(_)) => return Err(unsupported()),
+            (None, None) => {}
+        }
+    }
+
+    let unified = unify(&[lhs, rhs])?;
+    if op == BinaryOp::Div && unified.is_matrix() {
+        return Err(unsupported());
+    }
+    Ok(unified)
+}
+
+use crate::{BinaryOp, SocketId};
 
 #[derive(Error, Debug)]
 pub enum TypeError {
@@ -98,4 +161,20 @@ pub enum TypeError {
 
     #[error("required input socket {socket:?} is not connected")]
     UnconnectedRequiredInput { socket: SocketId },
+
+    #[error("group nodes must be inlined before type propagation")]
+    GroupNotInlined,
+
+    #[error("expected {expected} input types, found {found}")]
+    ArityMismatch { expected: usize, found: usize },
+
+    #[error("unknown node definition {0:?}")]
+    UnknownNodeDefinition(String),
+
+    #[error("operator {op:?} is not defined for {lhs:?} and {rhs:?}")]
+    UnsupportedOperands {
+        op: BinaryOp,
+        lhs: ValueType,
+        rhs: ValueType,
+    },
 }
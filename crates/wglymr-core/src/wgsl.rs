@@ -13,15 +13,61 @@ pub(crate) fn type_to_wgsl(ty: IrType) -> &'static str {
         IrType::Color => "vec4<f32>",
         IrType::Bool => "bool",
         IrType::Int => "i32",
+        IrType::Mat2 => "mat2x2<f32>",
+        IrType::Mat3 => "mat3x3<f32>",
+        IrType::Mat4 => "mat4x4<f32>",
     }
 }
 
+/// Emit a WGSL module for a program
+///
+/// A single output is returned directly from `main`; several outputs are
+/// returned through an `Output` struct with one field per output.
 pub fn emit_wgsl(ir: &IrProgram) -> String {
     let mut output = String::new();
 
-    output.push_str("fn main() -> ");
-    let return_type = get_return_type(ir);
-    output.push_str(type_to_wgsl(return_type));
+    for helper in &ir.helpers {
+        output.push_str(&helper.source);
+        output.push('\n');
+    }
+
+    let fields = output_fields(ir);
+
+    if fields.len() > 1 {
+        output.push_str("struct Output {\n");
+        for (field, ty, _) in &fields {
+            output.push_str("    ");
+            output.push_str(field);
+            output.push_str(": ");
+            output.push_str(type_to_wgsl(*ty));
+            output.push_str(",\n");
+        }
+        output.push_str("}\n\n");
+    }
+
+    output.push_str("fn main(");
+    let mut inputs: Vec<(&str, IrType)> = Vec::new();
+    for inst in &ir.instructions {
+        if let IrInst::Input { name, ty } = inst {
+            if !inputs.iter().any(|(existing, _)| existing == name) {
+                inputs.push((name, *ty));
+            }
+        }
+    }
+    let params: Vec<String> = inputs
+        .iter()
+        .map(|(name, ty)| format!("{}: {}", name, type_to_wgsl(*ty)))
+        .collect();
+    output.push_str(&params.join(", "));
+    output.push(')');
+    match fields.as_slice() {
+        [] => {}
+        [(_, ty, _)] => {
+            output.push_str(" -> ");
+            output.push_str(type_to_wgsl(*ty));
+        }
+        _ => output.push_str(" -> Output"),
+    }
     output.push_str(" {\n");
 
     for (index, inst) in ir.instructions.iter().enumerate() {
@@ -29,25 +75,251 @@ pub fn emit_wgsl(ir: &IrProgram) -> String {
         output.push_str(&emit_instruction(inst, value_id));
     }
 
-    let last_value = ValueId((ir.instructions.len() - 1) as u32);
-    output.push_str("    return ");
-    output.push_str(&value_name(last_value));
-    output.push_str(";\n");
+    match fields.as_slice() {
+        [] => {}
+        [(_, _, value)] => {
+            output.push_str("    return ");
+            output.push_str(&value_name(*value));
+            output.push_str(";\n");
+        }
+        _ => {
+            let values: Vec<String> = fields
+                .iter()
+                .map(|(_, _, value)| value_name(*value))
+                .collect();
+            output.push_str("    return Output(");
+            output.push_str(&values.join(", "));
+            output.push_str(");\n");
+        }
+    }
 
     output.push_str("}\n");
 
     output
 }
 
-fn get_return_type(ir: &IrProgram) -> IrType {
-    match ir.instructions.last() {
-        Some(IrInst::Constant { ty, .. }) => *ty,
-        Some(IrInst::Binary { ty, .. }) => *ty,
-        Some(IrInst::Convert { to_ty, .. }) => *to_ty,
-        None => panic!("Empty IR program"),
+/// Struct field name, type and value for each program output
+fn output_fields(ir: &IrProgram) -> Vec<(String, IrType, ValueId)> {
+    let mut fields: Vec<(String, IrType, ValueId)> = Vec::new();
+
+    for output in &ir.outputs {
+        let ty = ir
+            .value_type(output.value)
+            .expect("output must reference a value in the program");
+
+        let base = field_name(&output.name);
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while fields.iter().any(|(existing, _, _)| *existing == name) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+
+        fields.push((name, ty, output.value));
+    }
+
+    fields
+}
+
+/// Turn an output name like "Base Color" into a WGSL identifier
+fn field_name(name: &str) -> String {
+    let mut field = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            field.push(c.to_ascii_lowercase());
+        } else if !field.ends_with('_') {
+            field.push('_');
+        }
+    }
+    let field = field.trim_matches('_').to_string();
+
+    if field.is_empty() {
+        "output".to_string()
+    } else if field.starts_with(|c: char| c.is_ascii_digit())
+        || WGSL_RESERVED_WORDS.contains(&field.as_str())
+    {
+        format!("out_{}", field)
+    } else {
+        field
     }
 }
 
+/// WGSL keywords and reserved words, which cannot be used as identifiers
+const WGSL_RESERVED_WORDS: &[&str] = &[
+    // Keywords
+    "alias",
+    "break",
+    "case",
+    "const",
+    "const_assert",
+    "continue",
+    "continuing",
+    "default",
+    "diagnostic",
+    "discard",
+    "else",
+    "enable",
+    "false",
+    "fn",
+    "for",
+    "if",
+    "let",
+    "loop",
+    "override",
+    "requires",
+    "return",
+    "struct",
+    "switch",
+    "true",
+    "var",
+    "while",
+    // Reserved words
+    "abstract",
+    "active",
+    "alignas",
+    "alignof",
+    "as",
+    "asm",
+    "asm_fragment",
+    "async",
+    "attribute",
+    "auto",
+    "await",
+    "become",
+    "cast",
+    "catch",
+    "class",
+    "co_await",
+    "co_return",
+    "co_yield",
+    "coherent",
+    "column_major",
+    "common",
+    "compile",
+    "compile_fragment",
+    "concept",
+    "const_cast",
+    "consteval",
+    "constexpr",
+    "constinit",
+    "crate",
+    "debugger",
+    "decltype",
+    "delete",
+    "demote",
+    "demote_to_helper",
+    "do",
+    "dynamic_cast",
+    "enum",
+    "explicit",
+    "export",
+    "extends",
+    "extern",
+    "external",
+    "fallthrough",
+    "filter",
+    "final",
+    "finally",
+    "friend",
+    "from",
+    "fxgroup",
+    "get",
+    "goto",
+    "groupshared",
+    "highp",
+    "impl",
+    "implements",
+    "import",
+    "inline",
+    "instanceof",
+    "interface",
+    "layout",
+    "lowp",
+    "macro",
+    "macro_rules",
+    "match",
+    "mediump",
+    "meta",
+    "mod",
+    "module",
+    "move",
+    "mut",
+    "mutable",
+    "namespace",
+    "new",
+    "nil",
+    "noexcept",
+    "noinline",
+    "nointerpolation",
+    "non_coherent",
+    "noncoherent",
+    "noperspective",
+    "null",
+    "nullptr",
+    "of",
+    "operator",
+    "package",
+    "packoffset",
+    "partition",
+    "pass",
+    "patch",
+    "pixelfragment",
+    "precise",
+    "precision",
+    "premerge",
+    "priv",
+    "protected",
+    "pub",
+    "public",
+    "readonly",
+    "ref",
+    "regardless",
+    "register",
+    "reinterpret_cast",
+    "require",
+    "resource",
+    "restrict",
+    "self",
+    "set",
+    "shared",
+    "sizeof",
+    "smooth",
+    "snorm",
+    "static",
+    "static_assert",
+    "static_cast",
+    "std",
+    "subroutine",
+    "super",
+    "target",
+    "template",
+    "this",
+    "thread_local",
+    "throw",
+    "trait",
+    "try",
+    "type",
+    "typedef",
+    "typeid",
+    "typename",
+    "typeof",
+    "union",
+    "unless",
+    "unorm",
+    "unsafe",
+    "unsized",
+    "use",
+    "using",
+    "varying",
+    "virtual",
+    "volatile",
+    "wgsl",
+    "where",
+    "with",
+    "writeonly",
+    "yield",
+];
+
 fn emit_instruction(inst: &IrInst, value_id: ValueId) -> String {
     match inst {
         IrInst::Constant { value, ty } => {
@@ -79,6 +351,29 @@ fn emit_instruction(inst: &IrInst, value_id: ValueId) -> String {
             from_ty,
             to_ty,
         } => emit_conversion(value_id, *from, *from_ty, *to_ty),
+        IrInst::Input { name, ty } => {
+            let mut line = String::from("    let ");
+            line.push_str(&value_name(value_id));
+            line.push_str(": ");
+            line.push_str(type_to_wgsl(*ty));
+            line.push_str(" = ");
+            line.push_str(name);
+            line.push_str(";\n");
+            line
+        }
+        IrInst::Call { function, args, ty } => {
+            let mut line = String::from("    let ");
+            line.push_str(&value_name(value_id));
+            line.push_str(": ");
+            line.push_str(type_to_wgsl(*ty));
+            line.push_str(" = ");
+            line.push_str(function);
+            line.push('(');
+            let arg_names: Vec<String> = args.iter().map(|a| value_name(*a)).collect();
+            line.push_str(&arg_names.join(", "));
+            line.push_str(");\n");
+            line
+        }
     }
 }
 
@@ -119,6 +414,9 @@ fn emit_conversion(value_id: ValueId, from: ValueId, from_ty: IrType, to_ty: IrT
             line.push_str(&value_name(from));
             line.push_str(".z, 1.0)");
         }
+        (from_ty, to_ty) if from_ty.matrix_size().is_some() && to_ty.matrix_size().is_some() => {
+            line.push_str(&resize_matrix(&value_name(from), from_ty, to_ty));
+        }
         _ => {
             line.push_str(&value_name(from));
         }
@@ -128,6 +426,32 @@ fn emit_conversion(value_id: ValueId, from: ValueId, from_ty: IrType, to_ty: IrT
     line
 }
 
+/// Truncate a matrix, or embed it in the upper-left corner of the identity
+fn resize_matrix(value: &str, from_ty: IrType, to_ty: IrType) -> String {
+    let from = from_ty.matrix_size().unwrap_or(0) as usize;
+    let to = to_ty.matrix_size().unwrap_or(0) as usize;
+    let vector = ["", "", "vec2<f32>", "vec3<f32>", "vec4<f32>"][to];
+    let swizzle = ["", "", "xy", "xyz", "xyzw"][to];
+
+    let columns: Vec<String> = (0..to)
+        .map(|c| {
+            if c >= from {
+                let unit: Vec<&str> = (0..to)
+                    .map(|r| if r == c { "1.0" } else { "0.0" })
+                    .collect();
+                format!("{}({})", vector, unit.join(", "))
+            } else if to < from {
+                format!("{}[{}].{}", value, c, swizzle)
+            } else {
+                let padding = vec!["0.0"; to - from].join(", ");
+                format!("{}({}[{}], {})", vector, value, c, padding)
+            }
+        })
+        .collect();
+
+    format!("{}({})", type_to_wgsl(to_ty), columns.join(", "))
+}
+
 fn literal_to_wgsl(lit: &Literal, ty: IrType) -> String {
     match lit {
         Literal::Float(f) => format!("{}", f),
@@ -139,9 +463,17 @@ fn literal_to_wgsl(lit: &Literal, ty: IrType) -> String {
         },
         Literal::Bool(b) => format!("{}", b),
         Literal::Int(i) => format!("{}", i),
+        Literal::Mat2(m) => matrix_literal_to_wgsl(IrType::Mat2, m),
+        Literal::Mat3(m) => matrix_literal_to_wgsl(IrType::Mat3, m),
+        Literal::Mat4(m) => matrix_literal_to_wgsl(IrType::Mat4, m),
     }
 }
 
+fn matrix_literal_to_wgsl(ty: IrType, values: &[f32]) -> String {
+    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    format!("{}({})", type_to_wgsl(ty), values.join(", "))
+}
+
 fn binop_to_wgsl(op: BinaryOp) -> &'static str {
     match op {
         BinaryOp::Add => "+",
@@ -0,0 +1,299 @@
+//! Import WGSL utility functions as node definitions.
+//!
+//! A WGSL module is parsed with naga and every free function whose parameter
+//! and return types map to a `ValueType` becomes a `NodeDefinition`: one
+//! input per parameter and one output per return value, or per field when the
+//! function returns a struct. The module source is carried as a helper so
+//! imported functions can still call the rest of their library.
+//!
+//! Every module-scope name of the library is prefixed with the library name
+//! and entry points are dropped, so several libraries can be used by one
+//! shader without their declarations colliding.
+
+use naga::{Module, ScalarKind, TypeInner, UniqueArena, VectorSize};
+use thiserror::Error;
+
+use crate::code::is_identifier;
+use crate::registry::zero_literal;
+use crate::wgsl::type_to_wgsl;
+use crate::{
+    IrHelper, IrType, LoweringRule, NodeDefinition, NodeRegistry, RegistryError, SocketSpec,
+    TypeRule, ValueType,
+};
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("library name {0:?} is not a valid WGSL identifier")]
+    InvalidLibraryName(String),
+
+    #[error("failed to parse WGSL: {0}")]
+    Parse(String),
+
+    #[error("invalid WGSL module: {0}")]
+    Validation(String),
+
+    #[error("failed to write namespaced WGSL: {0}")]
+    Namespace(String),
+
+    #[error(transparent)]
+    Registry(#[from] RegistryError),
+}
+
+/// Function left out of an import, with the reason
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedFunction {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Result of importing a WGSL module
+#[derive(Debug, Clone, Default)]
+pub struct WgslImport {
+    pub definitions: Vec<NodeDefinition>,
+    pub skipped: Vec<SkippedFunction>,
+}
+
+/// Build node definitions for the free functions of a WGSL module
+///
+/// Definitions get the id `"<library>.<function>"` and the category
+/// `library`. Functions with unsupported signatures are reported in
+/// `WgslImport::skipped` instead of failing the import.
+pub fn import_wgsl_functions(library: &str, source: &str) -> Result<WgslImport, ImportError> {
+    if !is_identifier(library) {
+        return Err(ImportError::InvalidLibraryName(library.to_string()));
+    }
+
+    let module = naga::front::wgsl::parse_str(source)
+        .map_err(|err| ImportError::Parse(err.message().to_string()))?;
+
+    validate(&module)?;
+    let (namespaced, emitted_names) = namespace_module(library, module.clone())?;
+
+    let library_helper = IrHelper {
+        name: format!("wgsl_library_{}", library),
+        source: namespaced,
+    };
+
+    let mut import = WgslImport::default();
+
+    for ((_, function), emitted) in module.functions.iter().zip(&emitted_names) {
+        let Some(name) = function.name.clone() else {
+            continue;
+        };
+
+        match function_definition(&module, library, &name, emitted, function, &library_helper) {
+            Ok(definition) => import.definitions.push(definition),
+            Err(reason) => import.skipped.push(SkippedFunction { name, reason }),
+        }
+    }
+
+    Ok(import)
+}
+
+impl NodeRegistry {
+    /// Import a WGSL module and register every supported function
+    pub fn import_wgsl(&mut self, library: &str, source: &str) -> Result<WgslImport, ImportError> {
+        let import = import_wgsl_functions(library, source)?;
+        for definition in &import.definitions {
+            self.register(definition.clone())?;
+        }
+        Ok(import)
+    }
+}
+
+fn validate(module: &Module) -> Result<naga::valid::ModuleInfo, ImportError> {
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(module)
+    .map_err(|err| ImportError::Validation(err.as_inner().to_string()))
+}
+
+/// WGSL for the module with `"<library>_"` prepended to every module-scope
+/// name and without entry points
+///
+/// Also returns the name each function was written under, in function
+/// order. The writer may still adjust a name, e.g. one ending in a digit.
+fn namespace_module(
+    library: &str,
+    mut module: Module,
+) -> Result<(String, Vec<String>), ImportError> {
+    let prefix = |name: &mut Option<String>| {
+        if let Some(name) = name {
+            *name = format!("{}_{}", library, name);
+        }
+    };
+
+    module.entry_points.clear();
+    for (_, function) in module.functions.iter_mut() {
+        prefix(&mut function.name);
+    }
+    for (_, constant) in module.constants.iter_mut() {
+        prefix(&mut constant.name);
+    }
+    for (_, value) in module.overrides.iter_mut() {
+        prefix(&mut value.name);
+    }
+    for (_, global) in module.global_variables.iter_mut() {
+        prefix(&mut global.name);
+    }
+
+    // Types are deduplicated, so they are renamed by rebuilding the arena
+    // in the same order, which keeps every handle valid
+    let mut types = UniqueArena::new();
+    for (handle, ty) in module.types.iter() {
+        let mut ty = ty.clone();
+        prefix(&mut ty.name);
+        types.insert(ty, module.types.get_span(handle));
+    }
+    module.types = types;
+
+    let info = validate(&module)?;
+    let source =
+        naga::back::wgsl::write_string(&module, &info, naga::back::wgsl::WriterFlags::empty())
+            .map_err(|err| ImportError::Namespace(err.to_string()))?;
+
+    // Functions are written in arena order, which parsing keeps
+    let written = naga::front::wgsl::parse_str(&source)
+        .map_err(|err| ImportError::Namespace(err.message().to_string()))?;
+    let names: Vec<String> = written
+        .functions
+        .iter()
+        .map(|(_, function)| function.name.clone().unwrap_or_default())
+        .collect();
+    if names.len() != module.functions.len() {
+        return Err(ImportError::Namespace(
+            "written module has a different number of functions".to_string(),
+        ));
+    }
+
+    Ok((source, names))
+}
+
+fn function_definition(
+    module: &Module,
+    library: &str,
+    name: &str,
+    emitted: &str,
+    function: &naga::Function,
+    library_helper: &IrHelper,
+) -> Result<NodeDefinition, String> {
+    let mut inputs = Vec::new();
+    for (index, argument) in function.arguments.iter().enumerate() {
+        let param = argument
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("arg{}", index));
+        let value_type = map_type(module, argument.ty)
+            .ok_or_else(|| format!("parameter {:?} has an unsupported type", param))?;
+        inputs.push(SocketSpec::optional(
+            param,
+            value_type,
+            zero_literal(value_type),
+        ));
+    }
+
+    let result = function
+        .result
+        .as_ref()
+        .ok_or_else(|| "function does not return a value".to_string())?;
+
+    let mut outputs = Vec::new();
+    let mut functions = Vec::new();
+    let mut helpers = vec![library_helper.clone()];
+
+    if let TypeInner::Struct { members, .. } = &module.types[result.ty].inner {
+        let params: Vec<String> = inputs
+            .iter()
+            .map(|spec| format!("{}: {}", spec.name, wgsl_type(spec.value_type)))
+            .collect();
+        let args: Vec<&str> = inputs.iter().map(|spec| spec.name.as_str()).collect();
+
+        for member in members {
+            let field = member
+                .name
+                .clone()
+                .ok_or_else(|| "struct field has no name".to_string())?;
+            let value_type = map_type(module, member.ty)
+                .ok_or_else(|| format!("field {:?} has an unsupported type", field))?;
+
+            // The writer never emits a double underscore, so accessors
+            // cannot collide with a namespaced library name
+            let accessor = format!("{}__{}", emitted, field);
+            helpers.push(IrHelper {
+                name: accessor.clone(),
+                source: format!(
+                    "fn {}({}) -> {} {{\n    return {}({}).{};\n}}\n",
+                    accessor,
+                    params.join(", "),
+                    wgsl_type(value_type),
+                    emitted,
+                    args.join(", "),
+                    field
+                ),
+            });
+            outputs.push(SocketSpec::output(field, value_type));
+            functions.push(accessor);
+        }
+    } else {
+        let value_type =
+            map_type(module, result.ty).ok_or_else(|| "return type is unsupported".to_string())?;
+        outputs.push(SocketSpec::output("Result", value_type));
+        functions.push(emitted.to_string());
+    }
+
+    Ok(NodeDefinition {
+        id: format!("{}.{}", library, name),
+        category: library.to_string(),
+        display_name: name.to_string(),
+        inputs,
+        outputs,
+        properties: vec![],
+        variants: vec![],
+        type_rule: TypeRule::Declared,
+        lowering: LoweringRule::Call { functions, helpers },
+    })
+}
+
+/// `ValueType` for a naga type, if the type has one
+fn map_type(module: &Module, ty: naga::Handle<naga::Type>) -> Option<ValueType> {
+    match module.types[ty].inner {
+        TypeInner::Scalar(scalar) => match (scalar.kind, scalar.width) {
+            (ScalarKind::Float, 4) => Some(ValueType::Float),
+            (ScalarKind::Sint, 4) => Some(ValueType::Int),
+            (ScalarKind::Bool, _) => Some(ValueType::Bool),
+            _ => None,
+        },
+        TypeInner::Vector { size, scalar } if scalar.kind == ScalarKind::Float => match size {
+            VectorSize::Bi => Some(ValueType::Vec2),
+            VectorSize::Tri => Some(ValueType::Vec3),
+            VectorSize::Quad => Some(ValueType::Vec4),
+        },
+        TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } if columns == rows && scalar.kind == ScalarKind::Float => match columns {
+            VectorSize::Bi => Some(ValueType::Mat2),
+            VectorSize::Tri => Some(ValueType::Mat3),
+            VectorSize::Quad => Some(ValueType::Mat4),
+        },
+        _ => None,
+    }
+}
+
+fn wgsl_type(value_type: ValueType) -> &'static str {
+    type_to_wgsl(match value_type {
+        ValueType::Float => IrType::Float,
+        ValueType::Vec2 => IrType::Vec2,
+        ValueType::Vec3 => IrType::Vec3,
+        ValueType::Vec4 => IrType::Vec4,
+        ValueType::Bool => IrType::Bool,
+        ValueType::Int => IrType::Int,
+        ValueType::Color => IrType::Color,
+        ValueType::Mat2 => IrType::Mat2,
+        ValueType::Mat3 => IrType::Mat3,
+        ValueType::Mat4 => IrType::Mat4,
+    })
+}
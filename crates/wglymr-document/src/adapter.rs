@@ -1,23 +1,89 @@
 // Adapter layer to wglymr-graph
 // Translates editor operations to graph API calls
 
-use crate::commands::EditorCommand;
-use crate::descriptors::{EdgeDescriptor, NodeDescriptor, SocketDescriptor};
+use std::collections::HashMap;
+
+use wglymr_core::{CompileStats, NodeDefinition, NodeProperties, NodeRegistry};
+
+use crate::commands::{
+    CommandOutcome, EditorCommand, LiteralValue, NodeId, NodePosition, PropertyValue, SocketId,
+};
+use crate::descriptors::{
+    EdgeDescriptor, NodeDescriptor, PropertyDescriptor, SocketDescriptor, SocketDirection,
+    SocketMetadata,
+};
+use crate::error::DocumentError;
+use crate::events::{DocumentEvent, DocumentSubscriber, EventBus, SubscriptionId};
 use crate::snapshot::GraphSnapshot;
 
 // Boundary between editor UI and underlying graph
 // Owns document snapshot and applies atomic mutations
 pub trait DocumentAdapter {
     // Apply a single atomic command to the document
-    fn apply_command(&mut self, command: EditorCommand);
+    // A batch applies all of its commands or none of them
+    fn apply_command(&mut self, command: EditorCommand) -> Result<CommandOutcome, DocumentError>;
 
     // Monotonically increasing revision counter
-    // Increments after each successful command
+    // Increments after each successful command, undo and redo
     fn document_revision(&self) -> u64;
 
+    // Revert the most recent command
+    // Returns false when there is nothing to undo
+    fn undo(&mut self) -> bool {
+        false
+    }
+
+    // Reapply the most recently undone command
+    // Returns false when there is nothing to redo
+    fn redo(&mut self) -> bool {
+        false
+    }
+
+    fn can_undo(&self) -> bool {
+        false
+    }
+
+    fn can_redo(&self) -> bool {
+        false
+    }
+
+    // Label of the command that undo would revert
+    fn undo_label(&self) -> Option<&str> {
+        None
+    }
+
+    // Label of the command that redo would reapply
+    fn redo_label(&self) -> Option<&str> {
+        None
+    }
+
+    // Mark the current state as saved
+    fn mark_saved(&mut self) {}
+
+    // Whether the document differs from the state marked as saved
+    fn is_dirty(&self) -> bool {
+        false
+    }
+
     // Get immutable snapshot of current document state
     fn snapshot(&self) -> GraphSnapshot;
 
+    // Register a subscriber for the events of every later change
+    fn subscribe(&mut self, subscriber: DocumentSubscriber) -> SubscriptionId;
+
+    // Returns false when the subscription was already removed
+    fn unsubscribe(&mut self, subscription: SubscriptionId) -> bool;
+
+    // Compile the document and attach the resulting diagnostics to its
+    // descriptors, for documents that do not do so after every change
+    fn refresh_diagnostics(&mut self) {}
+
+    // Cost report of the most recent compilation
+    // None when the document has not been compiled
+    fn compile_stats(&self) -> Option<CompileStats> {
+        None
+    }
+
     // Legacy slice-based API (kept for backward compatibility during transition)
     // Prefer using snapshot() for new code
     fn nodes(&self) -> &[NodeDescriptor];
@@ -27,11 +93,19 @@ pub trait DocumentAdapter {
 
 // Basic concrete adapter implementation
 // Satisfies the interface without graph integration
+// Node creation builds sockets from the node registry
 pub struct BasicDocumentAdapter {
     revision: u64,
     nodes: Vec<NodeDescriptor>,
     sockets: Vec<SocketDescriptor>,
     edges: Vec<EdgeDescriptor>,
+    registry: NodeRegistry,
+    // Every input of each node, including inputs hidden by its properties
+    node_inputs: HashMap<NodeId, Vec<SocketId>>,
+    node_properties: HashMap<NodeId, NodeProperties>,
+    subscribers: EventBus,
+    next_node_id: u64,
+    next_socket_id: u64,
 }
 
 impl Default for BasicDocumentAdapter {
@@ -42,18 +116,190 @@ impl Default for BasicDocumentAdapter {
 
 impl BasicDocumentAdapter {
     pub fn new() -> Self {
+        Self::with_registry(NodeRegistry::with_builtins())
+    }
+
+    pub fn with_registry(registry: NodeRegistry) -> Self {
         Self {
             revision: 0,
             nodes: Vec::new(),
             sockets: Vec::new(),
             edges: Vec::new(),
+            registry,
+            node_inputs: HashMap::new(),
+            node_properties: HashMap::new(),
+            subscribers: EventBus::new(),
+            next_node_id: 1,
+            next_socket_id: 1,
         }
     }
+
+    pub fn registry(&self) -> &NodeRegistry {
+        &self.registry
+    }
+
+    // Create node and socket descriptors from the registry definition
+    fn create_node(
+        &mut self,
+        node_kind: &str,
+        position: NodePosition,
+        node_id: Option<NodeId>,
+    ) -> Result<NodeId, DocumentError> {
+        let definition = self
+            .registry
+            .get(node_kind)
+            .ok_or_else(|| DocumentError::UnknownNodeKind(node_kind.to_string()))?;
+        // The largest id is never assigned, so the counter cannot overflow
+        if let Some(node_id) = node_id.filter(|id| id.0 == u64::MAX) {
+            return Err(DocumentError::NodeIdReserved { node_id });
+        }
+        if let Some(node_id) = node_id.filter(|id| self.node_inputs.contains_key(id)) {
+            return Err(DocumentError::NodeIdInUse { node_id });
+        }
+
+        let node_id = node_id.unwrap_or(NodeId(self.next_node_id));
+        self.next_node_id = self.next_node_id.max(node_id.0.saturating_add(1));
+
+        let mut socket_ids = |specs: &[wglymr_core::SocketSpec], direction: SocketDirection| {
+            specs
+                .iter()
+                .map(|spec| {
+                    let socket_id = SocketId(self.next_socket_id);
+                    self.next_socket_id += 1;
+                    self.sockets.push(SocketDescriptor {
+                        socket_id,
+                        node_id,
+                        name: spec.name.clone(),
+                        direction,
+                        type_name: Some(format!("{:?}", spec.value_type)),
+                        default_value: match direction {
+                            SocketDirection::Input => spec.default.as_ref().map(LiteralValue::from),
+                            SocketDirection::Output => None,
+                        },
+                        metadata: match direction {
+                            SocketDirection::Input => SocketMetadata::from(&spec.metadata),
+                            SocketDirection::Output => SocketMetadata::default(),
+                        },
+                        connected_edges: Vec::new(),
+                        diagnostics: Vec::new(),
+                    });
+                    socket_id
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let inputs = socket_ids(&definition.inputs, SocketDirection::Input);
+        let outputs = socket_ids(&definition.outputs, SocketDirection::Output);
+        let properties = NodeProperties::new();
+
+        self.nodes.push(NodeDescriptor {
+            node_id,
+            node_kind: definition.id.clone(),
+            position,
+            inputs: visible_inputs(definition, &inputs, &properties),
+            outputs,
+            properties: property_descriptors(definition, &properties),
+            diagnostics: Vec::new(),
+        });
+        self.node_inputs.insert(node_id, inputs);
+        self.node_properties.insert(node_id, properties);
+
+        Ok(node_id)
+    }
+
+    // Set a property checked against the node's definition
+    // The node's visible inputs follow the new property values
+    fn set_node_property(
+        &mut self,
+        node_id: NodeId,
+        name: &str,
+        value: &PropertyValue,
+    ) -> Result<(), DocumentError> {
+        let node = self
+            .nodes
+            .iter_mut()
+            .find(|n| n.node_id == node_id)
+            .ok_or(DocumentError::NodeNotFound { node_id })?;
+        let definition = self
+            .registry
+            .get(&node.node_kind)
+            .ok_or(DocumentError::UnknownDefinition { node_id })?;
+        let value = wglymr_core::PropertyValue::try_from(value)
+            .map_err(|_| DocumentError::IntegerOutOfRange)?;
+        definition
+            .check_property(name, &value)
+            .map_err(|source| DocumentError::Property { node_id, source })?;
+
+        let properties = self.node_properties.entry(node_id).or_default();
+        properties.insert(name.to_string(), value);
+
+        let inputs = &self.node_inputs[&node_id];
+        node.inputs = visible_inputs(definition, inputs, properties);
+        node.properties = property_descriptors(definition, properties);
+        Ok(())
+    }
+}
+
+pub(crate) fn visible_inputs(
+    definition: &NodeDefinition,
+    inputs: &[SocketId],
+    properties: &NodeProperties,
+) -> Vec<SocketId> {
+    definition
+        .active_inputs(properties)
+        .into_iter()
+        .filter_map(|index| inputs.get(index).copied())
+        .collect()
+}
+
+pub(crate) fn property_descriptors(
+    definition: &NodeDefinition,
+    properties: &NodeProperties,
+) -> Vec<PropertyDescriptor> {
+    definition
+        .properties
+        .iter()
+        .map(|spec| PropertyDescriptor {
+            name: spec.name.clone(),
+            value: PropertyValue::from(properties.get(&spec.name).unwrap_or(&spec.default)),
+            options: spec.options().to_vec(),
+        })
+        .collect()
 }
 
 impl DocumentAdapter for BasicDocumentAdapter {
-    fn apply_command(&mut self, _command: EditorCommand) {
+    fn apply_command(&mut self, command: EditorCommand) -> Result<CommandOutcome, DocumentError> {
+        let mut outcome = CommandOutcome::default();
+        let mut events = Vec::new();
+        match command {
+            EditorCommand::CreateNode {
+                node_kind,
+                position,
+                node_id,
+            } => {
+                let node_id = self.create_node(&node_kind, position, node_id)?;
+                outcome.created_nodes.push(node_id);
+                events.push(DocumentEvent::NodeAdded { node_id });
+            }
+            EditorCommand::SetNodeProperty {
+                node_id,
+                name,
+                value,
+            } => {
+                self.set_node_property(node_id, &name, &value)?;
+                events.push(DocumentEvent::PropertyChanged {
+                    node_id,
+                    name,
+                    value,
+                });
+            }
+            _ => {}
+        }
+
         self.revision += 1;
+        self.subscribers.publish(self.revision, &events);
+        outcome.revision = self.revision;
+        Ok(outcome)
     }
 
     fn document_revision(&self) -> u64 {
@@ -64,6 +310,14 @@ impl DocumentAdapter for BasicDocumentAdapter {
         GraphSnapshot::from_slices(self.revision, &self.nodes, &self.sockets, &self.edges)
     }
 
+    fn subscribe(&mut self, subscriber: DocumentSubscriber) -> SubscriptionId {
+        self.subscribers.subscribe(subscriber)
+    }
+
+    fn unsubscribe(&mut self, subscription: SubscriptionId) -> bool {
+        self.subscribers.unsubscribe(subscription)
+    }
+
     fn nodes(&self) -> &[NodeDescriptor] {
         &self.nodes
     }
@@ -0,0 +1,149 @@
+// Copy, cut and paste of node selections
+// A clipboard payload refers to copied nodes by index, so pasting it
+// creates nodes, sockets and edges under fresh ids
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::adapter::DocumentAdapter;
+use crate::commands::{EditorCommand, LiteralValue, NodeId, NodePosition, PropertyValue, SocketId};
+use crate::descriptors::SocketDirection;
+
+// Selection copied out of a document
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ClipboardPayload {
+    pub nodes: Vec<ClipboardNode>,
+    // Edges between copied nodes
+    pub edges: Vec<ClipboardEdge>,
+    // Edges into copied nodes from nodes outside the selection
+    pub upstream_edges: Vec<UpstreamEdge>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipboardNode {
+    pub node_kind: String,
+    // Position relative to the top-left corner of the selection
+    pub offset: NodePosition,
+    // Input defaults keyed by input index
+    pub defaults: Vec<(usize, LiteralValue)>,
+    pub properties: Vec<(String, PropertyValue)>,
+}
+
+// Edge addressed by node index in the payload and socket index on the node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipboardEdge {
+    pub from_node: usize,
+    pub from_output: usize,
+    pub to_node: usize,
+    pub to_input: usize,
+}
+
+// Edge from a socket left in the source document into a copied node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpstreamEdge {
+    pub from: SocketId,
+    pub to_node: usize,
+    pub to_input: usize,
+}
+
+impl ClipboardPayload {
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+// Copy the selected nodes with their defaults, properties and edges
+// Ids that are not in the document are ignored
+pub fn copy_selection(document: &dyn DocumentAdapter, selection: &[NodeId]) -> ClipboardPayload {
+    let nodes: Vec<_> = document
+        .nodes()
+        .iter()
+        .filter(|node| selection.contains(&node.node_id))
+        .collect();
+    let Some(origin) = nodes
+        .iter()
+        .map(|node| node.position)
+        .reduce(|a, b| NodePosition {
+            x: a.x.min(b.x),
+            y: a.y.min(b.y),
+        })
+    else {
+        return ClipboardPayload::default();
+    };
+
+    // Socket id to (node index, socket index among the node's inputs or outputs)
+    let mut inputs = HashMap::new();
+    let mut outputs = HashMap::new();
+    let mut payload = ClipboardPayload::default();
+    for (index, node) in nodes.iter().enumerate() {
+        let sockets = document
+            .sockets()
+            .iter()
+            .filter(|s| s.node_id == node.node_id);
+        let (node_inputs, node_outputs): (Vec<_>, Vec<_>) =
+            sockets.partition(|s| s.direction == SocketDirection::Input);
+
+        let mut defaults = Vec::new();
+        for (input, socket) in node_inputs.iter().enumerate() {
+            inputs.insert(socket.socket_id, (index, input));
+            if let Some(value) = &socket.default_value {
+                defaults.push((input, value.clone()));
+            }
+        }
+        for (output, socket) in node_outputs.iter().enumerate() {
+            outputs.insert(socket.socket_id, (index, output));
+        }
+
+        payload.nodes.push(ClipboardNode {
+            node_kind: node.node_kind.clone(),
+            offset: NodePosition {
+                x: node.position.x - origin.x,
+                y: node.position.y - origin.y,
+            },
+            defaults,
+            properties: node
+                .properties
+                .iter()
+                .map(|property| (property.name.clone(), property.value.clone()))
+                .collect(),
+        });
+    }
+
+    for edge in document.edges() {
+        let Some(&(to_node, to_input)) = inputs.get(&edge.to) else {
+            continue;
+        };
+        match outputs.get(&edge.from) {
+            Some(&(from_node, from_output)) => payload.edges.push(ClipboardEdge {
+                from_node,
+                from_output,
+                to_node,
+                to_input,
+            }),
+            None => payload.upstream_edges.push(UpstreamEdge {
+                from: edge.from,
+                to_node,
+                to_input,
+            }),
+        }
+    }
+
+    payload
+}
+
+// Command deleting the selected nodes as one undo step
+// Copy the selection first to complete a cut
+pub fn cut_command(selection: &[NodeId]) -> EditorCommand {
+    let mut node_ids = selection.to_vec();
+    node_ids.sort_by_key(|id| id.0);
+    node_ids.dedup();
+
+    EditorCommand::Batch {
+        label: "Cut".to_string(),
+        commands: node_ids
+            .into_iter()
+            .map(|node_id| EditorCommand::DeleteNode { node_id })
+            .collect(),
+    }
+}
@@ -1,27 +1,41 @@
 // Command interface for graph mutations
 // All graph modifications flow through explicit commands
 
+use serde::{Deserialize, Serialize};
+
+use crate::clipboard::ClipboardPayload;
+
 // Stable identifier for a node in the document graph
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct NodeId(pub u64);
 
 // Stable identifier for a socket on a node
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SocketId(pub u64);
 
 // Stable identifier for an edge connecting two sockets
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct EdgeId(pub u64);
 
+// Result of a successfully applied command
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutcome {
+    // Document revision after the command
+    pub revision: u64,
+    // Nodes and edges the command created, in creation order
+    pub created_nodes: Vec<NodeId>,
+    pub created_edges: Vec<EdgeId>,
+}
+
 // Node position in document space
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct NodePosition {
     pub x: f32,
     pub y: f32,
 }
 
 // Typed literal value for socket defaults
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum LiteralValue {
     Bool(bool),
     Int(i64),
@@ -29,10 +43,80 @@ pub enum LiteralValue {
     Vec2([f32; 2]),
     Vec3([f32; 3]),
     Vec4([f32; 4]),
+    Mat2([f32; 4]),
+    Mat3([f32; 9]),
+    Mat4([f32; 16]),
+}
+
+impl From<&wglymr_core::Literal> for LiteralValue {
+    fn from(literal: &wglymr_core::Literal) -> Self {
+        match literal {
+            wglymr_core::Literal::Float(v) => LiteralValue::Float(*v as f64),
+            wglymr_core::Literal::Vec2(v) => LiteralValue::Vec2(*v),
+            wglymr_core::Literal::Vec3(v) => LiteralValue::Vec3(*v),
+            wglymr_core::Literal::Vec4(v) => LiteralValue::Vec4(*v),
+            wglymr_core::Literal::Bool(v) => LiteralValue::Bool(*v),
+            wglymr_core::Literal::Int(v) => LiteralValue::Int(*v as i64),
+            wglymr_core::Literal::Mat2(v) => LiteralValue::Mat2(*v),
+            wglymr_core::Literal::Mat3(v) => LiteralValue::Mat3(*v),
+            wglymr_core::Literal::Mat4(v) => LiteralValue::Mat4(*v),
+        }
+    }
+}
+
+impl TryFrom<&LiteralValue> for wglymr_core::Literal {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(value: &LiteralValue) -> Result<Self, Self::Error> {
+        Ok(match value {
+            LiteralValue::Bool(v) => wglymr_core::Literal::Bool(*v),
+            LiteralValue::Int(v) => wglymr_core::Literal::Int(i32::try_from(*v)?),
+            LiteralValue::Float(v) => wglymr_core::Literal::Float(*v as f32),
+            LiteralValue::Vec2(v) => wglymr_core::Literal::Vec2(*v),
+            LiteralValue::Vec3(v) => wglymr_core::Literal::Vec3(*v),
+            LiteralValue::Vec4(v) => wglymr_core::Literal::Vec4(*v),
+            LiteralValue::Mat2(v) => wglymr_core::Literal::Mat2(*v),
+            LiteralValue::Mat3(v) => wglymr_core::Literal::Mat3(*v),
+            LiteralValue::Mat4(v) => wglymr_core::Literal::Mat4(*v),
+        })
+    }
+}
+
+// Value of a node property
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PropertyValue {
+    Enum(String),
+    Bool(bool),
+    Int(i64),
+    String(String),
+}
+
+impl From<&wglymr_core::PropertyValue> for PropertyValue {
+    fn from(value: &wglymr_core::PropertyValue) -> Self {
+        match value {
+            wglymr_core::PropertyValue::Enum(v) => PropertyValue::Enum(v.clone()),
+            wglymr_core::PropertyValue::Bool(v) => PropertyValue::Bool(*v),
+            wglymr_core::PropertyValue::Int(v) => PropertyValue::Int(*v as i64),
+            wglymr_core::PropertyValue::String(v) => PropertyValue::String(v.clone()),
+        }
+    }
+}
+
+impl TryFrom<&PropertyValue> for wglymr_core::PropertyValue {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(value: &PropertyValue) -> Result<Self, Self::Error> {
+        Ok(match value {
+            PropertyValue::Enum(v) => wglymr_core::PropertyValue::Enum(v.clone()),
+            PropertyValue::Bool(v) => wglymr_core::PropertyValue::Bool(*v),
+            PropertyValue::Int(v) => wglymr_core::PropertyValue::Int(i32::try_from(*v)?),
+            PropertyValue::String(v) => wglymr_core::PropertyValue::String(v.clone()),
+        })
+    }
 }
 
 // Atomic document mutation command
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum EditorCommand {
     CreateNode {
         node_kind: String,
@@ -61,4 +145,40 @@ pub enum EditorCommand {
     ClearDefaultValue {
         socket_id: SocketId,
     },
+    SetNodeProperty {
+        node_id: NodeId,
+        name: String,
+        value: PropertyValue,
+    },
+    // Apply commands in order as one step
+    // Either all of them apply or none do, and they undo together
+    Batch {
+        label: String,
+        commands: Vec<EditorCommand>,
+    },
+    // Create copies of the payload's nodes placed relative to `position`
+    // Upstream edges are kept on request when their source socket still exists
+    Paste {
+        payload: ClipboardPayload,
+        position: NodePosition,
+        keep_upstream_edges: bool,
+    },
+}
+
+impl EditorCommand {
+    // Name of the edit for undo history display
+    pub fn label(&self) -> &str {
+        match self {
+            EditorCommand::CreateNode { .. } => "Create Node",
+            EditorCommand::DeleteNode { .. } => "Delete Node",
+            EditorCommand::MoveNode { .. } => "Move Node",
+            EditorCommand::CreateEdge { .. } => "Create Edge",
+            EditorCommand::DeleteEdge { .. } => "Delete Edge",
+            EditorCommand::SetDefaultValue { .. } => "Set Default Value",
+            EditorCommand::ClearDefaultValue { .. } => "Clear Default Value",
+            EditorCommand::SetNodeProperty { .. } => "Set Node Property",
+            EditorCommand::Batch { label, .. } => label,
+            EditorCommand::Paste { .. } => "Paste",
+        }
+    }
 }
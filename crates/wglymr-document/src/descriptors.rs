@@ -1,7 +1,7 @@
 // Read-only descriptor structs for rendering
 // Immutable snapshots derived from document state
 
-use crate::commands::{EdgeId, LiteralValue, NodeId, NodePosition, SocketId};
+use crate::commands::{EdgeId, LiteralValue, NodeId, NodePosition, PropertyValue, SocketId};
 
 // Socket direction on a node
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -10,6 +10,15 @@ pub enum SocketDirection {
     Output,
 }
 
+impl From<wglymr_core::SocketDirection> for SocketDirection {
+    fn from(direction: wglymr_core::SocketDirection) -> Self {
+        match direction {
+            wglymr_core::SocketDirection::Input => SocketDirection::Input,
+            wglymr_core::SocketDirection::Output => SocketDirection::Output,
+        }
+    }
+}
+
 // Diagnostic severity level
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DiagnosticSeverity {
@@ -18,6 +27,16 @@ pub enum DiagnosticSeverity {
     Info,
 }
 
+// Info is never produced by the compiler
+impl From<wglymr_core::DiagnosticSeverity> for DiagnosticSeverity {
+    fn from(severity: wglymr_core::DiagnosticSeverity) -> Self {
+        match severity {
+            wglymr_core::DiagnosticSeverity::Error => DiagnosticSeverity::Error,
+            wglymr_core::DiagnosticSeverity::Warning => DiagnosticSeverity::Warning,
+        }
+    }
+}
+
 // Diagnostic message attached to graph elements
 #[derive(Debug, Clone, PartialEq)]
 pub struct Diagnostic {
@@ -25,6 +44,70 @@ pub struct Diagnostic {
     pub severity: DiagnosticSeverity,
 }
 
+impl From<&wglymr_core::Diagnostic> for Diagnostic {
+    fn from(diagnostic: &wglymr_core::Diagnostic) -> Self {
+        Self {
+            message: diagnostic.message.clone(),
+            severity: diagnostic.severity.clone().into(),
+        }
+    }
+}
+
+// Meaning of a socket value, used to pick an inspector widget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SocketSubtype {
+    #[default]
+    None,
+    Factor,
+    Angle,
+    Distance,
+    Color,
+    Direction,
+}
+
+impl From<wglymr_core::SocketSubtype> for SocketSubtype {
+    fn from(subtype: wglymr_core::SocketSubtype) -> Self {
+        match subtype {
+            wglymr_core::SocketSubtype::None => SocketSubtype::None,
+            wglymr_core::SocketSubtype::Factor => SocketSubtype::Factor,
+            wglymr_core::SocketSubtype::Angle => SocketSubtype::Angle,
+            wglymr_core::SocketSubtype::Distance => SocketSubtype::Distance,
+            wglymr_core::SocketSubtype::Color => SocketSubtype::Color,
+            wglymr_core::SocketSubtype::Direction => SocketSubtype::Direction,
+        }
+    }
+}
+
+// Inspector hints for an input socket
+// Hard limits are enforced on defaults, soft limits only bound sliders
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SocketMetadata {
+    pub hard_min: Option<f64>,
+    pub hard_max: Option<f64>,
+    pub soft_min: Option<f64>,
+    pub soft_max: Option<f64>,
+    pub step: Option<f64>,
+    pub subtype: SocketSubtype,
+    pub unit: Option<String>,
+    pub description: Option<String>,
+}
+
+impl From<&wglymr_core::SocketMetadata> for SocketMetadata {
+    fn from(metadata: &wglymr_core::SocketMetadata) -> Self {
+        let widen = |value: Option<f32>| value.map(f64::from);
+        Self {
+            hard_min: widen(metadata.hard_min),
+            hard_max: widen(metadata.hard_max),
+            soft_min: widen(metadata.soft_min),
+            soft_max: widen(metadata.soft_max),
+            step: widen(metadata.step),
+            subtype: metadata.subtype.into(),
+            unit: metadata.unit.clone(),
+            description: metadata.description.clone(),
+        }
+    }
+}
+
 // Read-only socket descriptor for rendering
 #[derive(Debug, Clone, PartialEq)]
 pub struct SocketDescriptor {
@@ -34,11 +117,22 @@ pub struct SocketDescriptor {
     pub direction: SocketDirection,
     pub type_name: Option<String>,
     pub default_value: Option<LiteralValue>,
+    pub metadata: SocketMetadata,
     pub connected_edges: Vec<EdgeId>,
     pub diagnostics: Vec<Diagnostic>,
 }
 
+// Node property as shown in the inspector
+// Options list the choices of enum properties and are empty otherwise
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyDescriptor {
+    pub name: String,
+    pub value: PropertyValue,
+    pub options: Vec<String>,
+}
+
 // Read-only node descriptor for rendering
+// Inputs hidden by the node's properties are left out of inputs
 #[derive(Debug, Clone, PartialEq)]
 pub struct NodeDescriptor {
     pub node_id: NodeId,
@@ -46,6 +140,7 @@ pub struct NodeDescriptor {
     pub position: NodePosition,
     pub inputs: Vec<SocketId>,
     pub outputs: Vec<SocketId>,
+    pub properties: Vec<PropertyDescriptor>,
     pub diagnostics: Vec<Diagnostic>,
 }
 
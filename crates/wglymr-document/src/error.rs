@@ -0,0 +1,96 @@
+// Errors from applying editor commands to a document
+// Core graph errors are reported in terms of document ids
+
+use thiserror::Error;
+use wglymr_core::{GraphError, PropertyError};
+
+use crate::commands::{EdgeId, NodeId, SocketId};
+use crate::descriptors::SocketDirection;
+
+#[derive(Debug, Error)]
+pub enum DocumentError {
+    #[error("Unknown node kind {0:?}")]
+    UnknownNodeKind(String),
+
+    #[error("Node {node_id:?} not found")]
+    NodeNotFound { node_id: NodeId },
+
+    #[error("Socket {socket_id:?} not found")]
+    SocketNotFound { socket_id: SocketId },
+
+    #[error("Edge {edge_id:?} not found")]
+    EdgeNotFound { edge_id: EdgeId },
+
+    #[error("Node id {node_id:?} is already in use")]
+    NodeIdInUse { node_id: NodeId },
+
+    #[error("Edge id {edge_id:?} is already in use")]
+    EdgeIdInUse { edge_id: EdgeId },
+
+    #[error("Node id {node_id:?} is reserved")]
+    NodeIdReserved { node_id: NodeId },
+
+    #[error("Edge id {edge_id:?} is reserved")]
+    EdgeIdReserved { edge_id: EdgeId },
+
+    #[error("Node {node_id:?} still has edges attached")]
+    NodeHasEdges { node_id: NodeId },
+
+    #[error("Socket has wrong direction (expected {expected:?}, got {found:?})")]
+    WrongDirection {
+        expected: SocketDirection,
+        found: SocketDirection,
+    },
+
+    #[error("Type mismatch: cannot connect {from} to {to}")]
+    TypeMismatch { from: String, to: String },
+
+    #[error("Input socket {socket_id:?} already has an incoming edge")]
+    InputAlreadyConnected { socket_id: SocketId },
+
+    // `nodes` run downstream from the target node to the source node,
+    // joined by the existing edges in `path`
+    #[error("Edge would close a cycle through nodes {nodes:?}")]
+    EdgeClosesCycle {
+        nodes: Vec<NodeId>,
+        path: Vec<EdgeId>,
+    },
+
+    #[error("Input socket {socket_id:?} is required and takes no default value")]
+    RequiredInput { socket_id: SocketId },
+
+    #[error("Socket {socket_id:?} has no declared default value")]
+    NoDeclaredDefault { socket_id: SocketId },
+
+    #[error("Default value for socket {socket_id:?} is outside {min:?}..{max:?}")]
+    DefaultOutOfRange {
+        socket_id: SocketId,
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+
+    #[error(
+        "Default value type mismatch for socket {socket_id:?}: expected {expected}, found {found}"
+    )]
+    DefaultTypeMismatch {
+        socket_id: SocketId,
+        expected: String,
+        found: String,
+    },
+
+    #[error("Integer value does not fit in 32 bits")]
+    IntegerOutOfRange,
+
+    #[error("Node {node_id:?} has no registered definition")]
+    UnknownDefinition { node_id: NodeId },
+
+    #[error("Invalid property for node {node_id:?}: {source}")]
+    Property {
+        node_id: NodeId,
+        #[source]
+        source: PropertyError,
+    },
+
+    #[error(transparent)]
+    Graph(GraphError),
+}
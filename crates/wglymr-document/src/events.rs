@@ -0,0 +1,96 @@
+// Change events published by documents
+// Subscribers receive the events of each applied command, undo or redo
+// together with the revision they lead to
+
+use crate::commands::{EdgeId, LiteralValue, NodeId, NodePosition, PropertyValue, SocketId};
+
+// Structured delta of a single document change
+#[derive(Debug, Clone, PartialEq)]
+pub enum DocumentEvent {
+    NodeAdded {
+        node_id: NodeId,
+    },
+    NodeRemoved {
+        node_id: NodeId,
+    },
+    NodeMoved {
+        node_id: NodeId,
+        position: NodePosition,
+    },
+    EdgeAdded {
+        edge_id: EdgeId,
+        from: SocketId,
+        to: SocketId,
+    },
+    EdgeRemoved {
+        edge_id: EdgeId,
+    },
+    // Value is the stored default, after any clamping
+    DefaultChanged {
+        socket_id: SocketId,
+        value: LiteralValue,
+    },
+    PropertyChanged {
+        node_id: NodeId,
+        name: String,
+        value: PropertyValue,
+    },
+    // Elements whose diagnostics were added, changed or cleared
+    DiagnosticsChanged {
+        targets: Vec<DiagnosticTarget>,
+    },
+}
+
+// Document element diagnostics can be attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticTarget {
+    Node(NodeId),
+    Socket(SocketId),
+    Edge(EdgeId),
+}
+
+// Receives the revision after a change and the events of that change
+pub type DocumentSubscriber = Box<dyn FnMut(u64, &[DocumentEvent])>;
+
+// Handle for removing a subscriber
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+// Registered subscribers of a document
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<(SubscriptionId, DocumentSubscriber)>,
+    next_id: u64,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&mut self, subscriber: DocumentSubscriber) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.subscribers.push((id, subscriber));
+        id
+    }
+
+    // Returns false when the subscription was already removed
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let count = self.subscribers.len();
+        self.subscribers
+            .retain(|(subscription, _)| *subscription != id);
+        self.subscribers.len() != count
+    }
+
+    // Deliver events to every subscriber in registration order
+    // Nothing is delivered for an empty change
+    pub fn publish(&mut self, revision: u64, events: &[DocumentEvent]) {
+        if events.is_empty() {
+            return;
+        }
+        for (_, subscriber) in &mut self.subscribers {
+            subscriber(revision, events);
+        }
+    }
+}
@@ -0,0 +1,1403 @@
+// Document adapter backed by a wglymr-core graph
+// Applies editor commands to the graph and derives descriptors from it
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use wglymr_core::{
+    analyze_ranges, are_compatible, build_graph_view, compile_graph_with,
+    diagnostics_from_compile_error, CompileOptions, CompileStats, Graph, GraphError, InputDef,
+    IrProgram, Literal, NodeKind, NodeProperties, NodeRegistry, RangeHints, Socket,
+};
+
+use crate::adapter::{property_descriptors, visible_inputs, DocumentAdapter};
+use crate::clipboard::ClipboardPayload;
+use crate::commands::{
+    CommandOutcome, EdgeId, EditorCommand, LiteralValue, NodeId, NodePosition, PropertyValue,
+    SocketId,
+};
+use crate::descriptors::{
+    Diagnostic, EdgeDescriptor, NodeDescriptor, SocketDescriptor, SocketMetadata,
+};
+use crate::error::DocumentError;
+use crate::events::{
+    DiagnosticTarget, DocumentEvent, DocumentSubscriber, EventBus, SubscriptionId,
+};
+use crate::history::History;
+use crate::snapshot::GraphSnapshot;
+
+// Document id backed by a raw counter value
+trait DocumentId: Copy + Eq + Hash {
+    fn from_raw(raw: u64) -> Self;
+    fn raw(self) -> u64;
+
+    // The largest id is never assigned, so the counter past it cannot overflow
+    fn is_reserved(self) -> bool {
+        self.raw() == u64::MAX
+    }
+}
+
+impl DocumentId for NodeId {
+    fn from_raw(raw: u64) -> Self {
+        NodeId(raw)
+    }
+
+    fn raw(self) -> u64 {
+        self.0
+    }
+}
+
+impl DocumentId for SocketId {
+    fn from_raw(raw: u64) -> Self {
+        SocketId(raw)
+    }
+
+    fn raw(self) -> u64 {
+        self.0
+    }
+}
+
+impl DocumentId for EdgeId {
+    fn from_raw(raw: u64) -> Self {
+        EdgeId(raw)
+    }
+
+    fn raw(self) -> u64 {
+        self.0
+    }
+}
+
+// Two-way mapping between document ids and core ids
+// Document ids are never reused, even after their core entity is removed
+struct IdTable<D, C> {
+    to_core: HashMap<D, C>,
+    to_document: HashMap<C, D>,
+    next: u64,
+}
+
+impl<D: DocumentId, C: Copy + Eq + Hash> IdTable<D, C> {
+    fn new() -> Self {
+        Self {
+            to_core: HashMap::new(),
+            to_document: HashMap::new(),
+            next: 1,
+        }
+    }
+
+    // Whether a requested document id can be assigned
+    fn is_free(&self, id: D) -> bool {
+        !self.to_core.contains_key(&id)
+    }
+
+    fn insert(&mut self, requested: Option<D>, core: C) -> D {
+        let id = requested.unwrap_or(D::from_raw(self.next));
+        self.next = self.next.max(id.raw().saturating_add(1));
+        self.to_core.insert(id, core);
+        self.to_document.insert(core, id);
+        id
+    }
+
+    fn remove_core(&mut self, core: C) {
+        if let Some(id) = self.to_document.remove(&core) {
+            self.to_core.remove(&id);
+        }
+    }
+
+    fn core(&self, id: D) -> Option<C> {
+        self.to_core.get(&id).copied()
+    }
+
+    fn document(&self, core: C) -> Option<D> {
+        self.to_document.get(&core).copied()
+    }
+
+    fn to_saved(&self) -> SavedIdTable<C> {
+        let mut entries: Vec<_> = self
+            .to_core
+            .iter()
+            .map(|(id, &core)| (id.raw(), core))
+            .collect();
+        entries.sort_by_key(|&(id, _)| id);
+        SavedIdTable {
+            entries,
+            next: self.next,
+        }
+    }
+
+    // None when an id is mapped twice
+    fn from_saved(saved: SavedIdTable<C>) -> Option<Self> {
+        let mut table = Self::new();
+        for (id, core) in saved.entries {
+            let id = D::from_raw(id);
+            if id.is_reserved() || !table.is_free(id) || table.to_document.contains_key(&core) {
+                return None;
+            }
+            table.insert(Some(id), core);
+        }
+        table.next = table.next.max(saved.next);
+        Some(table)
+    }
+
+    fn len(&self) -> usize {
+        self.to_core.len()
+    }
+}
+
+// Document to core id mapping as stored in document files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SavedIdTable<C> {
+    entries: Vec<(u64, C)>,
+    next: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SavedIds {
+    nodes: SavedIdTable<wglymr_core::NodeId>,
+    sockets: SavedIdTable<wglymr_core::SocketId>,
+    edges: SavedIdTable<wglymr_core::LinkId>,
+}
+
+// Reversible edit of the graph, addressed by document ids
+// Applying a change yields the change that reverts it
+#[derive(Debug, Clone)]
+enum Change {
+    // Add a node from the registry under fresh socket ids
+    Instantiate {
+        node_kind: String,
+        position: Vec2,
+        node_id: Option<NodeId>,
+    },
+    // Restore a removed node under its original ids
+    AddNode(Box<NodeRecord>),
+    RemoveNode(NodeId),
+    MoveNode {
+        node_id: NodeId,
+        position: Vec2,
+    },
+    AddEdge {
+        edge_id: Option<EdgeId>,
+        from: SocketId,
+        to: SocketId,
+    },
+    RemoveEdge(EdgeId),
+    SetDefault {
+        socket_id: SocketId,
+        value: Literal,
+    },
+    // None removes the value, falling back to the declared default
+    SetProperty {
+        node_id: NodeId,
+        name: String,
+        value: Option<wglymr_core::PropertyValue>,
+    },
+}
+
+// Everything needed to restore a removed node
+#[derive(Debug, Clone)]
+struct NodeRecord {
+    node_id: NodeId,
+    kind: NodeKind,
+    position: Vec2,
+    properties: NodeProperties,
+    inputs: Vec<(SocketId, Socket)>,
+    outputs: Vec<(SocketId, Socket)>,
+}
+
+pub struct GraphDocumentAdapter {
+    graph: Graph,
+    registry: NodeRegistry,
+    revision: u64,
+    node_ids: IdTable<NodeId, wglymr_core::NodeId>,
+    socket_ids: IdTable<SocketId, wglymr_core::SocketId>,
+    edge_ids: IdTable<EdgeId, wglymr_core::LinkId>,
+    history: History<Change>,
+    // Events of the changes applied since the last commit
+    events: Vec<DocumentEvent>,
+    subscribers: EventBus,
+    // Descriptors rebuilt from the graph after every applied command
+    nodes: Vec<NodeDescriptor>,
+    sockets: Vec<SocketDescriptor>,
+    edges: Vec<EdgeDescriptor>,
+    // Shares unchanged descriptors with the previous revision
+    snapshot: GraphSnapshot,
+    // Compile after every change instead of on refresh_diagnostics
+    diagnostics_on_change: bool,
+    // Results of the last compilation
+    compiled: HashMap<DiagnosticTarget, Vec<Diagnostic>>,
+    compile_stats: Option<CompileStats>,
+}
+
+impl Default for GraphDocumentAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GraphDocumentAdapter {
+    pub fn new() -> Self {
+        Self::with_registry(NodeRegistry::with_builtins())
+    }
+
+    pub fn with_registry(registry: NodeRegistry) -> Self {
+        Self {
+            graph: Graph::new(),
+            registry,
+            revision: 0,
+            node_ids: IdTable::new(),
+            socket_ids: IdTable::new(),
+            edge_ids: IdTable::new(),
+            history: History::new(),
+            events: Vec::new(),
+            subscribers: EventBus::new(),
+            nodes: Vec::new(),
+            sockets: Vec::new(),
+            edges: Vec::new(),
+            snapshot: GraphSnapshot::empty(),
+            diagnostics_on_change: true,
+            compiled: HashMap::new(),
+            compile_stats: None,
+        }
+    }
+
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    pub fn registry(&self) -> &NodeRegistry {
+        &self.registry
+    }
+
+    pub fn core_node_id(&self, node_id: NodeId) -> Option<wglymr_core::NodeId> {
+        self.node_ids.core(node_id)
+    }
+
+    pub fn document_node_id(&self, node_id: wglymr_core::NodeId) -> Option<NodeId> {
+        self.node_ids.document(node_id)
+    }
+
+    pub fn core_socket_id(&self, socket_id: SocketId) -> Option<wglymr_core::SocketId> {
+        self.socket_ids.core(socket_id)
+    }
+
+    pub fn document_socket_id(&self, socket_id: wglymr_core::SocketId) -> Option<SocketId> {
+        self.socket_ids.document(socket_id)
+    }
+
+    pub fn core_link_id(&self, edge_id: EdgeId) -> Option<wglymr_core::LinkId> {
+        self.edge_ids.core(edge_id)
+    }
+
+    pub fn document_edge_id(&self, link_id: wglymr_core::LinkId) -> Option<EdgeId> {
+        self.edge_ids.document(link_id)
+    }
+
+    // Id mappings to store next to the graph in a document file
+    pub(crate) fn saved_ids(&self) -> SavedIds {
+        SavedIds {
+            nodes: self.node_ids.to_saved(),
+            sockets: self.socket_ids.to_saved(),
+            edges: self.edge_ids.to_saved(),
+        }
+    }
+
+    // Restore a saved document at revision 1 with an empty history
+    // None when the ids do not cover exactly the elements of the graph or
+    // the graph refers to sockets it does not contain
+    pub(crate) fn from_saved(graph: Graph, ids: SavedIds, registry: NodeRegistry) -> Option<Self> {
+        let node_ids = IdTable::from_saved(ids.nodes)?;
+        let socket_ids = IdTable::from_saved(ids.sockets)?;
+        let edge_ids = IdTable::from_saved(ids.edges)?;
+
+        // Every socket is mapped and belongs to the node listing it
+        let owned_socket = |socket: wglymr_core::SocketId, node, direction| {
+            socket_ids.document(socket).is_some()
+                && graph
+                    .socket(socket)
+                    .is_some_and(|socket| socket.node == node && socket.direction == direction)
+        };
+        let nodes_mapped = graph.nodes().all(|node| {
+            node_ids.document(node.id).is_some()
+                && node.inputs.iter().all(|&socket| {
+                    owned_socket(socket, node.id, wglymr_core::SocketDirection::Input)
+                })
+                && node.outputs.iter().all(|&socket| {
+                    owned_socket(socket, node.id, wglymr_core::SocketDirection::Output)
+                })
+        });
+        let sockets: usize = graph
+            .nodes()
+            .map(|node| node.inputs.len() + node.outputs.len())
+            .sum();
+        let linked_socket = |socket: wglymr_core::SocketId, direction| {
+            socket_ids.document(socket)?;
+            graph
+                .socket(socket)
+                .filter(|socket| socket.direction == direction)
+        };
+        // Links hold the invariants `Graph::connect` enforces
+        let mut linked_inputs = HashSet::new();
+        let links_valid = graph.links().all(|link| {
+            let from = linked_socket(link.from, wglymr_core::SocketDirection::Output);
+            let to = linked_socket(link.to, wglymr_core::SocketDirection::Input);
+            edge_ids.document(link.id).is_some()
+                && from
+                    .zip(to)
+                    .is_some_and(|(from, to)| are_compatible(from.value_type, to.value_type))
+                && linked_inputs.insert(link.to)
+        });
+        let roots: Vec<_> = graph.node_ids().collect();
+        let cycles_valid = !graph.rejects_cycles() || build_graph_view(&graph, &roots).is_ok();
+        if !nodes_mapped
+            || !links_valid
+            || !cycles_valid
+            || node_ids.len() != graph.nodes().count()
+            || socket_ids.len() != sockets
+            || edge_ids.len() != graph.links().count()
+        {
+            return None;
+        }
+
+        let mut adapter = Self::with_registry(registry);
+        adapter.graph = graph;
+        adapter.node_ids = node_ids;
+        adapter.socket_ids = socket_ids;
+        adapter.edge_ids = edge_ids;
+        if adapter.diagnostics_on_change {
+            adapter.compile();
+        }
+        adapter.rebuild_descriptors();
+        adapter.revision = 1;
+        adapter.snapshot = GraphSnapshot::from_slices(
+            adapter.revision,
+            &adapter.nodes,
+            &adapter.sockets,
+            &adapter.edges,
+        );
+        Some(adapter)
+    }
+
+    pub fn diagnostics_on_change(&self) -> bool {
+        self.diagnostics_on_change
+    }
+
+    // Turn compiling after every change on or off
+    // When off, diagnostics are only updated by refresh_diagnostics
+    pub fn set_diagnostics_on_change(&mut self, enabled: bool) {
+        self.diagnostics_on_change = enabled;
+    }
+
+    // Maximum number of undo steps kept
+    pub fn history_limit(&self) -> usize {
+        self.history.limit()
+    }
+
+    // Change the number of undo steps kept, dropping the oldest beyond it
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history.set_limit(limit);
+    }
+
+    // Carry out a command, returning the changes that revert it
+    fn execute(
+        &mut self,
+        command: EditorCommand,
+        outcome: &mut CommandOutcome,
+    ) -> Result<Vec<Change>, DocumentError> {
+        match command {
+            EditorCommand::Batch { commands, .. } => self.execute_all(commands, outcome),
+            EditorCommand::Paste {
+                payload,
+                position,
+                keep_upstream_edges,
+            } => self.paste(&payload, position, keep_upstream_edges, outcome),
+            command => {
+                let changes = self.changes_for(command)?;
+                let inverse = self.apply_changes(changes)?;
+                // Only creations are reverted by removals
+                for change in inverse.iter().rev() {
+                    match change {
+                        Change::RemoveNode(node_id) => outcome.created_nodes.push(*node_id),
+                        Change::RemoveEdge(edge_id) => outcome.created_edges.push(*edge_id),
+                        _ => {}
+                    }
+                }
+                Ok(inverse)
+            }
+        }
+    }
+
+    // Carry out commands in order
+    // When one fails, the commands already applied are reverted
+    fn execute_all(
+        &mut self,
+        commands: Vec<EditorCommand>,
+        outcome: &mut CommandOutcome,
+    ) -> Result<Vec<Change>, DocumentError> {
+        let mut inverse: Vec<Change> = Vec::new();
+        for command in commands {
+            match self.execute(command, outcome) {
+                Ok(mut changes) => {
+                    // Later commands are reverted first
+                    changes.append(&mut inverse);
+                    inverse = changes;
+                }
+                Err(error) => {
+                    self.apply_changes(inverse)
+                        .expect("inverse of applied commands applies");
+                    return Err(error);
+                }
+            }
+        }
+        Ok(inverse)
+    }
+
+    // Create the payload's nodes, then set up their defaults, properties and
+    // edges through the socket ids the new nodes received
+    fn paste(
+        &mut self,
+        payload: &ClipboardPayload,
+        position: NodePosition,
+        keep_upstream_edges: bool,
+        outcome: &mut CommandOutcome,
+    ) -> Result<Vec<Change>, DocumentError> {
+        let creations = payload
+            .nodes
+            .iter()
+            .map(|node| EditorCommand::CreateNode {
+                node_kind: node.node_kind.clone(),
+                position: NodePosition {
+                    x: position.x + node.offset.x,
+                    y: position.y + node.offset.y,
+                },
+                node_id: None,
+            })
+            .collect();
+        let first = outcome.created_nodes.len();
+        let mut inverse = self.execute_all(creations, outcome)?;
+
+        let node_ids = outcome.created_nodes[first..].to_vec();
+        let (inputs, outputs): (Vec<_>, Vec<_>) = node_ids
+            .iter()
+            .map(|&node_id| self.node_sockets(node_id))
+            .unzip();
+        let input = |node: usize, index: usize| inputs[node].get(index).copied();
+        let output = |node: usize, index: usize| outputs[node].get(index).copied();
+
+        let mut commands = Vec::new();
+        for (index, node) in payload.nodes.iter().enumerate() {
+            for (name, value) in &node.properties {
+                commands.push(EditorCommand::SetNodeProperty {
+                    node_id: node_ids[index],
+                    name: name.clone(),
+                    value: value.clone(),
+                });
+            }
+            for (input_index, value) in &node.defaults {
+                if let Some(socket_id) = input(index, *input_index) {
+                    commands.push(EditorCommand::SetDefaultValue {
+                        socket_id,
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+        for edge in &payload.edges {
+            let from = output(edge.from_node, edge.from_output);
+            let to = input(edge.to_node, edge.to_input);
+            if let (Some(from), Some(to)) = (from, to) {
+                commands.push(EditorCommand::CreateEdge {
+                    from,
+                    to,
+                    edge_id: None,
+                });
+            }
+        }
+        if keep_upstream_edges {
+            for edge in &payload.upstream_edges {
+                let to = input(edge.to_node, edge.to_input);
+                if let (Some(_), Some(to)) = (self.socket_ids.core(edge.from), to) {
+                    commands.push(EditorCommand::CreateEdge {
+                        from: edge.from,
+                        to,
+                        edge_id: None,
+                    });
+                }
+            }
+        }
+
+        match self.execute_all(commands, outcome) {
+            Ok(mut changes) => {
+                changes.append(&mut inverse);
+                Ok(changes)
+            }
+            Err(error) => {
+                self.apply_changes(inverse)
+                    .expect("inverse of applied commands applies");
+                Err(error)
+            }
+        }
+    }
+
+    // Document ids of a node's input and output sockets
+    fn node_sockets(&self, node_id: NodeId) -> (Vec<SocketId>, Vec<SocketId>) {
+        let node = self.node(node_id).expect("node was just created");
+        let document_ids = |sockets: &[wglymr_core::SocketId]| {
+            sockets
+                .iter()
+                .map(|&socket| self.socket_ids.document(socket).expect("socket is mapped"))
+                .collect()
+        };
+        (document_ids(&node.inputs), document_ids(&node.outputs))
+    }
+
+    // Translate a command into the changes that carry it out
+    // Fails early when the command is known not to apply
+    fn changes_for(&self, command: EditorCommand) -> Result<Vec<Change>, DocumentError> {
+        let changes = match command {
+            EditorCommand::CreateNode {
+                node_kind,
+                position,
+                node_id,
+            } => vec![Change::Instantiate {
+                node_kind,
+                position: Vec2::new(position.x, position.y),
+                node_id,
+            }],
+            EditorCommand::DeleteNode { node_id } => {
+                // Edges go first so that restoring the node can bring them back
+                let node = self.node(node_id)?;
+                let mut edges: Vec<EdgeId> = self
+                    .graph
+                    .links()
+                    .filter(|link| {
+                        node.inputs.contains(&link.to) || node.outputs.contains(&link.from)
+                    })
+                    .filter_map(|link| self.edge_ids.document(link.id))
+                    .collect();
+                edges.sort_by_key(|id| id.0);
+                edges
+                    .into_iter()
+                    .map(Change::RemoveEdge)
+                    .chain([Change::RemoveNode(node_id)])
+                    .collect()
+            }
+            EditorCommand::MoveNode {
+                node_id,
+                new_position,
+            } => vec![Change::MoveNode {
+                node_id,
+                position: Vec2::new(new_position.x, new_position.y),
+            }],
+            EditorCommand::CreateEdge { from, to, edge_id } => {
+                vec![Change::AddEdge { edge_id, from, to }]
+            }
+            EditorCommand::DeleteEdge { edge_id } => vec![Change::RemoveEdge(edge_id)],
+            EditorCommand::SetDefaultValue { socket_id, value } => vec![Change::SetDefault {
+                socket_id,
+                value: Literal::try_from(&value).map_err(|_| DocumentError::IntegerOutOfRange)?,
+            }],
+            EditorCommand::ClearDefaultValue { socket_id } => vec![Change::SetDefault {
+                socket_id,
+                value: self.declared_default(socket_id)?,
+            }],
+            EditorCommand::SetNodeProperty {
+                node_id,
+                name,
+                value,
+            } => {
+                let value = wglymr_core::PropertyValue::try_from(&value)
+                    .map_err(|_| DocumentError::IntegerOutOfRange)?;
+                let node = self.node(node_id)?;
+                let definition = self
+                    .registry
+                    .resolve(&node.kind)
+                    .ok_or(DocumentError::UnknownDefinition { node_id })?;
+                definition
+                    .check_property(&name, &value)
+                    .map_err(|source| DocumentError::Property { node_id, source })?;
+
+                // Edges into inputs the new value hides go first, so that
+                // undoing brings them back once the inputs are visible again
+                let mut properties = node.properties.clone();
+                properties.insert(name.clone(), value.clone());
+                let active = definition.active_inputs(&properties);
+                let hidden: Vec<wglymr_core::SocketId> = node
+                    .inputs
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| !active.contains(index))
+                    .map(|(_, &socket)| socket)
+                    .collect();
+                let mut edges: Vec<EdgeId> = self
+                    .graph
+                    .links()
+                    .filter(|link| hidden.contains(&link.to))
+                    .filter_map(|link| self.edge_ids.document(link.id))
+                    .collect();
+                edges.sort_by_key(|id| id.0);
+                edges
+                    .into_iter()
+                    .map(Change::RemoveEdge)
+                    .chain([Change::SetProperty {
+                        node_id,
+                        name,
+                        value: Some(value),
+                    }])
+                    .collect()
+            }
+            EditorCommand::Batch { .. } | EditorCommand::Paste { .. } => {
+                unreachable!("compound commands are executed command by command")
+            }
+        };
+        Ok(changes)
+    }
+
+    fn node(&self, node_id: NodeId) -> Result<&wglymr_core::Node, DocumentError> {
+        self.node_ids
+            .core(node_id)
+            .and_then(|node| self.graph.node(node))
+            .ok_or(DocumentError::NodeNotFound { node_id })
+    }
+
+    fn core_socket(&self, socket_id: SocketId) -> Result<wglymr_core::SocketId, DocumentError> {
+        self.socket_ids
+            .core(socket_id)
+            .ok_or(DocumentError::SocketNotFound { socket_id })
+    }
+
+    // Default declared by the definition of the socket's node
+    fn declared_default(&self, socket_id: SocketId) -> Result<Literal, DocumentError> {
+        let socket = self.core_socket(socket_id)?;
+        let node = self
+            .graph
+            .socket(socket)
+            .and_then(|s| self.graph.node(s.node))
+            .ok_or(DocumentError::SocketNotFound { socket_id })?;
+        let index = node.inputs.iter().position(|&input| input == socket);
+        let definition = self.registry.resolve(&node.kind);
+        index
+            .zip(definition)
+            .and_then(|(index, definition)| definition.inputs.get(index)?.default.clone())
+            .ok_or(DocumentError::NoDeclaredDefault { socket_id })
+    }
+
+    // Report a core graph error in terms of document ids
+    fn document_error(&self, error: GraphError) -> DocumentError {
+        let type_name = |value_type| format!("{:?}", value_type);
+        match error {
+            GraphError::SocketNotFound { socket } => match self.socket_ids.document(socket) {
+                Some(socket_id) => DocumentError::SocketNotFound { socket_id },
+                None => DocumentError::Graph(error),
+            },
+            GraphError::NodeNotFound { node } => match self.node_ids.document(node) {
+                Some(node_id) => DocumentError::NodeNotFound { node_id },
+                None => DocumentError::Graph(error),
+            },
+            GraphError::WrongDirection { expected, found } => DocumentError::WrongDirection {
+                expected: expected.into(),
+                found: found.into(),
+            },
+            GraphError::TypeMismatch { from, to } => DocumentError::TypeMismatch {
+                from: type_name(from),
+                to: type_name(to),
+            },
+            GraphError::LinkClosesCycle { nodes, path } => DocumentError::EdgeClosesCycle {
+                nodes: nodes
+                    .iter()
+                    .filter_map(|&node| self.node_ids.document(node))
+                    .collect(),
+                path: path
+                    .iter()
+                    .filter_map(|&link| self.edge_ids.document(link))
+                    .collect(),
+            },
+            GraphError::RequiredInput { socket } => match self.socket_ids.document(socket) {
+                Some(socket_id) => DocumentError::RequiredInput { socket_id },
+                None => DocumentError::Graph(error),
+            },
+            GraphError::DefaultOutOfRange { socket, min, max } => {
+                match self.socket_ids.document(socket) {
+                    Some(socket_id) => DocumentError::DefaultOutOfRange {
+                        socket_id,
+                        min: min.map(f64::from),
+                        max: max.map(f64::from),
+                    },
+                    None => DocumentError::Graph(error),
+                }
+            }
+            GraphError::DefaultLiteralTypeMismatch {
+                socket,
+                expected,
+                found,
+            } => match self.socket_ids.document(socket) {
+                Some(socket_id) => DocumentError::DefaultTypeMismatch {
+                    socket_id,
+                    expected: type_name(expected),
+                    found: type_name(found),
+                },
+                None => DocumentError::Graph(error),
+            },
+            _ => DocumentError::Graph(error),
+        }
+    }
+
+    // Apply changes in order, returning the changes that revert them all
+    // When a change fails, the ones already applied are reverted
+    fn apply_changes(&mut self, changes: Vec<Change>) -> Result<Vec<Change>, DocumentError> {
+        let mut inverses = Vec::with_capacity(changes.len());
+        for change in changes {
+            match self.apply_change(change) {
+                Ok(inverse) => {
+                    let event = self.event_for(&inverse);
+                    self.events.push(event);
+                    inverses.push(inverse);
+                }
+                Err(error) => {
+                    for inverse in inverses.into_iter().rev() {
+                        self.apply_change(inverse)
+                            .expect("inverse of an applied change applies");
+                    }
+                    return Err(error);
+                }
+            }
+        }
+        inverses.reverse();
+        Ok(inverses)
+    }
+
+    // Apply a single change, returning the change that reverts it
+    fn apply_change(&mut self, change: Change) -> Result<Change, DocumentError> {
+        match change {
+            Change::Instantiate {
+                node_kind,
+                position,
+                node_id,
+            } => {
+                if let Some(node_id) = node_id.filter(|&id| id.is_reserved()) {
+                    return Err(DocumentError::NodeIdReserved { node_id });
+                }
+                if let Some(node_id) = node_id.filter(|&id| !self.node_ids.is_free(id)) {
+                    return Err(DocumentError::NodeIdInUse { node_id });
+                }
+                let core_id = self
+                    .registry
+                    .instantiate(&mut self.graph, &node_kind, position)
+                    .map_err(|_| DocumentError::UnknownNodeKind(node_kind))?;
+                let node_id = self.node_ids.insert(node_id, core_id);
+                let node = self.graph.node(core_id).expect("node just added");
+                for &socket in node.inputs.iter().chain(&node.outputs) {
+                    self.socket_ids.insert(None, socket);
+                }
+                Ok(Change::RemoveNode(node_id))
+            }
+            Change::AddNode(record) => self.restore_node(*record),
+            Change::RemoveNode(node_id) => self.remove_node(node_id),
+            Change::MoveNode { node_id, position } => {
+                let node = self.node(node_id)?;
+                let (core_id, previous) = (node.id, node.position);
+                self.graph
+                    .set_node_position(core_id, position)
+                    .map_err(|error| self.document_error(error))?;
+                Ok(Change::MoveNode {
+                    node_id,
+                    position: previous,
+                })
+            }
+            Change::AddEdge { edge_id, from, to } => {
+                if let Some(edge_id) = edge_id.filter(|&id| id.is_reserved()) {
+                    return Err(DocumentError::EdgeIdReserved { edge_id });
+                }
+                if let Some(edge_id) = edge_id.filter(|&id| !self.edge_ids.is_free(id)) {
+                    return Err(DocumentError::EdgeIdInUse { edge_id });
+                }
+                let (core_from, core_to) = (self.core_socket(from)?, self.core_socket(to)?);
+                let link = match self.graph.connect(core_from, core_to) {
+                    Ok(link) => link,
+                    Err(GraphError::InputAlreadyConnected) => {
+                        return Err(DocumentError::InputAlreadyConnected { socket_id: to })
+                    }
+                    Err(error) => return Err(self.document_error(error)),
+                };
+                Ok(Change::RemoveEdge(self.edge_ids.insert(edge_id, link)))
+            }
+            Change::RemoveEdge(edge_id) => {
+                let link = self
+                    .edge_ids
+                    .core(edge_id)
+                    .and_then(|link| self.graph.link(link))
+                    .ok_or(DocumentError::EdgeNotFound { edge_id })?;
+                let link_id = link.id;
+                let inverse = Change::AddEdge {
+                    edge_id: Some(edge_id),
+                    from: self
+                        .socket_ids
+                        .document(link.from)
+                        .expect("socket is mapped"),
+                    to: self.socket_ids.document(link.to).expect("socket is mapped"),
+                };
+                self.graph.disconnect(link_id);
+                self.edge_ids.remove_core(link_id);
+                Ok(inverse)
+            }
+            Change::SetDefault { socket_id, value } => {
+                let socket = self.core_socket(socket_id)?;
+                let previous = self
+                    .graph
+                    .socket(socket)
+                    .and_then(|s| s.input_config.as_ref())
+                    .and_then(|config| config.default.clone());
+                self.graph
+                    .set_input_default(socket, value)
+                    .map_err(|error| self.document_error(error))?;
+                Ok(Change::SetDefault {
+                    socket_id,
+                    value: previous.expect("optional inputs have a default"),
+                })
+            }
+            Change::SetProperty {
+                node_id,
+                name,
+                value,
+            } => {
+                let core_id = self.node(node_id)?.id;
+                let previous = match value {
+                    Some(value) => self.graph.set_node_property(core_id, name.clone(), value),
+                    None => self.graph.remove_node_property(core_id, &name),
+                };
+                Ok(Change::SetProperty {
+                    node_id,
+                    name,
+                    value: previous.map_err(|error| self.document_error(error))?,
+                })
+            }
+        }
+    }
+
+    // Event describing an applied change, given the change that reverts it
+    fn event_for(&self, inverse: &Change) -> DocumentEvent {
+        match inverse {
+            Change::Instantiate { .. } => unreachable!("instantiation reverts no change"),
+            &Change::RemoveNode(node_id) => DocumentEvent::NodeAdded { node_id },
+            Change::AddNode(record) => DocumentEvent::NodeRemoved {
+                node_id: record.node_id,
+            },
+            &Change::MoveNode { node_id, .. } => {
+                let position = self.node(node_id).expect("moved node exists").position;
+                DocumentEvent::NodeMoved {
+                    node_id,
+                    position: NodePosition {
+                        x: position.x,
+                        y: position.y,
+                    },
+                }
+            }
+            &Change::RemoveEdge(edge_id) => {
+                let link = self
+                    .edge_ids
+                    .core(edge_id)
+                    .and_then(|link| self.graph.link(link))
+                    .expect("added edge exists");
+                DocumentEvent::EdgeAdded {
+                    edge_id,
+                    from: self
+                        .socket_ids
+                        .document(link.from)
+                        .expect("socket is mapped"),
+                    to: self.socket_ids.document(link.to).expect("socket is mapped"),
+                }
+            }
+            Change::AddEdge { edge_id, .. } => DocumentEvent::EdgeRemoved {
+                edge_id: edge_id.expect("removed edges keep their id"),
+            },
+            &Change::SetDefault { socket_id, .. } => {
+                let default = self
+                    .socket_ids
+                    .core(socket_id)
+                    .and_then(|socket| self.graph.socket(socket))
+                    .and_then(|socket| socket.input_config.as_ref()?.default.as_ref())
+                    .expect("changed default exists");
+                DocumentEvent::DefaultChanged {
+                    socket_id,
+                    value: LiteralValue::from(default),
+                }
+            }
+            Change::SetProperty { node_id, name, .. } => {
+                let node = self.node(*node_id).expect("changed node exists");
+                let value = self
+                    .registry
+                    .resolve(&node.kind)
+                    .and_then(|definition| definition.property(&node.properties, name))
+                    .or_else(|| node.properties.get(name))
+                    .expect("changed property has a value");
+                DocumentEvent::PropertyChanged {
+                    node_id: *node_id,
+                    name: name.clone(),
+                    value: PropertyValue::from(value),
+                }
+            }
+        }
+    }
+
+    // Remove a node whose edges are already gone, recording how to restore it
+    fn remove_node(&mut self, node_id: NodeId) -> Result<Change, DocumentError> {
+        let node = self.node(node_id)?;
+        let attached = self
+            .graph
+            .links()
+            .any(|link| node.inputs.contains(&link.to) || node.outputs.contains(&link.from));
+        if attached {
+            return Err(DocumentError::NodeHasEdges { node_id });
+        }
+
+        let record_sockets = |sockets: &[wglymr_core::SocketId]| {
+            sockets
+                .iter()
+                .map(|&socket| {
+                    let socket_id = self.socket_ids.document(socket).expect("socket is mapped");
+                    (
+                        socket_id,
+                        self.graph.socket(socket).expect("socket exists").clone(),
+                    )
+                })
+                .collect()
+        };
+        let core_id = node.id;
+        let record = NodeRecord {
+            node_id,
+            kind: node.kind.clone(),
+            position: node.position,
+            properties: node.properties.clone(),
+            inputs: record_sockets(&node.inputs),
+            outputs: record_sockets(&node.outputs),
+        };
+
+        self.graph.remove_node(core_id);
+        self.node_ids.remove_core(core_id);
+        for (_, socket) in record.inputs.iter().chain(&record.outputs) {
+            self.socket_ids.remove_core(socket.id);
+        }
+        Ok(Change::AddNode(Box::new(record)))
+    }
+
+    // Add a removed node back under its original document ids
+    fn restore_node(&mut self, record: NodeRecord) -> Result<Change, DocumentError> {
+        if !self.node_ids.is_free(record.node_id) {
+            return Err(DocumentError::NodeIdInUse {
+                node_id: record.node_id,
+            });
+        }
+
+        let inputs = record
+            .inputs
+            .iter()
+            .map(|(_, socket)| InputDef {
+                name: socket.name.clone(),
+                value_type: socket.value_type,
+                config: socket.input_config.clone().unwrap_or_default(),
+            })
+            .collect();
+        let outputs = record
+            .outputs
+            .iter()
+            .map(|(_, socket)| (socket.name.clone(), socket.value_type))
+            .collect();
+        let core_id =
+            self.graph
+                .add_node_with_config(record.kind, record.position, inputs, outputs);
+        for (name, value) in record.properties {
+            self.graph
+                .set_node_property(core_id, name, value)
+                .expect("node just added");
+        }
+
+        self.node_ids.insert(Some(record.node_id), core_id);
+        let node = self.graph.node(core_id).expect("node just added");
+        let core_sockets = node.inputs.iter().chain(&node.outputs);
+        let socket_ids = record.inputs.iter().chain(&record.outputs);
+        for (&socket, &(socket_id, _)) in core_sockets.zip(socket_ids) {
+            self.socket_ids.insert(Some(socket_id), socket);
+        }
+        Ok(Change::RemoveNode(record.node_id))
+    }
+
+    // Publish the graph and its events after an edit, undo or redo
+    fn commit(&mut self) {
+        if self.diagnostics_on_change {
+            self.compile();
+        }
+        self.publish();
+    }
+
+    fn publish(&mut self) {
+        let diagnostics = self.diagnostics();
+        self.rebuild_descriptors();
+        let changed = diff_diagnostics(&diagnostics, &self.diagnostics());
+        if !changed.is_empty() {
+            self.events
+                .push(DocumentEvent::DiagnosticsChanged { targets: changed });
+        }
+
+        self.revision += 1;
+        self.snapshot =
+            self.snapshot
+                .update(self.revision, &self.nodes, &self.sockets, &self.edges);
+        let events = std::mem::take(&mut self.events);
+        self.subscribers.publish(self.revision, &events);
+    }
+
+    // Compile every sink node and map the diagnostics to document elements
+    // Returns whether the diagnostics differ from the last compilation
+    fn compile(&mut self) -> bool {
+        let roots = self.sink_nodes();
+        let options = CompileOptions::default();
+        let mut diagnostics = Vec::new();
+        self.compile_stats = None;
+        if !roots.is_empty() {
+            match compile_graph_with(&self.graph, &roots, &self.registry, options) {
+                Ok(compiled) => {
+                    self.compile_stats = Some(compiled.stats);
+                    diagnostics.extend(
+                        Self::compile_warnings(&compiled.program)
+                            .into_iter()
+                            .map(|warning| (None, warning)),
+                    );
+                }
+                // Compile the sinks one at a time so a broken branch does not
+                // hide the errors of another
+                Err(_) => {
+                    for &root in &roots {
+                        let result =
+                            compile_graph_with(&self.graph, &[root], &self.registry, options);
+                        if let Err(error) = result {
+                            diagnostics.extend(
+                                diagnostics_from_compile_error(&error)
+                                    .into_iter()
+                                    .map(|diagnostic| (Some(root), diagnostic)),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut compiled: HashMap<DiagnosticTarget, Vec<Diagnostic>> = HashMap::new();
+        for (root, diagnostic) in diagnostics {
+            // Diagnostics without a location belong to the sink that failed
+            let target = self
+                .diagnostic_target(&diagnostic)
+                .or_else(|| {
+                    root.and_then(|root| self.node_ids.document(root))
+                        .map(DiagnosticTarget::Node)
+                })
+                .expect("every core node is mapped and warnings are located");
+            let entry = compiled.entry(target).or_default();
+            let diagnostic = Diagnostic::from(&diagnostic);
+            if !entry.contains(&diagnostic) {
+                entry.push(diagnostic);
+            }
+        }
+
+        let changed = compiled != self.compiled;
+        self.compiled = compiled;
+        changed
+    }
+
+    // Warnings for a graph that compiled: values the range analysis finds
+    // suspicious, such as possible divisions by zero
+    // Warnings that cannot be placed on a node are dropped
+    fn compile_warnings(program: &IrProgram) -> Vec<wglymr_core::Diagnostic> {
+        analyze_ranges(program, &RangeHints::default())
+            .diagnostics
+            .into_iter()
+            .filter(|warning| warning.node.is_some())
+            .collect()
+    }
+
+    // Nodes none of whose outputs are connected, ordered by document id
+    fn sink_nodes(&self) -> Vec<wglymr_core::NodeId> {
+        let mut sinks: Vec<_> = self
+            .graph
+            .nodes()
+            .filter(|node| {
+                node.outputs
+                    .iter()
+                    .all(|&output| self.graph.links_out_of(output).next().is_none())
+            })
+            .map(|node| node.id)
+            .collect();
+        sinks.sort_by_key(|&node| self.node_ids.document(node).map(|id| id.0));
+        sinks
+    }
+
+    fn diagnostic_target(&self, diagnostic: &wglymr_core::Diagnostic) -> Option<DiagnosticTarget> {
+        let socket = diagnostic
+            .socket
+            .and_then(|socket| self.socket_ids.document(socket))
+            .map(DiagnosticTarget::Socket);
+        let edge = || {
+            diagnostic
+                .link
+                .and_then(|link| self.edge_ids.document(link))
+                .map(DiagnosticTarget::Edge)
+        };
+        let node = || {
+            diagnostic
+                .node
+                .and_then(|node| self.node_ids.document(node))
+                .map(DiagnosticTarget::Node)
+        };
+        socket.or_else(edge).or_else(node)
+    }
+
+    // Diagnostics attached to an element by the last compilation
+    fn compiled_diagnostics(&self, target: DiagnosticTarget) -> Vec<Diagnostic> {
+        self.compiled.get(&target).cloned().unwrap_or_default()
+    }
+
+    // Diagnostics of every element that has any
+    fn diagnostics(&self) -> HashMap<DiagnosticTarget, Vec<Diagnostic>> {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| (DiagnosticTarget::Node(node.node_id), &node.diagnostics));
+        let sockets = self.sockets.iter().map(|socket| {
+            (
+                DiagnosticTarget::Socket(socket.socket_id),
+                &socket.diagnostics,
+            )
+        });
+        let edges = self
+            .edges
+            .iter()
+            .map(|edge| (DiagnosticTarget::Edge(edge.edge_id), &edge.diagnostics));
+        nodes
+            .chain(sockets)
+            .chain(edges)
+            .filter(|(_, diagnostics)| !diagnostics.is_empty())
+            .map(|(target, diagnostics)| (target, diagnostics.clone()))
+            .collect()
+    }
+
+    // Rebuild every descriptor from the graph, ordered by document id
+    fn rebuild_descriptors(&mut self) {
+        let mut core_nodes: Vec<_> = self.graph.nodes().collect();
+        core_nodes.sort_by_key(|node| self.node_ids.document(node.id).map(|id| id.0));
+
+        let mut core_links: Vec<_> = self.graph.links().collect();
+        core_links.sort_by_key(|link| self.edge_ids.document(link.id).map(|id| id.0));
+
+        let socket_id = |socket| {
+            self.socket_ids
+                .document(socket)
+                .expect("every core socket is mapped")
+        };
+        let edge_id = |link| {
+            self.edge_ids
+                .document(link)
+                .expect("every core link is mapped")
+        };
+
+        let mut nodes = Vec::new();
+        let mut sockets = Vec::new();
+        for node in core_nodes {
+            let node_id = self
+                .node_ids
+                .document(node.id)
+                .expect("every core node is mapped");
+            let definition = self.registry.resolve(&node.kind);
+            let inputs: Vec<SocketId> = node.inputs.iter().map(|&s| socket_id(s)).collect();
+
+            for &core_socket in node.inputs.iter().chain(&node.outputs) {
+                let socket = self.graph.socket(core_socket).expect("node socket exists");
+                let config = socket.input_config.as_ref();
+                let mut connected_edges: Vec<EdgeId> = core_links
+                    .iter()
+                    .filter(|link| link.from == core_socket || link.to == core_socket)
+                    .map(|link| edge_id(link.id))
+                    .collect();
+                connected_edges.sort_by_key(|id| id.0);
+
+                sockets.push(SocketDescriptor {
+                    socket_id: socket_id(core_socket),
+                    node_id,
+                    name: socket.name.clone(),
+                    direction: socket.direction.into(),
+                    type_name: Some(format!("{:?}", socket.value_type)),
+                    default_value: config
+                        .and_then(|c| c.default.as_ref())
+                        .map(LiteralValue::from),
+                    metadata: config
+                        .map(|c| SocketMetadata::from(&c.metadata))
+                        .unwrap_or_default(),
+                    connected_edges,
+                    diagnostics: self
+                        .compiled_diagnostics(DiagnosticTarget::Socket(socket_id(core_socket))),
+                });
+            }
+
+            nodes.push(NodeDescriptor {
+                node_id,
+                node_kind: node
+                    .kind
+                    .definition_id()
+                    .unwrap_or_else(|| format!("{:?}", node.kind)),
+                position: NodePosition {
+                    x: node.position.x,
+                    y: node.position.y,
+                },
+                inputs: match definition {
+                    Some(definition) => visible_inputs(definition, &inputs, &node.properties),
+                    None => inputs,
+                },
+                outputs: node.outputs.iter().map(|&s| socket_id(s)).collect(),
+                properties: definition
+                    .map(|definition| property_descriptors(definition, &node.properties))
+                    .unwrap_or_default(),
+                diagnostics: self.compiled_diagnostics(DiagnosticTarget::Node(node_id)),
+            });
+        }
+
+        let edges = core_links
+            .iter()
+            .map(|link| EdgeDescriptor {
+                edge_id: edge_id(link.id),
+                from: socket_id(link.from),
+                to: socket_id(link.to),
+                diagnostics: self.compiled_diagnostics(DiagnosticTarget::Edge(edge_id(link.id))),
+            })
+            .collect();
+
+        self.nodes = nodes;
+        self.sockets = sockets;
+        self.edges = edges;
+    }
+}
+
+impl DocumentAdapter for GraphDocumentAdapter {
+    fn apply_command(&mut self, command: EditorCommand) -> Result<CommandOutcome, DocumentError> {
+        let label = command.label().to_string();
+        let mut outcome = CommandOutcome::default();
+        let inverse = match self.execute(command, &mut outcome) {
+            Ok(inverse) => inverse,
+            Err(error) => {
+                // Events of the reverted changes cancel out
+                self.events.clear();
+                return Err(error);
+            }
+        };
+        // An empty batch leaves the document as it was
+        if !inverse.is_empty() {
+            self.history.record(label, inverse);
+            self.commit();
+        }
+        outcome.revision = self.revision;
+        Ok(outcome)
+    }
+
+    fn undo(&mut self) -> bool {
+        let Some(mut entry) = self.history.pop_undo() else {
+            return false;
+        };
+        entry.changes = self
+            .apply_changes(std::mem::take(&mut entry.changes))
+            .expect("recorded changes apply to the state they were recorded in");
+        self.history.push_redo(entry);
+        self.commit();
+        true
+    }
+
+    fn redo(&mut self) -> bool {
+        let Some(mut entry) = self.history.pop_redo() else {
+            return false;
+        };
+        entry.changes = self
+            .apply_changes(std::mem::take(&mut entry.changes))
+            .expect("recorded changes apply to the state they were recorded in");
+        self.history.push_undo(entry);
+        self.commit();
+        true
+    }
+
+    fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    fn undo_label(&self) -> Option<&str> {
+        self.history.undo_label()
+    }
+
+    fn redo_label(&self) -> Option<&str> {
+        self.history.redo_label()
+    }
+
+    fn mark_saved(&mut self) {
+        self.history.mark_saved();
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.history.is_dirty()
+    }
+
+    fn document_revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn snapshot(&self) -> GraphSnapshot {
+        self.snapshot.clone()
+    }
+
+    fn refresh_diagnostics(&mut self) {
+        if self.compile() {
+            self.publish();
+        }
+    }
+
+    fn compile_stats(&self) -> Option<CompileStats> {
+        self.compile_stats.clone()
+    }
+
+    fn subscribe(&mut self, subscriber: DocumentSubscriber) -> SubscriptionId {
+        self.subscribers.subscribe(subscriber)
+    }
+
+    fn unsubscribe(&mut self, subscription: SubscriptionId) -> bool {
+        self.subscribers.unsubscribe(subscription)
+    }
+
+    fn nodes(&self) -> &[NodeDescriptor] {
+        &self.nodes
+    }
+
+    fn sockets(&self) -> &[SocketDescriptor] {
+        &self.sockets
+    }
+
+    fn edges(&self) -> &[EdgeDescriptor] {
+        &self.edges
+    }
+}
+
+// Targets whose diagnostics differ between two states, in a stable order
+fn diff_diagnostics(
+    before: &HashMap<DiagnosticTarget, Vec<Diagnostic>>,
+    after: &HashMap<DiagnosticTarget, Vec<Diagnostic>>,
+) -> Vec<DiagnosticTarget> {
+    let mut changed: Vec<DiagnosticTarget> = before
+        .keys()
+        .chain(after.keys())
+        .filter(|target| before.get(target) != after.get(target))
+        .copied()
+        .collect();
+    changed.sort_by_key(|target| match *target {
+        DiagnosticTarget::Node(id) => (0, id.0),
+        DiagnosticTarget::Socket(id) => (1, id.0),
+        DiagnosticTarget::Edge(id) => (2, id.0),
+    });
+    changed.dedup();
+    changed
+}
@@ -0,0 +1,118 @@
+// Undo/redo history of document edits
+// Each entry holds the changes that revert one edit; applying them yields
+// the changes that bring the edit back, which become the redo entry
+
+// Number of undo steps kept unless configured otherwise
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+pub(crate) struct HistoryEntry<C> {
+    // Identifies the document state right after the edit
+    id: u64,
+    label: String,
+    pub(crate) changes: Vec<C>,
+}
+
+pub(crate) struct History<C> {
+    undo: Vec<HistoryEntry<C>>,
+    redo: Vec<HistoryEntry<C>>,
+    limit: usize,
+    // State of the oldest entry dropped by the limit, 0 for the initial state
+    base: u64,
+    // State marked as saved
+    saved: u64,
+    next_id: u64,
+}
+
+impl<C> History<C> {
+    pub(crate) fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            limit: DEFAULT_HISTORY_LIMIT,
+            base: 0,
+            saved: 0,
+            next_id: 1,
+        }
+    }
+
+    // Record a new edit by the changes that revert it
+    // Discards every redo entry
+    pub(crate) fn record(&mut self, label: impl Into<String>, changes: Vec<C>) {
+        self.redo.clear();
+        let id = self.next_id;
+        self.next_id += 1;
+        self.undo.push(HistoryEntry {
+            id,
+            label: label.into(),
+            changes,
+        });
+        self.trim();
+    }
+
+    pub(crate) fn pop_undo(&mut self) -> Option<HistoryEntry<C>> {
+        self.undo.pop()
+    }
+
+    // Store an undone entry with the changes that redo it
+    pub(crate) fn push_redo(&mut self, entry: HistoryEntry<C>) {
+        self.redo.push(entry);
+    }
+
+    pub(crate) fn pop_redo(&mut self) -> Option<HistoryEntry<C>> {
+        self.redo.pop()
+    }
+
+    // Store a redone entry with the changes that undo it again
+    pub(crate) fn push_undo(&mut self, entry: HistoryEntry<C>) {
+        self.undo.push(entry);
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    // Label of the edit that undo would revert
+    pub(crate) fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(|entry| entry.label.as_str())
+    }
+
+    // Label of the edit that redo would reapply
+    pub(crate) fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|entry| entry.label.as_str())
+    }
+
+    pub(crate) fn limit(&self) -> usize {
+        self.limit
+    }
+
+    // Drop the oldest undo entries beyond the new limit
+    pub(crate) fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        self.trim();
+    }
+
+    pub(crate) fn mark_saved(&mut self) {
+        self.saved = self.state();
+    }
+
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.state() != self.saved
+    }
+
+    // Id of the current document state
+    fn state(&self) -> u64 {
+        self.undo.last().map_or(self.base, |entry| entry.id)
+    }
+
+    fn trim(&mut self) {
+        if self.undo.len() > self.limit {
+            let excess = self.undo.len() - self.limit;
+            self.base = self.undo[excess - 1].id;
+            self.undo.drain(..excess);
+        }
+    }
+}
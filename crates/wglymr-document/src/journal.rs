@@ -0,0 +1,393 @@
+// Command journal for reproducing editing sessions
+// Every command, undo, redo and diagnostics refresh applied to a document is
+// recorded with a timestamp and the revision it led to, and can be written
+// as JSON Lines and replayed against an empty document
+
+use std::io::{BufRead, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use wglymr_core::{CompileStats, NodeRegistry};
+
+use crate::adapter::DocumentAdapter;
+use crate::commands::{CommandOutcome, EditorCommand};
+use crate::descriptors::{EdgeDescriptor, NodeDescriptor, SocketDescriptor};
+use crate::error::DocumentError;
+use crate::events::{DocumentSubscriber, SubscriptionId};
+use crate::graph_adapter::GraphDocumentAdapter;
+use crate::history::DEFAULT_HISTORY_LIMIT;
+use crate::snapshot::GraphSnapshot;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JournalAction {
+    Command(EditorCommand),
+    Undo,
+    Redo,
+    RefreshDiagnostics,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    // Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    pub action: JournalAction,
+    // Document revision after the action
+    pub revision: u64,
+    // Message of the error a command failed with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum JournalError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Malformed journal line {line}: {source}")]
+    Format {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("Entry {index} failed on replay: {source}")]
+    CommandFailed {
+        index: usize,
+        #[source]
+        source: DocumentError,
+    },
+
+    #[error("Entry {index} was recorded as failing but succeeded on replay")]
+    UnexpectedSuccess { index: usize },
+
+    #[error("Entry {index} led to revision {found} instead of {expected}")]
+    RevisionMismatch {
+        index: usize,
+        expected: u64,
+        found: u64,
+    },
+
+    #[error("Replayed document does not match the expected snapshot")]
+    SnapshotMismatch,
+}
+
+// Milliseconds since the Unix epoch
+pub type JournalClock = Box<dyn FnMut() -> u64>;
+
+// Recorded actions in the order they were applied
+pub struct CommandJournal {
+    entries: Vec<JournalEntry>,
+    clock: JournalClock,
+}
+
+impl Default for CommandJournal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandJournal {
+    pub fn new() -> Self {
+        Self::with_clock(Box::new(system_time_ms))
+    }
+
+    // Journal taking timestamps from the given clock
+    // Use on targets without a system clock, or for reproducible output
+    pub fn with_clock(clock: JournalClock) -> Self {
+        Self {
+            entries: Vec::new(),
+            clock,
+        }
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn record(&mut self, action: JournalAction, revision: u64, error: Option<String>) {
+        let timestamp_ms = (self.clock)();
+        self.entries.push(JournalEntry {
+            timestamp_ms,
+            action,
+            revision,
+            error,
+        });
+    }
+
+    // Write one JSON object per entry and line
+    pub fn write_jsonl(&self, mut writer: impl Write) -> Result<(), JournalError> {
+        for (index, entry) in self.entries.iter().enumerate() {
+            serde_json::to_writer(&mut writer, entry).map_err(|source| JournalError::Format {
+                line: index + 1,
+                source,
+            })?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+fn system_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+// Read entries written by write_jsonl, skipping blank lines
+// Line numbers in errors start at 1
+pub fn read_journal(reader: impl BufRead) -> Result<Vec<JournalEntry>, JournalError> {
+    let mut entries = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line).map_err(|source| JournalError::Format {
+            line: index + 1,
+            source,
+        })?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+// Document settings that change how actions replay
+// Must match the settings of the recorded document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayOptions {
+    pub diagnostics_on_change: bool,
+    pub history_limit: usize,
+}
+
+impl Default for ReplayOptions {
+    // Settings of a new document
+    fn default() -> Self {
+        Self {
+            diagnostics_on_change: true,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+        }
+    }
+}
+
+impl ReplayOptions {
+    // Settings of the given document
+    pub fn of(document: &GraphDocumentAdapter) -> Self {
+        Self {
+            diagnostics_on_change: document.diagnostics_on_change(),
+            history_limit: document.history_limit(),
+        }
+    }
+}
+
+// Apply the entries to an empty document using the built-in node registry
+// and the settings of a new document
+pub fn replay_journal(entries: &[JournalEntry]) -> Result<GraphDocumentAdapter, ReplayError> {
+    replay_journal_with(
+        entries,
+        NodeRegistry::with_builtins(),
+        ReplayOptions::default(),
+    )
+}
+
+pub fn replay_journal_with_registry(
+    entries: &[JournalEntry],
+    registry: NodeRegistry,
+) -> Result<GraphDocumentAdapter, ReplayError> {
+    replay_journal_with(entries, registry, ReplayOptions::default())
+}
+
+// Apply the entries to an empty document, checking that every command
+// fails or succeeds as recorded and leads to the recorded revision
+pub fn replay_journal_with(
+    entries: &[JournalEntry],
+    registry: NodeRegistry,
+    options: ReplayOptions,
+) -> Result<GraphDocumentAdapter, ReplayError> {
+    let mut document = GraphDocumentAdapter::with_registry(registry);
+    document.set_diagnostics_on_change(options.diagnostics_on_change);
+    document.set_history_limit(options.history_limit);
+    for (index, entry) in entries.iter().enumerate() {
+        match &entry.action {
+            JournalAction::Command(command) => {
+                match (document.apply_command(command.clone()), &entry.error) {
+                    (Ok(_), Some(_)) => return Err(ReplayError::UnexpectedSuccess { index }),
+                    (Err(source), None) => {
+                        return Err(ReplayError::CommandFailed { index, source })
+                    }
+                    _ => {}
+                }
+            }
+            JournalAction::Undo => {
+                document.undo();
+            }
+            JournalAction::Redo => {
+                document.redo();
+            }
+            JournalAction::RefreshDiagnostics => document.refresh_diagnostics(),
+        }
+
+        let found = document.document_revision();
+        if found != entry.revision {
+            return Err(ReplayError::RevisionMismatch {
+                index,
+                expected: entry.revision,
+                found,
+            });
+        }
+    }
+    Ok(document)
+}
+
+// Replay the entries with the settings of a new document and check the
+// result against the recorded document
+pub fn verify_replay(
+    entries: &[JournalEntry],
+    expected: &GraphSnapshot,
+) -> Result<GraphDocumentAdapter, ReplayError> {
+    verify_replay_with(entries, expected, ReplayOptions::default())
+}
+
+pub fn verify_replay_with(
+    entries: &[JournalEntry],
+    expected: &GraphSnapshot,
+    options: ReplayOptions,
+) -> Result<GraphDocumentAdapter, ReplayError> {
+    let document = replay_journal_with(entries, NodeRegistry::with_builtins(), options)?;
+    let snapshot = document.snapshot();
+    if snapshot.revision() != expected.revision() || !snapshot.same_contents(expected) {
+        return Err(ReplayError::SnapshotMismatch);
+    }
+    Ok(document)
+}
+
+// Document that records everything applied to it in a journal
+pub struct JournaledDocument<A> {
+    document: A,
+    journal: CommandJournal,
+}
+
+impl<A: DocumentAdapter> JournaledDocument<A> {
+    pub fn new(document: A) -> Self {
+        Self::with_journal(document, CommandJournal::new())
+    }
+
+    pub fn with_journal(document: A, journal: CommandJournal) -> Self {
+        Self { document, journal }
+    }
+
+    pub fn document(&self) -> &A {
+        &self.document
+    }
+
+    pub fn journal(&self) -> &CommandJournal {
+        &self.journal
+    }
+
+    pub fn journal_mut(&mut self) -> &mut CommandJournal {
+        &mut self.journal
+    }
+
+    pub fn into_parts(self) -> (A, CommandJournal) {
+        (self.document, self.journal)
+    }
+}
+
+impl<A: DocumentAdapter> DocumentAdapter for JournaledDocument<A> {
+    fn apply_command(&mut self, command: EditorCommand) -> Result<CommandOutcome, DocumentError> {
+        let result = self.document.apply_command(command.clone());
+        let error = result.as_ref().err().map(ToString::to_string);
+        self.journal.record(
+            JournalAction::Command(command),
+            self.document.document_revision(),
+            error,
+        );
+        result
+    }
+
+    fn document_revision(&self) -> u64 {
+        self.document.document_revision()
+    }
+
+    fn undo(&mut self) -> bool {
+        let undone = self.document.undo();
+        self.journal
+            .record(JournalAction::Undo, self.document.document_revision(), None);
+        undone
+    }
+
+    fn redo(&mut self) -> bool {
+        let redone = self.document.redo();
+        self.journal
+            .record(JournalAction::Redo, self.document.document_revision(), None);
+        redone
+    }
+
+    fn can_undo(&self) -> bool {
+        self.document.can_undo()
+    }
+
+    fn can_redo(&self) -> bool {
+        self.document.can_redo()
+    }
+
+    fn undo_label(&self) -> Option<&str> {
+        self.document.undo_label()
+    }
+
+    fn redo_label(&self) -> Option<&str> {
+        self.document.redo_label()
+    }
+
+    fn mark_saved(&mut self) {
+        self.document.mark_saved();
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.document.is_dirty()
+    }
+
+    fn snapshot(&self) -> GraphSnapshot {
+        self.document.snapshot()
+    }
+
+    fn subscribe(&mut self, subscriber: DocumentSubscriber) -> SubscriptionId {
+        self.document.subscribe(subscriber)
+    }
+
+    fn unsubscribe(&mut self, subscription: SubscriptionId) -> bool {
+        self.document.unsubscribe(subscription)
+    }
+
+    fn refresh_diagnostics(&mut self) {
+        self.document.refresh_diagnostics();
+        self.journal.record(
+            JournalAction::RefreshDiagnostics,
+            self.document.document_revision(),
+            None,
+        );
+    }
+
+    fn compile_stats(&self) -> Option<CompileStats> {
+        self.document.compile_stats()
+    }
+
+    fn nodes(&self) -> &[NodeDescriptor] {
+        self.document.nodes()
+    }
+
+    fn sockets(&self) -> &[SocketDescriptor] {
+        self.document.sockets()
+    }
+
+    fn edges(&self) -> &[EdgeDescriptor] {
+        self.document.edges()
+    }
+}
@@ -2,12 +2,42 @@
 // Owns editable state, undo/redo, and command processing.
 
 pub mod adapter;
+pub mod clipboard;
 pub mod commands;
 pub mod descriptors;
+pub mod error;
+pub mod events;
+pub mod graph_adapter;
+pub mod history;
+pub mod journal;
+pub mod persistence;
 pub mod snapshot;
 pub mod test_adapter;
 
+#[cfg(test)]
+mod tests;
+
 pub use adapter::DocumentAdapter;
-pub use commands::{EdgeId, EditorCommand, LiteralValue, NodeId, NodePosition, SocketId};
-pub use descriptors::{EdgeDescriptor, NodeDescriptor, SocketDescriptor, SocketDirection};
-pub use snapshot::GraphSnapshot;
+pub use clipboard::{copy_selection, cut_command, ClipboardPayload};
+pub use commands::{
+    CommandOutcome, EdgeId, EditorCommand, LiteralValue, NodeId, NodePosition, PropertyValue,
+    SocketId,
+};
+pub use descriptors::{
+    Diagnostic, DiagnosticSeverity, EdgeDescriptor, NodeDescriptor, PropertyDescriptor,
+    SocketDescriptor, SocketDirection, SocketMetadata, SocketSubtype,
+};
+pub use error::DocumentError;
+pub use events::{DiagnosticTarget, DocumentEvent, DocumentSubscriber, EventBus, SubscriptionId};
+pub use graph_adapter::GraphDocumentAdapter;
+pub use history::DEFAULT_HISTORY_LIMIT;
+pub use journal::{
+    read_journal, replay_journal, replay_journal_with, replay_journal_with_registry, verify_replay,
+    verify_replay_with, CommandJournal, JournalAction, JournalClock, JournalEntry, JournalError,
+    JournaledDocument, ReplayError, ReplayOptions,
+};
+pub use persistence::{
+    load_document, load_document_with_registry, save_document, DocumentFileError, EditorState,
+    Frame, NodeDisplay, ViewCamera, DOCUMENT_FORMAT_VERSION,
+};
+pub use snapshot::{GraphSnapshot, SnapshotChanges};
@@ -0,0 +1,139 @@
+// Saving and loading documents
+// A document file stores the core graph, the document ids of its elements
+// and editor-only state as versioned JSON
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use wglymr_core::{Graph, NodeRegistry};
+
+use crate::commands::NodeId;
+use crate::graph_adapter::{GraphDocumentAdapter, SavedIds};
+
+// Version written by save_document
+// Files with a newer version are rejected
+pub const DOCUMENT_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum DocumentFileError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Malformed document file: {0}")]
+    Format(#[from] serde_json::Error),
+
+    #[error("Unsupported document format version {found} (newest supported is {supported})")]
+    UnsupportedVersion { found: u32, supported: u32 },
+
+    #[error("Document ids or references do not match the graph")]
+    Inconsistent,
+}
+
+// State that belongs to the editor rather than the graph
+// Node positions are part of the graph itself
+// Fields missing from older files take their default
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct EditorState {
+    #[serde(default)]
+    pub views: Vec<ViewCamera>,
+    // Nodes without an entry are expanded and active
+    #[serde(default)]
+    pub nodes: HashMap<NodeId, NodeDisplay>,
+    #[serde(default)]
+    pub frames: Vec<Frame>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewCamera {
+    pub view_id: String,
+    pub pan: [f32; 2],
+    pub zoom: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct NodeDisplay {
+    #[serde(default)]
+    pub collapsed: bool,
+    #[serde(default)]
+    pub muted: bool,
+}
+
+// Labelled box drawn behind a group of nodes, in document space
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    pub label: String,
+    pub position: [f32; 2],
+    pub size: [f32; 2],
+    #[serde(default)]
+    pub nodes: Vec<NodeId>,
+}
+
+#[derive(Serialize)]
+struct DocumentFileRef<'a> {
+    version: u32,
+    graph: &'a Graph,
+    ids: SavedIds,
+    editor: &'a EditorState,
+}
+
+#[derive(Deserialize)]
+struct DocumentFile {
+    graph: Graph,
+    ids: SavedIds,
+    #[serde(default)]
+    editor: EditorState,
+}
+
+// Read before the rest of the file so newer formats are reported as such
+#[derive(Deserialize)]
+struct FormatHeader {
+    version: u32,
+}
+
+// Write the document and editor state
+// Call mark_saved on the document once the write succeeded
+pub fn save_document(
+    document: &GraphDocumentAdapter,
+    editor: &EditorState,
+    writer: impl Write,
+) -> Result<(), DocumentFileError> {
+    let file = DocumentFileRef {
+        version: DOCUMENT_FORMAT_VERSION,
+        graph: document.graph(),
+        ids: document.saved_ids(),
+        editor,
+    };
+    serde_json::to_writer_pretty(writer, &file)?;
+    Ok(())
+}
+
+// Read a document using the built-in node registry
+pub fn load_document(
+    reader: impl Read,
+) -> Result<(GraphDocumentAdapter, EditorState), DocumentFileError> {
+    load_document_with_registry(reader, NodeRegistry::with_builtins())
+}
+
+// Read a document at a fresh revision with an empty undo history
+pub fn load_document_with_registry(
+    mut reader: impl Read,
+    registry: NodeRegistry,
+) -> Result<(GraphDocumentAdapter, EditorState), DocumentFileError> {
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents)?;
+
+    let header: FormatHeader = serde_json::from_str(&contents)?;
+    if header.version > DOCUMENT_FORMAT_VERSION {
+        return Err(DocumentFileError::UnsupportedVersion {
+            found: header.version,
+            supported: DOCUMENT_FORMAT_VERSION,
+        });
+    }
+
+    let file: DocumentFile = serde_json::from_str(&contents)?;
+    let document = GraphDocumentAdapter::from_saved(file.graph, file.ids, registry)
+        .ok_or(DocumentFileError::Inconsistent)?;
+    Ok((document, file.editor))
+}
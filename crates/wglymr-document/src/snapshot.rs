@@ -1,24 +1,43 @@
 // Immutable document snapshot
 // Provides type-safe, versioned access to document state
 
+use crate::commands::{EdgeId, NodeId, SocketId};
 use crate::descriptors::{EdgeDescriptor, NodeDescriptor, SocketDescriptor};
+use std::collections::HashMap;
+use std::hash::Hash;
 use std::sync::Arc;
 
 /// Immutable snapshot of document state at a specific revision
 ///
-/// This type enforces immutability and enables efficient cloning via Arc.
-/// Snapshots are created on each document read to ensure view layer
-/// sees consistent state even as document is mutated.
+/// Cloning is O(1). Each descriptor is stored behind its own Arc and
+/// stamped with the revision it last changed in, so consecutive snapshots
+/// of a document share every unchanged entry.
 #[derive(Clone, Debug)]
 pub struct GraphSnapshot {
     revision: u64,
-    nodes: Arc<[NodeDescriptor]>,
-    sockets: Arc<[SocketDescriptor]>,
-    edges: Arc<[EdgeDescriptor]>,
+    nodes: Arc<EntityTable<NodeId, NodeDescriptor>>,
+    sockets: Arc<EntityTable<SocketId, SocketDescriptor>>,
+    edges: Arc<EntityTable<EdgeId, EdgeDescriptor>>,
+}
+
+/// Ids added, changed or removed between two snapshots of one document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotChanges<K> {
+    pub added: Vec<K>,
+    pub changed: Vec<K>,
+    pub removed: Vec<K>,
+}
+
+impl<K> SnapshotChanges<K> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
 }
 
 impl GraphSnapshot {
     /// Create new snapshot from vectors (takes ownership)
+    ///
+    /// Every entry is stamped with the snapshot revision.
     pub fn new(
         revision: u64,
         nodes: Vec<NodeDescriptor>,
@@ -27,9 +46,9 @@ impl GraphSnapshot {
     ) -> Self {
         Self {
             revision,
-            nodes: nodes.into(),
-            sockets: sockets.into(),
-            edges: edges.into(),
+            nodes: Arc::new(EntityTable::new(revision, nodes)),
+            sockets: Arc::new(EntityTable::new(revision, sockets)),
+            edges: Arc::new(EntityTable::new(revision, edges)),
         }
     }
 
@@ -39,23 +58,33 @@ impl GraphSnapshot {
         nodes: &[NodeDescriptor],
         sockets: &[SocketDescriptor],
         edges: &[EdgeDescriptor],
+    ) -> Self {
+        Self::new(revision, nodes.to_vec(), sockets.to_vec(), edges.to_vec())
+    }
+
+    /// Create the snapshot following this one
+    ///
+    /// Descriptors equal to the entry with the same id in this snapshot are
+    /// shared and keep their stamp. Only new or changed descriptors are
+    /// copied, stamped with the new revision.
+    pub fn update(
+        &self,
+        revision: u64,
+        nodes: &[NodeDescriptor],
+        sockets: &[SocketDescriptor],
+        edges: &[EdgeDescriptor],
     ) -> Self {
         Self {
             revision,
-            nodes: nodes.to_vec().into(),
-            sockets: sockets.to_vec().into(),
-            edges: edges.to_vec().into(),
+            nodes: EntityTable::update(&self.nodes, revision, nodes),
+            sockets: EntityTable::update(&self.sockets, revision, sockets),
+            edges: EntityTable::update(&self.edges, revision, edges),
         }
     }
 
     /// Create empty snapshot
     pub fn empty() -> Self {
-        Self {
-            revision: 0,
-            nodes: Arc::new([]),
-            sockets: Arc::new([]),
-            edges: Arc::new([]),
-        }
+        Self::new(0, Vec::new(), Vec::new(), Vec::new())
     }
 
     /// Revision number - monotonically increasing
@@ -64,18 +93,67 @@ impl GraphSnapshot {
     }
 
     /// All nodes in snapshot
-    pub fn nodes(&self) -> &[NodeDescriptor] {
-        &self.nodes
+    pub fn nodes(&self) -> impl ExactSizeIterator<Item = &NodeDescriptor> {
+        self.nodes.iter()
     }
 
     /// All sockets in snapshot
-    pub fn sockets(&self) -> &[SocketDescriptor] {
-        &self.sockets
+    pub fn sockets(&self) -> impl ExactSizeIterator<Item = &SocketDescriptor> {
+        self.sockets.iter()
     }
 
     /// All edges in snapshot
-    pub fn edges(&self) -> &[EdgeDescriptor] {
-        &self.edges
+    pub fn edges(&self) -> impl ExactSizeIterator<Item = &EdgeDescriptor> {
+        self.edges.iter()
+    }
+
+    pub fn node(&self, node_id: NodeId) -> Option<&NodeDescriptor> {
+        self.nodes.get(node_id)
+    }
+
+    pub fn socket(&self, socket_id: SocketId) -> Option<&SocketDescriptor> {
+        self.sockets.get(socket_id)
+    }
+
+    pub fn edge(&self, edge_id: EdgeId) -> Option<&EdgeDescriptor> {
+        self.edges.get(edge_id)
+    }
+
+    /// Revision the node last changed in
+    pub fn node_revision(&self, node_id: NodeId) -> Option<u64> {
+        self.nodes.revision(node_id)
+    }
+
+    /// Revision the socket last changed in
+    pub fn socket_revision(&self, socket_id: SocketId) -> Option<u64> {
+        self.sockets.revision(socket_id)
+    }
+
+    /// Revision the edge last changed in
+    pub fn edge_revision(&self, edge_id: EdgeId) -> Option<u64> {
+        self.edges.revision(edge_id)
+    }
+
+    /// Whether both snapshots hold equal descriptors, ignoring revisions
+    pub fn same_contents(&self, other: &GraphSnapshot) -> bool {
+        self.nodes().eq(other.nodes())
+            && self.sockets().eq(other.sockets())
+            && self.edges().eq(other.edges())
+    }
+
+    /// Nodes that differ from an earlier snapshot of the same document
+    pub fn node_changes(&self, previous: &GraphSnapshot) -> SnapshotChanges<NodeId> {
+        self.nodes.changes(&previous.nodes)
+    }
+
+    /// Sockets that differ from an earlier snapshot of the same document
+    pub fn socket_changes(&self, previous: &GraphSnapshot) -> SnapshotChanges<SocketId> {
+        self.sockets.changes(&previous.sockets)
+    }
+
+    /// Edges that differ from an earlier snapshot of the same document
+    pub fn edge_changes(&self, previous: &GraphSnapshot) -> SnapshotChanges<EdgeId> {
+        self.edges.changes(&previous.edges)
     }
 }
 
@@ -84,3 +162,148 @@ impl Default for GraphSnapshot {
         Self::empty()
     }
 }
+
+// Descriptor addressed by a document id
+trait Entity: Clone + PartialEq {
+    type Id: Copy + Eq + Hash;
+
+    fn id(&self) -> Self::Id;
+}
+
+impl Entity for NodeDescriptor {
+    type Id = NodeId;
+
+    fn id(&self) -> NodeId {
+        self.node_id
+    }
+}
+
+impl Entity for SocketDescriptor {
+    type Id = SocketId;
+
+    fn id(&self) -> SocketId {
+        self.socket_id
+    }
+}
+
+impl Entity for EdgeDescriptor {
+    type Id = EdgeId;
+
+    fn id(&self) -> EdgeId {
+        self.edge_id
+    }
+}
+
+#[derive(Debug)]
+struct Stamped<T> {
+    revision: u64,
+    value: Arc<T>,
+}
+
+impl<T> Clone for Stamped<T> {
+    fn clone(&self) -> Self {
+        Self {
+            revision: self.revision,
+            value: self.value.clone(),
+        }
+    }
+}
+
+// Entries in document order with an index by id
+#[derive(Debug)]
+struct EntityTable<K, T> {
+    entries: Vec<Stamped<T>>,
+    index: HashMap<K, usize>,
+}
+
+impl<T: Entity> EntityTable<T::Id, T> {
+    fn new(revision: u64, values: Vec<T>) -> Self {
+        values
+            .into_iter()
+            .map(|value| Stamped {
+                revision,
+                value: Arc::new(value),
+            })
+            .collect()
+    }
+
+    // Returns the previous table itself when nothing changed
+    fn update(previous: &Arc<Self>, revision: u64, values: &[T]) -> Arc<Self> {
+        let mut unchanged = values.len() == previous.entries.len();
+        let table: Self = values
+            .iter()
+            .enumerate()
+            .map(|(position, value)| match previous.entry(value.id()) {
+                Some((index, entry)) if *entry.value == *value => {
+                    unchanged &= index == position;
+                    entry.clone()
+                }
+                _ => {
+                    unchanged = false;
+                    Stamped {
+                        revision,
+                        value: Arc::new(value.clone()),
+                    }
+                }
+            })
+            .collect();
+
+        if unchanged {
+            previous.clone()
+        } else {
+            Arc::new(table)
+        }
+    }
+
+    fn iter(&self) -> impl ExactSizeIterator<Item = &T> {
+        self.entries.iter().map(|entry| entry.value.as_ref())
+    }
+
+    fn entry(&self, id: T::Id) -> Option<(usize, &Stamped<T>)> {
+        let index = *self.index.get(&id)?;
+        Some((index, &self.entries[index]))
+    }
+
+    fn get(&self, id: T::Id) -> Option<&T> {
+        self.entry(id).map(|(_, entry)| entry.value.as_ref())
+    }
+
+    fn revision(&self, id: T::Id) -> Option<u64> {
+        self.entry(id).map(|(_, entry)| entry.revision)
+    }
+
+    fn changes(&self, previous: &Self) -> SnapshotChanges<T::Id> {
+        let mut changes = SnapshotChanges {
+            added: Vec::new(),
+            changed: Vec::new(),
+            removed: Vec::new(),
+        };
+        for entry in &self.entries {
+            let id = entry.value.id();
+            match previous.entry(id) {
+                None => changes.added.push(id),
+                Some((_, old)) if old.revision != entry.revision => changes.changed.push(id),
+                Some(_) => {}
+            }
+        }
+        changes.removed = previous
+            .entries
+            .iter()
+            .map(|entry| entry.value.id())
+            .filter(|&id| !self.index.contains_key(&id))
+            .collect();
+        changes
+    }
+}
+
+impl<T: Entity> FromIterator<Stamped<T>> for EntityTable<T::Id, T> {
+    fn from_iter<I: IntoIterator<Item = Stamped<T>>>(iter: I) -> Self {
+        let entries: Vec<_> = iter.into_iter().collect();
+        let index = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (entry.value.id(), index))
+            .collect();
+        Self { entries, index }
+    }
+}
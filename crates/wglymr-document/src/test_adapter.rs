@@ -13,12 +13,18 @@
 // document => render_model => hit_test => input => renderer
 
 use crate::adapter::DocumentAdapter;
-use crate::commands::{EdgeId, EditorCommand, NodeId, NodePosition, SocketId};
-use crate::descriptors::{EdgeDescriptor, NodeDescriptor, SocketDescriptor, SocketDirection};
+use crate::commands::{CommandOutcome, EdgeId, EditorCommand, NodeId, NodePosition, SocketId};
+use crate::descriptors::{
+    EdgeDescriptor, NodeDescriptor, SocketDescriptor, SocketDirection, SocketMetadata,
+};
+use crate::error::DocumentError;
+use crate::events::{DocumentSubscriber, EventBus, SubscriptionId};
 use crate::snapshot::GraphSnapshot;
 
 pub struct TestDocumentAdapter {
     revision: u64,
+    // Fixed data never changes, so nothing is ever published
+    subscribers: EventBus,
 }
 
 impl Default for TestDocumentAdapter {
@@ -29,7 +35,10 @@ impl Default for TestDocumentAdapter {
 
 impl TestDocumentAdapter {
     pub fn new() -> Self {
-        Self { revision: 0 }
+        Self {
+            revision: 0,
+            subscribers: EventBus::new(),
+        }
     }
 
     fn build_test_nodes(&self) -> Vec<NodeDescriptor> {
@@ -40,6 +49,7 @@ impl TestDocumentAdapter {
                 position: NodePosition { x: 50.0, y: 100.0 },
                 inputs: vec![],
                 outputs: vec![SocketId(1)],
+                properties: vec![],
                 diagnostics: vec![],
             },
             NodeDescriptor {
@@ -48,6 +58,7 @@ impl TestDocumentAdapter {
                 position: NodePosition { x: 300.0, y: 80.0 },
                 inputs: vec![SocketId(2)],
                 outputs: vec![SocketId(3)],
+                properties: vec![],
                 diagnostics: vec![],
             },
             NodeDescriptor {
@@ -56,6 +67,7 @@ impl TestDocumentAdapter {
                 position: NodePosition { x: 550.0, y: 120.0 },
                 inputs: vec![SocketId(4)],
                 outputs: vec![],
+                properties: vec![],
                 diagnostics: vec![],
             },
         ]
@@ -70,6 +82,7 @@ impl TestDocumentAdapter {
                 direction: SocketDirection::Output,
                 type_name: Some("Value".to_string()),
                 default_value: None,
+                metadata: SocketMetadata::default(),
                 connected_edges: vec![EdgeId(1)],
                 diagnostics: vec![],
             },
@@ -80,6 +93,7 @@ impl TestDocumentAdapter {
                 direction: SocketDirection::Input,
                 type_name: Some("Value".to_string()),
                 default_value: None,
+                metadata: SocketMetadata::default(),
                 connected_edges: vec![EdgeId(1)],
                 diagnostics: vec![],
             },
@@ -90,6 +104,7 @@ impl TestDocumentAdapter {
                 direction: SocketDirection::Output,
                 type_name: Some("Value".to_string()),
                 default_value: None,
+                metadata: SocketMetadata::default(),
                 connected_edges: vec![EdgeId(2)],
                 diagnostics: vec![],
             },
@@ -100,6 +115,7 @@ impl TestDocumentAdapter {
                 direction: SocketDirection::Input,
                 type_name: Some("Value".to_string()),
                 default_value: None,
+                metadata: SocketMetadata::default(),
                 connected_edges: vec![EdgeId(2)],
                 diagnostics: vec![],
             },
@@ -125,8 +141,12 @@ impl TestDocumentAdapter {
 }
 
 impl DocumentAdapter for TestDocumentAdapter {
-    fn apply_command(&mut self, _command: EditorCommand) {
+    fn apply_command(&mut self, _command: EditorCommand) -> Result<CommandOutcome, DocumentError> {
         self.revision += 1;
+        Ok(CommandOutcome {
+            revision: self.revision,
+            ..CommandOutcome::default()
+        })
     }
 
     fn document_revision(&self) -> u64 {
@@ -137,6 +157,14 @@ impl DocumentAdapter for TestDocumentAdapter {
         GraphSnapshot::from_slices(self.revision, self.nodes(), self.sockets(), self.edges())
     }
 
+    fn subscribe(&mut self, subscriber: DocumentSubscriber) -> SubscriptionId {
+        self.subscribers.subscribe(subscriber)
+    }
+
+    fn unsubscribe(&mut self, subscription: SubscriptionId) -> bool {
+        self.subscribers.unsubscribe(subscription)
+    }
+
     fn nodes(&self) -> &[NodeDescriptor] {
         thread_local! {
             static NODES: Vec<NodeDescriptor> = TestDocumentAdapter::new().build_test_nodes();
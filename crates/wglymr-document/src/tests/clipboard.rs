@@ -0,0 +1,196 @@
+use super::{create_node, create_node_at, input_of, output_of};
+use crate::{
+    copy_selection, cut_command, DocumentAdapter, EditorCommand, GraphDocumentAdapter,
+    LiteralValue, NodeId, NodePosition, PropertyValue, SocketId,
+};
+
+fn connect(adapter: &mut GraphDocumentAdapter, from: SocketId, to: SocketId) {
+    adapter
+        .apply_command(EditorCommand::CreateEdge {
+            from,
+            to,
+            edge_id: None,
+        })
+        .unwrap();
+}
+
+fn paste(
+    adapter: &mut GraphDocumentAdapter,
+    payload: crate::ClipboardPayload,
+    keep_upstream_edges: bool,
+) -> Vec<NodeId> {
+    adapter
+        .apply_command(EditorCommand::Paste {
+            payload,
+            position: NodePosition { x: 100.0, y: 50.0 },
+            keep_upstream_edges,
+        })
+        .unwrap()
+        .created_nodes
+}
+
+// value -> a -> b, with a custom default on b's second input
+fn chain(adapter: &mut GraphDocumentAdapter) -> (NodeId, NodeId, NodeId) {
+    let value = create_node(adapter, "value.float");
+    let a = create_node_at(adapter, "math.add", 10.0, 20.0);
+    let b = create_node_at(adapter, "math.math", 40.0, 30.0);
+    connect(
+        adapter,
+        output_of(adapter, value, 0),
+        input_of(adapter, a, 0),
+    );
+    connect(adapter, output_of(adapter, a, 0), input_of(adapter, b, 0));
+    adapter
+        .apply_command(EditorCommand::SetDefaultValue {
+            socket_id: input_of(adapter, b, 1),
+            value: LiteralValue::Float(4.0),
+        })
+        .unwrap();
+    adapter
+        .apply_command(EditorCommand::SetNodeProperty {
+            node_id: b,
+            name: "operation".to_string(),
+            value: PropertyValue::Enum("power".to_string()),
+        })
+        .unwrap();
+    (value, a, b)
+}
+
+#[test]
+fn test_copy_records_relative_positions_and_edges() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let (_, a, b) = chain(&mut adapter);
+
+    let payload = copy_selection(&adapter, &[a, b]);
+
+    assert_eq!(payload.nodes.len(), 2);
+    assert_eq!(payload.nodes[0].offset, NodePosition { x: 0.0, y: 0.0 });
+    assert_eq!(payload.nodes[1].offset, NodePosition { x: 30.0, y: 10.0 });
+    assert!(payload.nodes[1]
+        .defaults
+        .contains(&(1, LiteralValue::Float(4.0))));
+    assert_eq!(
+        payload.nodes[1].properties,
+        vec![(
+            "operation".to_string(),
+            PropertyValue::Enum("power".to_string())
+        )]
+    );
+    assert_eq!(payload.edges.len(), 1);
+    assert_eq!(payload.upstream_edges.len(), 1);
+}
+
+#[test]
+fn test_copy_of_empty_selection_is_empty() {
+    let mut adapter = GraphDocumentAdapter::new();
+    chain(&mut adapter);
+
+    assert!(copy_selection(&adapter, &[]).is_empty());
+    assert!(copy_selection(&adapter, &[NodeId(99)]).is_empty());
+}
+
+#[test]
+fn test_paste_creates_fresh_ids_and_internal_edges() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let (value, a, b) = chain(&mut adapter);
+    let payload = copy_selection(&adapter, &[a, b]);
+    let revision = adapter.document_revision();
+
+    let pasted = paste(&mut adapter, payload, false);
+
+    assert_eq!(adapter.document_revision(), revision + 1);
+    assert_eq!(pasted.len(), 2);
+    assert!(pasted.iter().all(|id| ![value, a, b].contains(id)));
+    assert_eq!(adapter.nodes().len(), 5);
+    assert_eq!(adapter.edges().len(), 3);
+
+    let new_b = adapter
+        .nodes()
+        .iter()
+        .find(|n| n.node_id == pasted[1])
+        .unwrap();
+    assert_eq!(new_b.position, NodePosition { x: 130.0, y: 60.0 });
+    assert_eq!(
+        new_b.properties[0].value,
+        PropertyValue::Enum("power".to_string())
+    );
+    let b_input = input_of(&adapter, pasted[1], 1);
+    let socket = adapter
+        .sockets()
+        .iter()
+        .find(|s| s.socket_id == b_input)
+        .unwrap();
+    assert_eq!(socket.default_value, Some(LiteralValue::Float(4.0)));
+
+    // The pasted edge joins the pasted nodes
+    let a_out = output_of(&adapter, pasted[0], 0);
+    let b_in = input_of(&adapter, pasted[1], 0);
+    assert!(adapter
+        .edges()
+        .iter()
+        .any(|e| e.from == a_out && e.to == b_in));
+}
+
+#[test]
+fn test_paste_keeps_upstream_edges_on_request() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let (value, a, _) = chain(&mut adapter);
+    let payload = copy_selection(&adapter, &[a]);
+
+    let pasted = paste(&mut adapter, payload, true);
+
+    let value_out = output_of(&adapter, value, 0);
+    let new_input = input_of(&adapter, pasted[0], 0);
+    assert!(adapter
+        .edges()
+        .iter()
+        .any(|e| e.from == value_out && e.to == new_input));
+}
+
+#[test]
+fn test_paste_skips_upstream_edges_to_missing_sockets() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let (value, a, _) = chain(&mut adapter);
+    let payload = copy_selection(&adapter, &[a]);
+    adapter
+        .apply_command(EditorCommand::DeleteNode { node_id: value })
+        .unwrap();
+    let edges = adapter.edges().len();
+
+    paste(&mut adapter, payload, true);
+
+    assert_eq!(adapter.edges().len(), edges);
+}
+
+#[test]
+fn test_paste_undoes_as_one_step() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let (_, a, b) = chain(&mut adapter);
+    let nodes = adapter.nodes().to_vec();
+    let edges = adapter.edges().to_vec();
+    let payload = copy_selection(&adapter, &[a, b]);
+
+    paste(&mut adapter, payload, true);
+    assert_eq!(adapter.undo_label(), Some("Paste"));
+    assert!(adapter.undo());
+
+    assert_eq!(adapter.nodes(), nodes.as_slice());
+    assert_eq!(adapter.edges(), edges.as_slice());
+}
+
+#[test]
+fn test_cut_then_paste_restores_selection() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let (_, a, b) = chain(&mut adapter);
+    let payload = copy_selection(&adapter, &[a, b]);
+
+    adapter.apply_command(cut_command(&[b, a, b])).unwrap();
+    assert_eq!(adapter.nodes().len(), 1);
+    assert!(adapter.edges().is_empty());
+    assert_eq!(adapter.undo_label(), Some("Cut"));
+
+    let pasted = paste(&mut adapter, payload, true);
+    assert_eq!(pasted.len(), 2);
+    assert_eq!(adapter.nodes().len(), 3);
+    assert_eq!(adapter.edges().len(), 2);
+}
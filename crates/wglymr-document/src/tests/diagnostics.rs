@@ -0,0 +1,122 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{create_node, input_of, output_of};
+use crate::{
+    Diagnostic, DiagnosticSeverity, DiagnosticTarget, DocumentAdapter, DocumentEvent,
+    EditorCommand, GraphDocumentAdapter,
+};
+
+#[test]
+fn test_compile_errors_attach_to_sockets() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let reroute = create_node(&mut adapter, "utility.reroute");
+
+    let snapshot = adapter.snapshot();
+    let socket = snapshot.socket(input_of(&adapter, reroute, 0)).unwrap();
+    assert_eq!(socket.diagnostics.len(), 1);
+    assert_eq!(socket.diagnostics[0].severity, DiagnosticSeverity::Error);
+    assert!(socket.diagnostics[0].message.contains("Required input"));
+    assert!(snapshot.node(reroute).unwrap().diagnostics.is_empty());
+    assert!(adapter.compile_stats().is_none());
+}
+
+#[test]
+fn test_fixing_the_graph_clears_diagnostics() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let reroute = create_node(&mut adapter, "utility.reroute");
+    let value = create_node(&mut adapter, "value.float");
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let sink = received.clone();
+    adapter.subscribe(Box::new(move |_, events| {
+        sink.borrow_mut().extend(events.iter().cloned());
+    }));
+
+    adapter
+        .apply_command(EditorCommand::CreateEdge {
+            from: output_of(&adapter, value, 0),
+            to: input_of(&adapter, reroute, 0),
+            edge_id: None,
+        })
+        .unwrap();
+
+    assert!(adapter.sockets().iter().all(|s| s.diagnostics.is_empty()));
+    assert!(adapter.compile_stats().is_some());
+    assert!(received
+        .borrow()
+        .contains(&DocumentEvent::DiagnosticsChanged {
+            targets: vec![DiagnosticTarget::Socket(input_of(&adapter, reroute, 0))],
+        }));
+}
+
+#[test]
+fn test_each_broken_branch_reports_its_errors() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let first = create_node(&mut adapter, "utility.reroute");
+    let second = create_node(&mut adapter, "utility.reroute");
+
+    let snapshot = adapter.snapshot();
+    for node_id in [first, second] {
+        let socket = snapshot.socket(input_of(&adapter, node_id, 0)).unwrap();
+        assert_eq!(socket.diagnostics.len(), 1);
+    }
+}
+
+#[test]
+fn test_successful_compile_attaches_warnings() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let value = create_node(&mut adapter, "value.float");
+    let divide = create_node(&mut adapter, "math.divide");
+    adapter
+        .apply_command(EditorCommand::CreateEdge {
+            from: output_of(&adapter, value, 0),
+            to: input_of(&adapter, divide, 1),
+            edge_id: None,
+        })
+        .unwrap();
+
+    // The value defaults to zero
+    assert!(adapter.compile_stats().is_some());
+    let snapshot = adapter.snapshot();
+    let warnings = &snapshot.node(divide).unwrap().diagnostics;
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].severity, DiagnosticSeverity::Warning);
+    assert_eq!(warnings[0].message, "Division by zero");
+    let inputs = &snapshot.node(divide).unwrap().inputs;
+    assert!(inputs
+        .iter()
+        .all(|&input| snapshot.socket(input).unwrap().diagnostics.is_empty()));
+}
+
+#[test]
+fn test_diagnostics_on_demand() {
+    let mut adapter = GraphDocumentAdapter::new();
+    adapter.set_diagnostics_on_change(false);
+    let reroute = create_node(&mut adapter, "utility.reroute");
+    let socket_id = input_of(&adapter, reroute, 0);
+    assert!(adapter
+        .snapshot()
+        .socket(socket_id)
+        .unwrap()
+        .diagnostics
+        .is_empty());
+
+    adapter.refresh_diagnostics();
+    let snapshot = adapter.snapshot();
+    assert_eq!(snapshot.revision(), 2);
+    assert_eq!(snapshot.socket(socket_id).unwrap().diagnostics.len(), 1);
+    assert_eq!(snapshot.socket_revision(socket_id), Some(2));
+
+    // Nothing changed, so no new revision
+    adapter.refresh_diagnostics();
+    assert_eq!(adapter.document_revision(), 2);
+}
+
+#[test]
+fn test_core_severities_map_to_document_severities() {
+    let warning = wglymr_core::warning_default_value_used(wglymr_core::SocketId(1));
+    let diagnostic = Diagnostic::from(&warning);
+
+    assert_eq!(diagnostic.severity, DiagnosticSeverity::Warning);
+    assert_eq!(diagnostic.message, warning.message);
+}
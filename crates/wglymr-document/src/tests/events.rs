@@ -0,0 +1,205 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{create_node, input_of, output_of};
+use crate::{
+    copy_selection, DocumentAdapter, DocumentEvent, EditorCommand, EventBus, GraphDocumentAdapter,
+    LiteralValue, NodeId, NodePosition, PropertyValue,
+};
+
+type Received = Rc<RefCell<Vec<(u64, Vec<DocumentEvent>)>>>;
+
+fn record(adapter: &mut dyn DocumentAdapter) -> Received {
+    let received: Received = Rc::default();
+    let sink = received.clone();
+    adapter.subscribe(Box::new(move |revision, events| {
+        sink.borrow_mut().push((revision, events.to_vec()));
+    }));
+    received
+}
+
+#[test]
+fn test_event_bus_delivers_to_subscribers_until_removed() {
+    let mut bus = EventBus::new();
+    let count = Rc::new(RefCell::new(0));
+    let sink = count.clone();
+    let subscription = bus.subscribe(Box::new(move |_, events| {
+        *sink.borrow_mut() += events.len();
+    }));
+    let event = DocumentEvent::NodeAdded { node_id: NodeId(1) };
+
+    bus.publish(1, std::slice::from_ref(&event));
+    bus.publish(2, &[]);
+    assert_eq!(*count.borrow(), 1);
+
+    assert!(bus.unsubscribe(subscription));
+    assert!(!bus.unsubscribe(subscription));
+    bus.publish(3, &[event]);
+    assert_eq!(*count.borrow(), 1);
+}
+
+#[test]
+fn test_commands_publish_events_with_revision() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let received = record(&mut adapter);
+
+    let node_id = create_node(&mut adapter, "math.math");
+    adapter
+        .apply_command(EditorCommand::MoveNode {
+            node_id,
+            new_position: NodePosition { x: 3.0, y: 4.0 },
+        })
+        .unwrap();
+    let socket_id = input_of(&adapter, node_id, 1);
+    adapter
+        .apply_command(EditorCommand::SetDefaultValue {
+            socket_id,
+            value: LiteralValue::Float(2.5),
+        })
+        .unwrap();
+    adapter
+        .apply_command(EditorCommand::SetNodeProperty {
+            node_id,
+            name: "operation".to_string(),
+            value: PropertyValue::Enum("power".to_string()),
+        })
+        .unwrap();
+
+    assert_eq!(
+        *received.borrow(),
+        vec![
+            (1, vec![DocumentEvent::NodeAdded { node_id }]),
+            (
+                2,
+                vec![DocumentEvent::NodeMoved {
+                    node_id,
+                    position: NodePosition { x: 3.0, y: 4.0 },
+                }]
+            ),
+            (
+                3,
+                vec![DocumentEvent::DefaultChanged {
+                    socket_id,
+                    value: LiteralValue::Float(2.5),
+                }]
+            ),
+            (
+                4,
+                vec![DocumentEvent::PropertyChanged {
+                    node_id,
+                    name: "operation".to_string(),
+                    value: PropertyValue::Enum("power".to_string()),
+                }]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_delete_node_publishes_edge_removals_first() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let value = create_node(&mut adapter, "value.float");
+    let add = create_node(&mut adapter, "math.add");
+    let from = output_of(&adapter, value, 0);
+    let to = input_of(&adapter, add, 0);
+    let edge_id = adapter
+        .apply_command(EditorCommand::CreateEdge {
+            from,
+            to,
+            edge_id: None,
+        })
+        .unwrap()
+        .created_edges[0];
+    let received = record(&mut adapter);
+
+    adapter
+        .apply_command(EditorCommand::DeleteNode { node_id: add })
+        .unwrap();
+    adapter.undo();
+
+    let received = received.borrow();
+    assert_eq!(
+        received[0].1,
+        vec![
+            DocumentEvent::EdgeRemoved { edge_id },
+            DocumentEvent::NodeRemoved { node_id: add },
+        ]
+    );
+    assert_eq!(
+        received[1].1,
+        vec![
+            DocumentEvent::NodeAdded { node_id: add },
+            DocumentEvent::EdgeAdded { edge_id, from, to },
+        ]
+    );
+}
+
+#[test]
+fn test_failed_command_publishes_nothing() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let node_id = create_node(&mut adapter, "math.add");
+    let received = record(&mut adapter);
+
+    let result = adapter.apply_command(EditorCommand::Batch {
+        label: "Move And Fail".to_string(),
+        commands: vec![
+            EditorCommand::MoveNode {
+                node_id,
+                new_position: NodePosition { x: 1.0, y: 1.0 },
+            },
+            EditorCommand::DeleteNode {
+                node_id: NodeId(99),
+            },
+        ],
+    });
+    assert!(result.is_err());
+    assert!(received.borrow().is_empty());
+
+    adapter
+        .apply_command(EditorCommand::MoveNode {
+            node_id,
+            new_position: NodePosition { x: 2.0, y: 2.0 },
+        })
+        .unwrap();
+    assert_eq!(received.borrow()[0].1.len(), 1);
+}
+
+#[test]
+fn test_batch_publishes_events_together() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let node_id = create_node(&mut adapter, "math.math");
+    let payload = copy_selection(&adapter, &[node_id]);
+    let received = record(&mut adapter);
+
+    adapter
+        .apply_command(EditorCommand::Paste {
+            payload,
+            position: NodePosition { x: 0.0, y: 0.0 },
+            keep_upstream_edges: false,
+        })
+        .unwrap();
+
+    let received = received.borrow();
+    assert_eq!(received.len(), 1);
+    assert!(matches!(received[0].1[0], DocumentEvent::NodeAdded { .. }));
+    assert!(received[0]
+        .1
+        .iter()
+        .any(|event| matches!(event, DocumentEvent::PropertyChanged { .. })));
+}
+
+#[test]
+fn test_unsubscribed_adapter_stops_publishing() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let received: Received = Rc::default();
+    let sink = received.clone();
+    let subscription = adapter.subscribe(Box::new(move |revision, events| {
+        sink.borrow_mut().push((revision, events.to_vec()));
+    }));
+
+    create_node(&mut adapter, "math.add");
+    assert!(adapter.unsubscribe(subscription));
+    create_node(&mut adapter, "math.add");
+
+    assert_eq!(received.borrow().len(), 1);
+}
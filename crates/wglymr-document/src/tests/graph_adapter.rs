@@ -0,0 +1,715 @@
+use wglymr_core::PropertyError;
+
+use super::{create_node, input_of, output_of};
+use crate::{
+    DocumentAdapter, DocumentError, EdgeId, EditorCommand, GraphDocumentAdapter, LiteralValue,
+    NodeId, NodePosition, PropertyValue, SocketDirection, SocketId,
+};
+
+#[test]
+fn test_create_node_adds_core_node_and_descriptors() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let node_id = create_node(&mut adapter, "math.add");
+
+    assert_eq!(adapter.document_revision(), 1);
+    let core_id = adapter.core_node_id(node_id).unwrap();
+    assert!(adapter.graph().node(core_id).is_some());
+    assert_eq!(adapter.document_node_id(core_id), Some(node_id));
+
+    let node = &adapter.nodes()[0];
+    assert_eq!(node.node_kind, "math.add");
+    assert_eq!(node.inputs.len(), 2);
+    assert_eq!(node.outputs.len(), 1);
+
+    let input = &adapter.sockets()[0];
+    assert_eq!(input.direction, SocketDirection::Input);
+    assert_eq!(input.name, "A");
+    assert_eq!(input.default_value, Some(LiteralValue::Float(0.0)));
+}
+
+#[test]
+fn test_unknown_node_kind_leaves_document_unchanged() {
+    let mut adapter = GraphDocumentAdapter::new();
+    create_node(&mut adapter, "math.add");
+
+    let result = adapter.apply_command(EditorCommand::CreateNode {
+        node_kind: "does.not.exist".to_string(),
+        position: NodePosition { x: 0.0, y: 0.0 },
+        node_id: None,
+    });
+
+    assert!(
+        matches!(result, Err(DocumentError::UnknownNodeKind(kind)) if kind == "does.not.exist")
+    );
+
+    assert_eq!(adapter.document_revision(), 1);
+    assert_eq!(adapter.nodes().len(), 1);
+}
+
+#[test]
+fn test_create_node_with_taken_id_is_rejected() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let node_id = create_node(&mut adapter, "math.add");
+
+    let result = adapter.apply_command(EditorCommand::CreateNode {
+        node_kind: "math.add".to_string(),
+        position: NodePosition { x: 0.0, y: 0.0 },
+        node_id: Some(node_id),
+    });
+
+    assert!(matches!(result, Err(DocumentError::NodeIdInUse { node_id: id }) if id == node_id));
+
+    assert_eq!(adapter.nodes().len(), 1);
+    assert_eq!(adapter.graph().nodes().count(), 1);
+}
+
+#[test]
+fn test_largest_ids_are_rejected() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let value = create_node(&mut adapter, "value.float");
+    let add = create_node(&mut adapter, "math.add");
+
+    let result = adapter.apply_command(EditorCommand::CreateNode {
+        node_kind: "math.add".to_string(),
+        position: NodePosition { x: 0.0, y: 0.0 },
+        node_id: Some(NodeId(u64::MAX)),
+    });
+    assert!(matches!(
+        result,
+        Err(DocumentError::NodeIdReserved {
+            node_id: NodeId(u64::MAX)
+        })
+    ));
+
+    let result = adapter.apply_command(EditorCommand::CreateEdge {
+        from: output_of(&adapter, value, 0),
+        to: input_of(&adapter, add, 0),
+        edge_id: Some(EdgeId(u64::MAX)),
+    });
+    assert!(matches!(
+        result,
+        Err(DocumentError::EdgeIdReserved {
+            edge_id: EdgeId(u64::MAX)
+        })
+    ));
+
+    // The counter is unaffected
+    let next = create_node(&mut adapter, "math.add");
+    assert_eq!(next, NodeId(add.0 + 1));
+}
+
+#[test]
+fn test_move_node_updates_core_position() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let node_id = create_node(&mut adapter, "math.add");
+
+    adapter
+        .apply_command(EditorCommand::MoveNode {
+            node_id,
+            new_position: NodePosition { x: 10.0, y: -4.0 },
+        })
+        .unwrap();
+
+    let core_id = adapter.core_node_id(node_id).unwrap();
+    assert_eq!(adapter.graph().node(core_id).unwrap().position.x, 10.0);
+    assert_eq!(
+        adapter.nodes()[0].position,
+        NodePosition { x: 10.0, y: -4.0 }
+    );
+}
+
+#[test]
+fn test_create_and_delete_edge() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let value = create_node(&mut adapter, "value.float");
+    let add = create_node(&mut adapter, "math.add");
+    let from = output_of(&adapter, value, 0);
+    let to = input_of(&adapter, add, 0);
+
+    adapter
+        .apply_command(EditorCommand::CreateEdge {
+            from,
+            to,
+            edge_id: None,
+        })
+        .unwrap();
+
+    assert_eq!(adapter.graph().links().count(), 1);
+    let edge = adapter.edges()[0].clone();
+    assert_eq!((edge.from, edge.to), (from, to));
+    let socket = adapter
+        .sockets()
+        .iter()
+        .find(|s| s.socket_id == to)
+        .unwrap();
+    assert_eq!(socket.connected_edges, vec![edge.edge_id]);
+
+    adapter
+        .apply_command(EditorCommand::DeleteEdge {
+            edge_id: edge.edge_id,
+        })
+        .unwrap();
+
+    assert_eq!(adapter.graph().links().count(), 0);
+    assert!(adapter.edges().is_empty());
+    assert!(adapter.core_link_id(edge.edge_id).is_none());
+}
+
+#[test]
+fn test_delete_node_removes_its_sockets_and_edges() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let value = create_node(&mut adapter, "value.float");
+    let add = create_node(&mut adapter, "math.add");
+    let from = output_of(&adapter, value, 0);
+    let to = input_of(&adapter, add, 0);
+    adapter
+        .apply_command(EditorCommand::CreateEdge {
+            from,
+            to,
+            edge_id: None,
+        })
+        .unwrap();
+    let edge_id = adapter.edges()[0].edge_id;
+
+    adapter
+        .apply_command(EditorCommand::DeleteNode { node_id: value })
+        .unwrap();
+
+    assert_eq!(adapter.nodes().len(), 1);
+    assert!(adapter.edges().is_empty());
+    assert!(adapter.core_node_id(value).is_none());
+    assert!(adapter.core_socket_id(from).is_none());
+    assert!(adapter.core_link_id(edge_id).is_none());
+    assert!(adapter.sockets().iter().all(|s| s.node_id == add));
+}
+
+#[test]
+fn test_set_and_clear_default_value() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let add = create_node(&mut adapter, "math.multiply");
+    let socket_id = input_of(&adapter, add, 1);
+    let default_of = |adapter: &GraphDocumentAdapter| {
+        let socket = adapter.sockets().iter().find(|s| s.socket_id == socket_id);
+        socket.unwrap().default_value.clone()
+    };
+
+    adapter
+        .apply_command(EditorCommand::SetDefaultValue {
+            socket_id,
+            value: LiteralValue::Float(3.5),
+        })
+        .unwrap();
+    assert_eq!(default_of(&adapter), Some(LiteralValue::Float(3.5)));
+
+    adapter
+        .apply_command(EditorCommand::ClearDefaultValue { socket_id })
+        .unwrap();
+    assert_eq!(default_of(&adapter), Some(LiteralValue::Float(1.0)));
+    assert_eq!(adapter.document_revision(), 3);
+}
+
+#[test]
+fn test_set_node_property_hides_inputs() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let node_id = create_node(&mut adapter, "math.math");
+    assert_eq!(adapter.nodes()[0].inputs.len(), 2);
+
+    adapter
+        .apply_command(EditorCommand::SetNodeProperty {
+            node_id,
+            name: "operation".to_string(),
+            value: PropertyValue::Enum("sqrt".to_string()),
+        })
+        .unwrap();
+
+    let node = &adapter.nodes()[0];
+    assert_eq!(node.inputs.len(), 1);
+    assert_eq!(
+        node.properties[0].value,
+        PropertyValue::Enum("sqrt".to_string())
+    );
+
+    let result = adapter.apply_command(EditorCommand::SetNodeProperty {
+        node_id,
+        name: "operation".to_string(),
+        value: PropertyValue::Enum("modulo".to_string()),
+    });
+    assert!(matches!(
+        result,
+        Err(DocumentError::Property {
+            source: PropertyError::UnknownOption { .. },
+            ..
+        })
+    ));
+    assert_eq!(adapter.document_revision(), 2);
+}
+
+#[test]
+fn test_snapshot_reflects_graph() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let node_id = create_node(&mut adapter, "math.add");
+
+    let snapshot = adapter.snapshot();
+
+    assert_eq!(snapshot.revision(), 1);
+    assert_eq!(snapshot.nodes().next().unwrap().node_id, node_id);
+    assert_eq!(snapshot.sockets().len(), 3);
+}
+
+#[test]
+fn test_undo_and_redo_create_node() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let node_id = create_node(&mut adapter, "math.add");
+    let sockets: Vec<SocketId> = adapter.sockets().iter().map(|s| s.socket_id).collect();
+
+    assert!(adapter.undo());
+    assert!(adapter.nodes().is_empty());
+    assert_eq!(adapter.graph().nodes().count(), 0);
+    assert_eq!(adapter.document_revision(), 2);
+    assert!(!adapter.can_undo());
+
+    assert!(adapter.redo());
+    assert_eq!(adapter.nodes()[0].node_id, node_id);
+    let restored: Vec<SocketId> = adapter.sockets().iter().map(|s| s.socket_id).collect();
+    assert_eq!(restored, sockets);
+    assert_eq!(adapter.document_revision(), 3);
+    assert!(!adapter.can_redo());
+}
+
+#[test]
+fn test_undo_delete_node_restores_sockets_edges_and_defaults() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let value = create_node(&mut adapter, "value.float");
+    let math = create_node(&mut adapter, "math.math");
+    let from = output_of(&adapter, value, 0);
+    let to = input_of(&adapter, math, 0);
+    let b = input_of(&adapter, math, 1);
+    adapter
+        .apply_command(EditorCommand::CreateEdge {
+            from,
+            to,
+            edge_id: None,
+        })
+        .unwrap();
+    adapter
+        .apply_command(EditorCommand::SetDefaultValue {
+            socket_id: b,
+            value: LiteralValue::Float(7.0),
+        })
+        .unwrap();
+    adapter
+        .apply_command(EditorCommand::SetNodeProperty {
+            node_id: math,
+            name: "operation".to_string(),
+            value: PropertyValue::Enum("power".to_string()),
+        })
+        .unwrap();
+    let nodes = adapter.nodes().to_vec();
+    let sockets = adapter.sockets().to_vec();
+    let edges = adapter.edges().to_vec();
+
+    adapter
+        .apply_command(EditorCommand::DeleteNode { node_id: math })
+        .unwrap();
+    assert_eq!(adapter.nodes().len(), 1);
+    assert!(adapter.edges().is_empty());
+
+    assert!(adapter.undo());
+    assert_eq!(adapter.nodes(), nodes.as_slice());
+    assert_eq!(adapter.sockets(), sockets.as_slice());
+    assert_eq!(adapter.edges(), edges.as_slice());
+    assert_eq!(adapter.graph().links().count(), 1);
+
+    assert!(adapter.redo());
+    assert_eq!(adapter.nodes().len(), 1);
+    assert!(adapter.edges().is_empty());
+}
+
+#[test]
+fn test_undo_reverts_moves_defaults_and_properties() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let node_id = create_node(&mut adapter, "math.math");
+    let b = input_of(&adapter, node_id, 1);
+    let initial = adapter.nodes().to_vec();
+    let initial_sockets = adapter.sockets().to_vec();
+
+    adapter
+        .apply_command(EditorCommand::MoveNode {
+            node_id,
+            new_position: NodePosition { x: 5.0, y: 6.0 },
+        })
+        .unwrap();
+    adapter
+        .apply_command(EditorCommand::SetDefaultValue {
+            socket_id: b,
+            value: LiteralValue::Float(2.0),
+        })
+        .unwrap();
+    adapter
+        .apply_command(EditorCommand::SetNodeProperty {
+            node_id,
+            name: "operation".to_string(),
+            value: PropertyValue::Enum("sqrt".to_string()),
+        })
+        .unwrap();
+
+    for _ in 0..3 {
+        assert!(adapter.undo());
+    }
+
+    assert_eq!(adapter.nodes(), initial.as_slice());
+    assert_eq!(adapter.sockets(), initial_sockets.as_slice());
+    let core_id = adapter.core_node_id(node_id).unwrap();
+    assert!(adapter.graph().node(core_id).unwrap().properties.is_empty());
+}
+
+#[test]
+fn test_hiding_an_input_removes_its_edge_in_one_undo_step() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let value = create_node(&mut adapter, "value.float");
+    let math = create_node(&mut adapter, "math.math");
+    let b = input_of(&adapter, math, 1);
+    adapter
+        .apply_command(EditorCommand::CreateEdge {
+            from: output_of(&adapter, value, 0),
+            to: b,
+            edge_id: None,
+        })
+        .unwrap();
+    let linked = adapter.edges().to_vec();
+
+    adapter
+        .apply_command(EditorCommand::SetNodeProperty {
+            node_id: math,
+            name: "operation".to_string(),
+            value: PropertyValue::Enum("sqrt".to_string()),
+        })
+        .unwrap();
+    assert!(adapter.edges().is_empty());
+    assert!(adapter.graph().links().next().is_none());
+
+    assert!(adapter.undo());
+    assert_eq!(adapter.edges(), linked.as_slice());
+    assert_eq!(input_of(&adapter, math, 1), b);
+
+    assert!(adapter.redo());
+    assert!(adapter.edges().is_empty());
+}
+
+#[test]
+fn test_failed_command_is_not_recorded() {
+    let mut adapter = GraphDocumentAdapter::new();
+    create_node(&mut adapter, "math.add");
+
+    let result = adapter.apply_command(EditorCommand::DeleteNode {
+        node_id: NodeId(99),
+    });
+
+    assert!(matches!(
+        result,
+        Err(DocumentError::NodeNotFound {
+            node_id: NodeId(99)
+        })
+    ));
+
+    assert!(adapter.undo());
+    assert!(!adapter.can_undo());
+}
+
+#[test]
+fn test_new_command_clears_redo() {
+    let mut adapter = GraphDocumentAdapter::new();
+    create_node(&mut adapter, "math.add");
+    adapter.undo();
+    assert!(adapter.can_redo());
+
+    create_node(&mut adapter, "math.subtract");
+
+    assert!(!adapter.can_redo());
+    assert!(!adapter.redo());
+}
+
+#[test]
+fn test_dirty_tracking_across_undo() {
+    let mut adapter = GraphDocumentAdapter::new();
+    assert!(!adapter.is_dirty());
+
+    create_node(&mut adapter, "math.add");
+    assert!(adapter.is_dirty());
+    adapter.mark_saved();
+    assert!(!adapter.is_dirty());
+
+    create_node(&mut adapter, "math.add");
+    assert!(adapter.is_dirty());
+    adapter.undo();
+    assert!(!adapter.is_dirty());
+}
+
+#[test]
+fn test_history_limit_bounds_undo() {
+    let mut adapter = GraphDocumentAdapter::new();
+    adapter.set_history_limit(2);
+    for _ in 0..4 {
+        create_node(&mut adapter, "math.add");
+    }
+
+    assert!(adapter.undo());
+    assert!(adapter.undo());
+    assert!(!adapter.undo());
+    assert_eq!(adapter.nodes().len(), 2);
+}
+
+#[test]
+fn test_redo_keeps_pinned_ids_for_later_edges() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let value = create_node(&mut adapter, "value.float");
+    let add = create_node(&mut adapter, "math.add");
+    let from = output_of(&adapter, value, 0);
+    let to = input_of(&adapter, add, 0);
+    adapter
+        .apply_command(EditorCommand::CreateEdge {
+            from,
+            to,
+            edge_id: None,
+        })
+        .unwrap();
+    let edges = adapter.edges().to_vec();
+
+    for _ in 0..3 {
+        adapter.undo();
+    }
+    assert!(adapter.nodes().is_empty());
+    for _ in 0..3 {
+        assert!(adapter.redo());
+    }
+
+    assert_eq!(adapter.edges(), edges.as_slice());
+}
+
+fn batch(label: &str, commands: Vec<EditorCommand>) -> EditorCommand {
+    EditorCommand::Batch {
+        label: label.to_string(),
+        commands,
+    }
+}
+
+#[test]
+fn test_batch_applies_as_one_undo_step() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let a = create_node(&mut adapter, "math.add");
+    let b = create_node(&mut adapter, "math.add");
+    let revision = adapter.document_revision();
+
+    let moves = [a, b]
+        .into_iter()
+        .map(|node_id| EditorCommand::MoveNode {
+            node_id,
+            new_position: NodePosition { x: 20.0, y: 30.0 },
+        })
+        .collect();
+    adapter
+        .apply_command(batch("Move Selection", moves))
+        .unwrap();
+
+    assert_eq!(adapter.document_revision(), revision + 1);
+    assert!(adapter
+        .nodes()
+        .iter()
+        .all(|n| n.position == NodePosition { x: 20.0, y: 30.0 }));
+    assert_eq!(adapter.undo_label(), Some("Move Selection"));
+
+    assert!(adapter.undo());
+    assert!(adapter
+        .nodes()
+        .iter()
+        .all(|n| n.position == NodePosition { x: 0.0, y: 0.0 }));
+    assert_eq!(adapter.undo_label(), Some("Create Node"));
+    assert_eq!(adapter.redo_label(), Some("Move Selection"));
+}
+
+#[test]
+fn test_failing_batch_rolls_back_everything() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let value = create_node(&mut adapter, "value.float");
+    let add = create_node(&mut adapter, "math.add");
+    let from = output_of(&adapter, value, 0);
+    let to = input_of(&adapter, add, 0);
+    adapter
+        .apply_command(EditorCommand::CreateEdge {
+            from,
+            to,
+            edge_id: None,
+        })
+        .unwrap();
+    let nodes = adapter.nodes().to_vec();
+    let sockets = adapter.sockets().to_vec();
+    let edges = adapter.edges().to_vec();
+    let revision = adapter.document_revision();
+
+    let result = adapter.apply_command(batch(
+        "Delete Selection",
+        vec![
+            EditorCommand::DeleteNode { node_id: value },
+            EditorCommand::MoveNode {
+                node_id: add,
+                new_position: NodePosition { x: 1.0, y: 1.0 },
+            },
+            EditorCommand::DeleteNode {
+                node_id: NodeId(99),
+            },
+        ],
+    ));
+
+    assert!(matches!(result, Err(DocumentError::NodeNotFound { .. })));
+
+    assert_eq!(adapter.document_revision(), revision);
+    assert_eq!(adapter.nodes(), nodes.as_slice());
+    assert_eq!(adapter.sockets(), sockets.as_slice());
+    assert_eq!(adapter.edges(), edges.as_slice());
+    assert_eq!(adapter.graph().links().count(), 1);
+    assert_eq!(adapter.undo_label(), Some("Create Edge"));
+}
+
+#[test]
+fn test_batch_commands_see_earlier_commands() {
+    let mut adapter = GraphDocumentAdapter::new();
+
+    adapter
+        .apply_command(batch(
+            "Add Nodes",
+            vec![
+                EditorCommand::CreateNode {
+                    node_kind: "math.add".to_string(),
+                    position: NodePosition { x: 0.0, y: 0.0 },
+                    node_id: Some(NodeId(10)),
+                },
+                EditorCommand::MoveNode {
+                    node_id: NodeId(10),
+                    new_position: NodePosition { x: 4.0, y: 2.0 },
+                },
+            ],
+        ))
+        .unwrap();
+
+    assert_eq!(adapter.nodes()[0].node_id, NodeId(10));
+    assert_eq!(adapter.nodes()[0].position, NodePosition { x: 4.0, y: 2.0 });
+
+    adapter.undo();
+    assert!(adapter.nodes().is_empty());
+    adapter.redo();
+    assert_eq!(adapter.nodes()[0].position, NodePosition { x: 4.0, y: 2.0 });
+}
+
+#[test]
+fn test_empty_batch_is_not_recorded() {
+    let mut adapter = GraphDocumentAdapter::new();
+
+    adapter.apply_command(batch("Nothing", vec![])).unwrap();
+
+    assert_eq!(adapter.document_revision(), 0);
+    assert!(!adapter.can_undo());
+}
+
+#[test]
+fn test_outcome_reports_created_ids() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let value = create_node(&mut adapter, "value.float");
+
+    let outcome = adapter
+        .apply_command(batch(
+            "Add And Connect",
+            vec![
+                EditorCommand::CreateNode {
+                    node_kind: "math.add".to_string(),
+                    position: NodePosition { x: 0.0, y: 0.0 },
+                    node_id: Some(NodeId(7)),
+                },
+                EditorCommand::CreateEdge {
+                    from: output_of(&adapter, value, 0),
+                    // Sockets of the new node follow those already allocated
+                    to: SocketId(2),
+                    edge_id: None,
+                },
+            ],
+        ))
+        .unwrap();
+
+    assert_eq!(outcome.created_nodes, vec![NodeId(7)]);
+    assert_eq!(outcome.created_edges, vec![adapter.edges()[0].edge_id]);
+    assert_eq!(outcome.revision, adapter.document_revision());
+}
+
+#[test]
+fn test_graph_errors_map_to_document_ids() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let value = create_node(&mut adapter, "value.vec3");
+    let add = create_node(&mut adapter, "math.add");
+    let from = output_of(&adapter, value, 0);
+    let to = input_of(&adapter, add, 0);
+
+    let result = adapter.apply_command(EditorCommand::CreateEdge {
+        from,
+        to,
+        edge_id: None,
+    });
+    assert!(matches!(
+        result,
+        Err(DocumentError::TypeMismatch { ref from, ref to }) if from == "Vec3" && to == "Float"
+    ));
+
+    let result = adapter.apply_command(EditorCommand::SetDefaultValue {
+        socket_id: to,
+        value: LiteralValue::Bool(true),
+    });
+    assert!(matches!(
+        result,
+        Err(DocumentError::DefaultTypeMismatch { socket_id, .. }) if socket_id == to
+    ));
+
+    let result = adapter.apply_command(EditorCommand::CreateEdge {
+        from: to,
+        to: from,
+        edge_id: None,
+    });
+    assert!(matches!(
+        result,
+        Err(DocumentError::WrongDirection {
+            expected: SocketDirection::Output,
+            found: SocketDirection::Input,
+        })
+    ));
+
+    let result = adapter.apply_command(EditorCommand::DeleteEdge { edge_id: EdgeId(5) });
+    assert!(matches!(
+        result,
+        Err(DocumentError::EdgeNotFound { edge_id: EdgeId(5) })
+    ));
+    assert_eq!(adapter.document_revision(), 2);
+}
+
+#[test]
+fn test_second_edge_into_input_is_rejected() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let a = create_node(&mut adapter, "value.float");
+    let b = create_node(&mut adapter, "value.float");
+    let add = create_node(&mut adapter, "math.add");
+    let to = input_of(&adapter, add, 0);
+    adapter
+        .apply_command(EditorCommand::CreateEdge {
+            from: output_of(&adapter, a, 0),
+            to,
+            edge_id: None,
+        })
+        .unwrap();
+
+    let result = adapter.apply_command(EditorCommand::CreateEdge {
+        from: output_of(&adapter, b, 0),
+        to,
+        edge_id: None,
+    });
+
+    assert!(matches!(
+        result,
+        Err(DocumentError::InputAlreadyConnected { socket_id }) if socket_id == to
+    ));
+}
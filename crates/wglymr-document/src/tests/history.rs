@@ -0,0 +1,111 @@
+use crate::history::History;
+
+fn undo(history: &mut History<u32>) {
+    let entry = history.pop_undo().unwrap();
+    history.push_redo(entry);
+}
+
+fn redo(history: &mut History<u32>) {
+    let entry = history.pop_redo().unwrap();
+    history.push_undo(entry);
+}
+
+#[test]
+fn test_new_history_is_clean_and_empty() {
+    let history = History::<u32>::new();
+
+    assert!(!history.can_undo());
+    assert!(!history.can_redo());
+    assert!(!history.is_dirty());
+}
+
+#[test]
+fn test_record_clears_redo() {
+    let mut history = History::new();
+    history.record("Edit", vec![1]);
+    history.record("Edit", vec![2]);
+    undo(&mut history);
+    assert!(history.can_redo());
+
+    history.record("Edit", vec![3]);
+
+    assert!(!history.can_redo());
+    assert_eq!(history.pop_undo().unwrap().changes, vec![3]);
+    assert_eq!(history.pop_undo().unwrap().changes, vec![1]);
+}
+
+#[test]
+fn test_saved_marker_follows_undo_and_redo() {
+    let mut history = History::new();
+    history.record("Edit", vec![1]);
+    history.mark_saved();
+    assert!(!history.is_dirty());
+
+    history.record("Edit", vec![2]);
+    assert!(history.is_dirty());
+
+    undo(&mut history);
+    assert!(!history.is_dirty());
+
+    undo(&mut history);
+    assert!(history.is_dirty());
+
+    redo(&mut history);
+    assert!(!history.is_dirty());
+}
+
+#[test]
+fn test_saved_state_discarded_from_redo_stays_dirty() {
+    let mut history = History::new();
+    history.record("Edit", vec![1]);
+    history.mark_saved();
+    undo(&mut history);
+
+    history.record("Edit", vec![2]);
+    assert!(history.is_dirty());
+    undo(&mut history);
+    assert!(history.is_dirty());
+}
+
+#[test]
+fn test_limit_drops_oldest_entries() {
+    let mut history = History::new();
+    history.set_limit(2);
+    for change in 1..=4 {
+        history.record("Edit", vec![change]);
+    }
+
+    assert_eq!(history.pop_undo().unwrap().changes, vec![4]);
+    assert_eq!(history.pop_undo().unwrap().changes, vec![3]);
+    assert!(!history.can_undo());
+    // The initial state can no longer be reached
+    assert!(history.is_dirty());
+}
+
+#[test]
+fn test_lowering_limit_trims_existing_entries() {
+    let mut history = History::new();
+    for change in 1..=3 {
+        history.record("Edit", vec![change]);
+    }
+
+    history.set_limit(1);
+
+    assert_eq!(history.limit(), 1);
+    assert_eq!(history.pop_undo().unwrap().changes, vec![3]);
+    assert!(!history.can_undo());
+}
+
+#[test]
+fn test_labels_follow_undo_and_redo() {
+    let mut history = History::new();
+    history.record("Create Node", vec![1]);
+    history.record("Move Node", vec![2]);
+    assert_eq!(history.undo_label(), Some("Move Node"));
+    assert_eq!(history.redo_label(), None);
+
+    undo(&mut history);
+
+    assert_eq!(history.undo_label(), Some("Create Node"));
+    assert_eq!(history.redo_label(), Some("Move Node"));
+}
@@ -0,0 +1,174 @@
+use super::{create_node, input_of, output_of};
+use crate::{
+    copy_selection, read_journal, replay_journal, verify_replay, verify_replay_with,
+    CommandJournal, DocumentAdapter, EditorCommand, GraphDocumentAdapter, JournalAction,
+    JournalError, JournaledDocument, LiteralValue, NodeId, NodePosition, PropertyValue,
+    ReplayError, ReplayOptions,
+};
+
+fn journaled() -> JournaledDocument<GraphDocumentAdapter> {
+    let mut time = 1_000;
+    let clock = Box::new(move || {
+        time += 10;
+        time
+    });
+    JournaledDocument::with_journal(
+        GraphDocumentAdapter::new(),
+        CommandJournal::with_clock(clock),
+    )
+}
+
+// Session with edges, defaults, properties, a paste, a failure and undo
+fn record_session(document: &mut JournaledDocument<GraphDocumentAdapter>) {
+    let value = create_node(document, "value.float");
+    let math = create_node(document, "math.math");
+    let commands = vec![
+        EditorCommand::CreateEdge {
+            from: output_of(document, value, 0),
+            to: input_of(document, math, 1),
+            edge_id: None,
+        },
+        EditorCommand::SetDefaultValue {
+            socket_id: document.snapshot().node(math).unwrap().inputs[0],
+            value: LiteralValue::Float(0.1),
+        },
+        EditorCommand::SetNodeProperty {
+            node_id: math,
+            name: "operation".to_string(),
+            value: PropertyValue::Enum("power".to_string()),
+        },
+        EditorCommand::MoveNode {
+            node_id: value,
+            new_position: NodePosition { x: 12.5, y: -3.0 },
+        },
+        EditorCommand::Paste {
+            payload: copy_selection(document, &[value, math]),
+            position: NodePosition { x: 300.0, y: 0.0 },
+            keep_upstream_edges: false,
+        },
+    ];
+    for command in commands {
+        document.apply_command(command).unwrap();
+    }
+    assert!(document
+        .apply_command(EditorCommand::DeleteNode {
+            node_id: NodeId(99),
+        })
+        .is_err());
+    document.undo();
+    document.redo();
+    document.undo();
+}
+
+#[test]
+fn test_journal_records_actions_with_revision_and_time() {
+    let mut document = journaled();
+    record_session(&mut document);
+    let entries = document.journal().entries();
+
+    assert_eq!(entries.len(), 11);
+    assert_eq!(entries[0].timestamp_ms, 1_010);
+    assert_eq!(entries[0].revision, 1);
+    assert!(entries[7].error.is_some());
+    assert_eq!(entries[7].revision, entries[6].revision);
+    assert_eq!(entries[8].action, JournalAction::Undo);
+    assert_eq!(
+        entries.last().unwrap().revision,
+        document.document_revision()
+    );
+}
+
+#[test]
+fn test_journal_round_trips_through_json_lines() {
+    let mut document = journaled();
+    record_session(&mut document);
+
+    let mut bytes = Vec::new();
+    document.journal().write_jsonl(&mut bytes).unwrap();
+    let text = String::from_utf8(bytes).unwrap();
+    assert_eq!(text.lines().count(), 11);
+
+    let entries = read_journal(text.as_bytes()).unwrap();
+    assert_eq!(entries, document.journal().entries());
+}
+
+#[test]
+fn test_replay_rebuilds_the_recorded_document() {
+    let mut document = journaled();
+    record_session(&mut document);
+    let mut bytes = Vec::new();
+    document.journal().write_jsonl(&mut bytes).unwrap();
+    let entries = read_journal(bytes.as_slice()).unwrap();
+
+    let replayed = verify_replay(&entries, &document.snapshot()).unwrap();
+
+    assert_eq!(replayed.nodes(), document.nodes());
+    assert_eq!(replayed.edges(), document.edges());
+    assert!(replayed.can_redo());
+}
+
+#[test]
+fn test_replay_reports_divergence() {
+    let mut document = journaled();
+    record_session(&mut document);
+    let mut entries = document.journal().entries().to_vec();
+
+    entries[2].revision += 1;
+    assert!(matches!(
+        replay_journal(&entries),
+        Err(ReplayError::RevisionMismatch { index: 2, .. })
+    ));
+
+    entries[2].revision -= 1;
+    entries[7].error = None;
+    assert!(matches!(
+        replay_journal(&entries),
+        Err(ReplayError::CommandFailed { index: 7, .. })
+    ));
+}
+
+#[test]
+fn test_verify_replay_checks_final_snapshot() {
+    let mut document = journaled();
+    record_session(&mut document);
+    let entries = document.journal().entries();
+
+    let result = verify_replay(&entries[..entries.len() - 1], &document.snapshot());
+
+    assert!(matches!(result, Err(ReplayError::SnapshotMismatch)));
+}
+
+#[test]
+fn test_replay_of_on_demand_diagnostics_session() {
+    let mut recorded = GraphDocumentAdapter::new();
+    recorded.set_diagnostics_on_change(false);
+    recorded.set_history_limit(1);
+    let options = ReplayOptions::of(&recorded);
+    let mut document = JournaledDocument::new(recorded);
+
+    create_node(&mut document, "utility.reroute");
+    create_node(&mut document, "value.float");
+    document.refresh_diagnostics();
+    document.undo();
+    assert!(!document.can_undo());
+    let entries = document.journal().entries();
+
+    let replayed = verify_replay_with(entries, &document.snapshot(), options).unwrap();
+    assert!(!replayed.diagnostics_on_change());
+    assert!(!replayed.can_undo());
+
+    // With the settings of a new document the refresh changes nothing
+    assert!(matches!(
+        replay_journal(entries),
+        Err(ReplayError::RevisionMismatch { index: 2, .. })
+    ));
+}
+
+#[test]
+fn test_read_journal_reports_bad_line() {
+    let text = "\n{\"timestamp_ms\":1,\"action\":\"Undo\",\"revision\":0}\nnot json\n";
+
+    let result = read_journal(text.as_bytes());
+
+    assert!(matches!(result, Err(JournalError::Format { line: 3, .. })));
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod clipboard;
+#[cfg(test)]
+mod diagnostics;
+#[cfg(test)]
+mod events;
+#[cfg(test)]
+mod graph_adapter;
+#[cfg(test)]
+mod history;
+#[cfg(test)]
+mod journal;
+#[cfg(test)]
+mod persistence;
+#[cfg(test)]
+mod snapshot;
+
+// Helpers shared by the test modules
+
+#[cfg(test)]
+use crate::{DocumentAdapter, EditorCommand, NodeDescriptor, NodeId, NodePosition, SocketId};
+
+#[cfg(test)]
+fn create_node(document: &mut dyn DocumentAdapter, node_kind: &str) -> NodeId {
+    create_node_at(document, node_kind, 0.0, 0.0)
+}
+
+#[cfg(test)]
+fn create_node_at(document: &mut dyn DocumentAdapter, node_kind: &str, x: f32, y: f32) -> NodeId {
+    document
+        .apply_command(EditorCommand::CreateNode {
+            node_kind: node_kind.to_string(),
+            position: NodePosition { x, y },
+            node_id: None,
+        })
+        .unwrap()
+        .created_nodes[0]
+}
+
+// Visible input socket of a node by index
+#[cfg(test)]
+fn input_of(document: &dyn DocumentAdapter, node_id: NodeId, index: usize) -> SocketId {
+    node_of(document, node_id).inputs[index]
+}
+
+#[cfg(test)]
+fn output_of(document: &dyn DocumentAdapter, node_id: NodeId, index: usize) -> SocketId {
+    node_of(document, node_id).outputs[index]
+}
+
+#[cfg(test)]
+fn node_of(document: &dyn DocumentAdapter, node_id: NodeId) -> &NodeDescriptor {
+    document
+        .nodes()
+        .iter()
+        .find(|node| node.node_id == node_id)
+        .unwrap()
+}
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+
+use super::{create_node, create_node_at, input_of, output_of};
+use crate::{
+    load_document, save_document, DocumentAdapter, DocumentFileError, EditorCommand, EditorState,
+    Frame, GraphDocumentAdapter, LiteralValue, NodeDisplay, NodeId, NodePosition, PropertyValue,
+    ViewCamera, DOCUMENT_FORMAT_VERSION,
+};
+
+// Document with edges, defaults, properties and a deleted node
+fn build_document() -> GraphDocumentAdapter {
+    let mut adapter = GraphDocumentAdapter::new();
+    let value = create_node_at(&mut adapter, "value.float", 0.0, 10.0);
+    let math = create_node_at(&mut adapter, "math.math", 200.0, 10.0);
+    let removed = create_node_at(&mut adapter, "math.add", 400.0, 10.0);
+
+    let commands = vec![
+        EditorCommand::CreateEdge {
+            from: output_of(&adapter, value, 0),
+            to: input_of(&adapter, math, 0),
+            edge_id: None,
+        },
+        EditorCommand::SetDefaultValue {
+            socket_id: input_of(&adapter, math, 1),
+            value: LiteralValue::Float(3.0),
+        },
+        EditorCommand::SetNodeProperty {
+            node_id: math,
+            name: "operation".to_string(),
+            value: PropertyValue::Enum("power".to_string()),
+        },
+        EditorCommand::MoveNode {
+            node_id: value,
+            new_position: NodePosition { x: -50.0, y: 25.0 },
+        },
+        EditorCommand::DeleteNode { node_id: removed },
+    ];
+    for command in commands {
+        adapter.apply_command(command).unwrap();
+    }
+    adapter
+}
+
+fn round_trip(
+    document: &GraphDocumentAdapter,
+    editor: &EditorState,
+) -> (GraphDocumentAdapter, EditorState) {
+    let mut bytes = Vec::new();
+    save_document(document, editor, &mut bytes).unwrap();
+    load_document(bytes.as_slice()).unwrap()
+}
+
+#[test]
+fn test_round_trip_preserves_descriptors() {
+    let document = build_document();
+    let (loaded, _) = round_trip(&document, &EditorState::default());
+
+    assert_eq!(loaded.nodes(), document.nodes());
+    assert_eq!(loaded.sockets(), document.sockets());
+    assert_eq!(loaded.edges(), document.edges());
+}
+
+#[test]
+fn test_loaded_document_starts_fresh() {
+    let mut document = build_document();
+    document.mark_saved();
+    let (loaded, _) = round_trip(&document, &EditorState::default());
+
+    assert_eq!(loaded.document_revision(), 1);
+    assert_eq!(loaded.snapshot().revision(), 1);
+    assert!(!loaded.can_undo());
+    assert!(!loaded.can_redo());
+    assert!(!loaded.is_dirty());
+}
+
+#[test]
+fn test_loaded_document_does_not_reuse_ids() {
+    let document = build_document();
+    let (mut loaded, _) = round_trip(&document, &EditorState::default());
+
+    // Node 3 was deleted before saving
+    let node_id = create_node(&mut loaded, "math.add");
+    assert_eq!(node_id, NodeId(4));
+    let existing: Vec<_> = document.sockets().iter().map(|s| s.socket_id).collect();
+    let node = loaded.snapshot().node(node_id).unwrap().clone();
+    assert!(node
+        .inputs
+        .iter()
+        .chain(&node.outputs)
+        .all(|socket_id| !existing.contains(socket_id)));
+}
+
+#[test]
+fn test_round_trip_preserves_editor_state() {
+    let document = build_document();
+    let editor = EditorState {
+        views: vec![ViewCamera {
+            view_id: "main".to_string(),
+            pan: [120.0, -40.0],
+            zoom: 1.5,
+        }],
+        nodes: HashMap::from([
+            (
+                NodeId(1),
+                NodeDisplay {
+                    collapsed: true,
+                    muted: false,
+                },
+            ),
+            (
+                NodeId(2),
+                NodeDisplay {
+                    collapsed: false,
+                    muted: true,
+                },
+            ),
+        ]),
+        frames: vec![Frame {
+            label: "Inputs".to_string(),
+            position: [-80.0, -20.0],
+            size: [360.0, 140.0],
+            nodes: vec![NodeId(1), NodeId(2)],
+        }],
+    };
+    let (_, loaded) = round_trip(&document, &editor);
+
+    assert_eq!(loaded, editor);
+}
+
+#[test]
+fn test_editor_state_fields_default_when_missing() {
+    let mut bytes = Vec::new();
+    save_document(&build_document(), &EditorState::default(), &mut bytes).unwrap();
+    let mut file: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    file["editor"] = serde_json::json!({ "views": [] });
+
+    let (_, editor) = load_document(file.to_string().as_bytes()).unwrap();
+
+    assert_eq!(editor, EditorState::default());
+}
+
+#[test]
+fn test_load_rejects_newer_format() {
+    let mut bytes = Vec::new();
+    save_document(&build_document(), &EditorState::default(), &mut bytes).unwrap();
+    let mut file: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    file["version"] = (DOCUMENT_FORMAT_VERSION + 1).into();
+
+    let result = load_document(file.to_string().as_bytes());
+
+    assert!(matches!(
+        result,
+        Err(DocumentFileError::UnsupportedVersion { found, .. }) if found == DOCUMENT_FORMAT_VERSION + 1
+    ));
+}
+
+#[test]
+fn test_load_rejects_ids_not_matching_graph() {
+    let mut bytes = Vec::new();
+    save_document(&build_document(), &EditorState::default(), &mut bytes).unwrap();
+    let mut file: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    file["ids"]["nodes"]["entries"]
+        .as_array_mut()
+        .unwrap()
+        .pop();
+
+    let result = load_document(file.to_string().as_bytes());
+
+    assert!(matches!(result, Err(DocumentFileError::Inconsistent)));
+}
+
+#[test]
+fn test_load_rejects_malformed_file() {
+    let result = load_document("{ \"version\": 1 }".as_bytes());
+
+    assert!(matches!(result, Err(DocumentFileError::Format(_))));
+}
+
+#[test]
+fn test_load_rejects_malformed_links() {
+    let mut bytes = Vec::new();
+    save_document(&build_document(), &EditorState::default(), &mut bytes).unwrap();
+    let file: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+    // A link ending at an output socket, and one starting at an unmapped socket
+    let reversed = |link: &mut serde_json::Value| link["to"] = link["from"].clone();
+    let unmapped = |link: &mut serde_json::Value| link["from"] = 9999.into();
+    for corrupt in [&reversed as &dyn Fn(&mut serde_json::Value), &unmapped] {
+        let mut file = file.clone();
+        for link in file["graph"]["links"].as_object_mut().unwrap().values_mut() {
+            corrupt(link);
+        }
+
+        let result = load_document(file.to_string().as_bytes());
+
+        assert!(matches!(result, Err(DocumentFileError::Inconsistent)));
+    }
+}
+
+// Add a link with a fresh edge id to a saved document file
+fn add_link(file: &mut serde_json::Value, from: u64, to: u64) {
+    let graph = &mut file["graph"];
+    let id = graph["next_link_id"].as_u64().unwrap();
+    graph["next_link_id"] = (id + 1).into();
+    graph["links"][id.to_string()] = serde_json::json!({ "id": id, "from": from, "to": to });
+    let edges = &mut file["ids"]["edges"];
+    let edge_id = edges["next"].as_u64().unwrap();
+    edges["next"] = (edge_id + 1).into();
+    edges["entries"]
+        .as_array_mut()
+        .unwrap()
+        .push(serde_json::json!([edge_id, id]));
+}
+
+// Core ids of the saved sockets matching the predicate, in id order
+fn core_sockets(
+    file: &serde_json::Value,
+    matches: impl Fn(&serde_json::Value) -> bool,
+) -> Vec<u64> {
+    let mut sockets: Vec<u64> = file["graph"]["sockets"]
+        .as_object()
+        .unwrap()
+        .values()
+        .filter(|socket| matches(socket))
+        .map(|socket| socket["id"].as_u64().unwrap())
+        .collect();
+    sockets.sort_unstable();
+    sockets
+}
+
+#[test]
+fn test_load_rejects_incompatible_link() {
+    let mut adapter = GraphDocumentAdapter::new();
+    create_node(&mut adapter, "value.vec3");
+    create_node(&mut adapter, "math.add");
+    let mut bytes = Vec::new();
+    save_document(&adapter, &EditorState::default(), &mut bytes).unwrap();
+    let mut file: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+    // Vec3 output into a Float input
+    let from = core_sockets(&file, |socket| socket["value_type"] == "Vec3")[0];
+    let to = core_sockets(&file, |socket| {
+        socket["direction"] == "Input" && socket["value_type"] == "Float"
+    })[0];
+    add_link(&mut file, from, to);
+
+    let result = load_document(file.to_string().as_bytes());
+
+    assert!(matches!(result, Err(DocumentFileError::Inconsistent)));
+}
+
+#[test]
+fn test_load_rejects_second_link_into_input() {
+    let mut bytes = Vec::new();
+    save_document(&build_document(), &EditorState::default(), &mut bytes).unwrap();
+    let mut file: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    let link = file["graph"]["links"]["0"].clone();
+    add_link(
+        &mut file,
+        link["from"].as_u64().unwrap(),
+        link["to"].as_u64().unwrap(),
+    );
+
+    let result = load_document(file.to_string().as_bytes());
+
+    assert!(matches!(result, Err(DocumentFileError::Inconsistent)));
+}
+
+#[test]
+fn test_load_rejects_cycle_when_graph_rejects_cycles() {
+    let mut bytes = Vec::new();
+    save_document(&build_document(), &EditorState::default(), &mut bytes).unwrap();
+    let mut file: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+    // Math result back into its unlinked B input
+    let linked = file["graph"]["links"]["0"]["to"].to_string();
+    let math = file["graph"]["sockets"][linked]["node"].clone();
+    let result_socket = core_sockets(&file, |socket| {
+        socket["node"] == math && socket["direction"] == "Output"
+    })[0];
+    let b_input = core_sockets(&file, |socket| {
+        socket["node"] == math && socket["name"] == "B"
+    })[0];
+    add_link(&mut file, result_socket, b_input);
+
+    // Documents may hold cycles unless the graph refuses to create them
+    assert!(load_document(file.to_string().as_bytes()).is_ok());
+    file["graph"]["reject_cycles"] = true.into();
+    let result = load_document(file.to_string().as_bytes());
+
+    assert!(matches!(result, Err(DocumentFileError::Inconsistent)));
+}
+
+#[test]
+fn test_load_rejects_socket_owned_by_another_node() {
+    let mut bytes = Vec::new();
+    save_document(&build_document(), &EditorState::default(), &mut bytes).unwrap();
+    let mut file: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    let nodes: Vec<serde_json::Value> = file["graph"]["nodes"]
+        .as_object()
+        .unwrap()
+        .values()
+        .map(|node| node["id"].clone())
+        .collect();
+    let socket = file["graph"]["sockets"]
+        .as_object_mut()
+        .unwrap()
+        .values_mut()
+        .next()
+        .unwrap();
+    let other = nodes.iter().find(|&id| *id != socket["node"]).unwrap();
+    socket["node"] = other.clone();
+
+    let result = load_document(file.to_string().as_bytes());
+
+    assert!(matches!(result, Err(DocumentFileError::Inconsistent)));
+}
@@ -0,0 +1,104 @@
+use super::create_node;
+use crate::{
+    DocumentAdapter, EditorCommand, GraphDocumentAdapter, GraphSnapshot, NodeId, NodePosition,
+};
+
+fn move_node(adapter: &mut GraphDocumentAdapter, node_id: NodeId, x: f32) {
+    adapter
+        .apply_command(EditorCommand::MoveNode {
+            node_id,
+            new_position: NodePosition { x, y: 0.0 },
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_snapshot_looks_up_entities_by_id() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let node_id = create_node(&mut adapter, "math.add");
+    let snapshot = adapter.snapshot();
+
+    let node = snapshot.node(node_id).unwrap();
+    assert_eq!(node.node_kind, "math.add");
+    for &socket_id in node.inputs.iter().chain(&node.outputs) {
+        assert_eq!(snapshot.socket(socket_id).unwrap().node_id, node_id);
+    }
+    assert!(snapshot.node(NodeId(99)).is_none());
+    assert_eq!(snapshot.nodes().len(), 1);
+}
+
+#[test]
+fn test_snapshot_shares_unchanged_nodes() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let moved = create_node(&mut adapter, "math.add");
+    let still = create_node(&mut adapter, "math.add");
+    let before = adapter.snapshot();
+
+    move_node(&mut adapter, moved, 50.0);
+    let after = adapter.snapshot();
+
+    assert!(std::ptr::eq(
+        before.node(still).unwrap(),
+        after.node(still).unwrap()
+    ));
+    assert!(!std::ptr::eq(
+        before.node(moved).unwrap(),
+        after.node(moved).unwrap()
+    ));
+    assert_eq!(after.node(moved).unwrap().position.x, 50.0);
+    assert_eq!(before.node(moved).unwrap().position.x, 0.0);
+}
+
+#[test]
+fn test_snapshot_stamps_entities_with_last_change() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let first = create_node(&mut adapter, "math.add");
+    let second = create_node(&mut adapter, "math.add");
+    move_node(&mut adapter, first, 10.0);
+    let snapshot = adapter.snapshot();
+
+    assert_eq!(snapshot.revision(), 3);
+    assert_eq!(snapshot.node_revision(first), Some(3));
+    assert_eq!(snapshot.node_revision(second), Some(2));
+    let socket_id = snapshot.node(second).unwrap().inputs[0];
+    assert_eq!(snapshot.socket_revision(socket_id), Some(2));
+}
+
+#[test]
+fn test_snapshot_changes_between_revisions() {
+    let mut adapter = GraphDocumentAdapter::new();
+    let kept = create_node(&mut adapter, "math.add");
+    let moved = create_node(&mut adapter, "math.add");
+    let deleted = create_node(&mut adapter, "math.add");
+    let before = adapter.snapshot();
+
+    move_node(&mut adapter, moved, 10.0);
+    adapter
+        .apply_command(EditorCommand::DeleteNode { node_id: deleted })
+        .unwrap();
+    let added = create_node(&mut adapter, "math.add");
+    let after = adapter.snapshot();
+
+    let changes = after.node_changes(&before);
+    assert_eq!(changes.added, vec![added]);
+    assert_eq!(changes.changed, vec![moved]);
+    assert_eq!(changes.removed, vec![deleted]);
+    assert!(!changes.changed.contains(&kept));
+    assert!(after.node_changes(&after).is_empty());
+}
+
+#[test]
+fn test_snapshot_update_keeps_equal_descriptors() {
+    let mut adapter = GraphDocumentAdapter::new();
+    create_node(&mut adapter, "math.add");
+    let snapshot = adapter.snapshot();
+    let nodes: Vec<_> = snapshot.nodes().cloned().collect();
+    let sockets: Vec<_> = snapshot.sockets().cloned().collect();
+
+    let next = snapshot.update(5, &nodes, &sockets, &[]);
+
+    assert_eq!(next.revision(), 5);
+    assert!(next.node_changes(&snapshot).is_empty());
+    assert!(next.socket_changes(&snapshot).is_empty());
+    assert!(GraphSnapshot::empty().edge_changes(&next).is_empty());
+}
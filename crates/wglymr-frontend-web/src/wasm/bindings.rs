@@ -184,6 +184,17 @@ pub fn set_visible(id: &str, visible: bool) {
     });
 }
 
+/// Cost report of the latest compilation as JSON, `null` when unavailable.
+#[wasm_bindgen]
+pub fn get_compile_stats() -> String {
+    EditorRuntime::with(|rt| {
+        serde_json::to_string(&rt.compile_stats()).unwrap_or_else(|e| {
+            wglymr_runtime::logging::error(&format!("Failed to serialize compile stats: {}", e));
+            "null".to_string()
+        })
+    })
+}
+
 #[wasm_bindgen]
 pub fn render() {
     EditorRuntime::with(|rt| {
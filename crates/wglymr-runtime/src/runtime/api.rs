@@ -1,6 +1,7 @@
 use super::errors::RuntimeError;
 use super::EditorRuntime;
 use wglymr_app::ViewId;
+use wglymr_core::CompileStats;
 
 impl EditorRuntime {
     pub fn init_engine(&mut self) -> Result<(), RuntimeError> {
@@ -271,6 +272,11 @@ impl EditorRuntime {
         Ok(())
     }
 
+    /// Cost report of the document's most recent compilation
+    pub fn compile_stats(&self) -> Option<CompileStats> {
+        self.engine.document().compile_stats()
+    }
+
     pub fn set_visible(&mut self, id: &str, visible: bool) -> Result<(), RuntimeError> {
         self.gpu_views.set_visible(id, visible)?;
         Ok(())
@@ -1,3 +1,5 @@
+use wglymr_document::{EditorCommand, NodePosition};
+
 use super::Command;
 use crate::runtime::{errors::RuntimeError, logging, EditorRuntime};
 pub fn dispatch(runtime: &mut EditorRuntime, command: Command) -> Result<(), RuntimeError> {
@@ -114,6 +116,15 @@ fn handle_node_add(
     x: f32,
     y: f32,
 ) -> Result<(), RuntimeError> {
+    runtime
+        .engine_mut()
+        .apply_command(EditorCommand::CreateNode {
+            node_kind: node_type.to_string(),
+            position: NodePosition { x, y },
+            node_id: None,
+        })
+        .map_err(|error| RuntimeError::CommandFailed(error.to_string()))?;
+
     let view_ids: Vec<String> = runtime
         .gpu_views()
         .iter()
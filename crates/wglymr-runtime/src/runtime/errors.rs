@@ -9,6 +9,7 @@ pub enum RuntimeError {
     SurfaceCreationFailed(String),
     RenderFailed(String),
     InvalidState(String),
+    CommandFailed(String),
 }
 
 impl fmt::Display for RuntimeError {
@@ -25,6 +26,7 @@ impl fmt::Display for RuntimeError {
             }
             RuntimeError::RenderFailed(msg) => write!(f, "Render failed: {}", msg),
             RuntimeError::InvalidState(msg) => write!(f, "Invalid state: {}", msg),
+            RuntimeError::CommandFailed(msg) => write!(f, "Command failed: {}", msg),
         }
     }
 }
@@ -1,7 +1,7 @@
 use std::cell::RefCell;
 
 use wglymr_app::EditorEngine;
-use wglymr_document::test_adapter::TestDocumentAdapter;
+use wglymr_document::GraphDocumentAdapter;
 
 pub mod api;
 #[cfg(target_arch = "wasm32")]
@@ -31,9 +31,7 @@ pub struct EditorRuntime {
 
 impl EditorRuntime {
     fn new() -> Self {
-        // TEMPORARY: Using test adapter for visual validation
-        // TODO: Replace with real graph adapter when integration is complete
-        let document = Box::new(TestDocumentAdapter::new());
+        let document = Box::new(GraphDocumentAdapter::new());
         let engine = EditorEngine::new(document);
 
         Self {
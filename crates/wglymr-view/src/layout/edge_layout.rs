@@ -3,12 +3,12 @@ use std::collections::HashMap;
 use wglymr_document::EdgeDescriptor;
 use wglymr_document::SocketId;
 
-pub fn build_render_edges(
-    edges: &[EdgeDescriptor],
+pub fn build_render_edges<'a>(
+    edges: impl IntoIterator<Item = &'a EdgeDescriptor>,
     socket_positions: &HashMap<SocketId, [f32; 2]>,
 ) -> Vec<RenderEdge> {
     edges
-        .iter()
+        .into_iter()
         .filter_map(|edge| build_render_edge(edge, socket_positions))
         .collect()
 }
@@ -1,15 +1,10 @@
-use std::collections::HashMap;
 use wglymr_color::Color;
-use wglymr_document::{NodeDescriptor, SocketDescriptor};
+use wglymr_document::{GraphSnapshot, NodeDescriptor};
 
 use super::{NodeBodyUI, NodeHeaderUI, NodeUIDefinition, SocketUIDefinition, SocketVisualType};
 
 pub trait NodeUIProvider {
-    fn ui_definition(
-        &self,
-        node: &NodeDescriptor,
-        socket_map: &HashMap<wglymr_document::SocketId, &SocketDescriptor>,
-    ) -> NodeUIDefinition;
+    fn ui_definition(&self, node: &NodeDescriptor, snapshot: &GraphSnapshot) -> NodeUIDefinition;
 }
 
 pub struct DefaultNodeUIProvider {
@@ -33,11 +28,7 @@ impl Default for DefaultNodeUIProvider {
 }
 
 impl NodeUIProvider for DefaultNodeUIProvider {
-    fn ui_definition(
-        &self,
-        node: &NodeDescriptor,
-        socket_map: &HashMap<wglymr_document::SocketId, &SocketDescriptor>,
-    ) -> NodeUIDefinition {
+    fn ui_definition(&self, node: &NodeDescriptor, snapshot: &GraphSnapshot) -> NodeUIDefinition {
         let header = NodeHeaderUI::new(
             node.node_kind.clone(),
             self.header_color,
@@ -50,7 +41,7 @@ impl NodeUIProvider for DefaultNodeUIProvider {
             .inputs
             .iter()
             .filter_map(|socket_id| {
-                socket_map.get(socket_id).map(|socket| {
+                snapshot.socket(*socket_id).map(|socket| {
                     SocketUIDefinition::new(
                         *socket_id,
                         socket.name.clone(),
@@ -64,7 +55,7 @@ impl NodeUIProvider for DefaultNodeUIProvider {
             .outputs
             .iter()
             .filter_map(|socket_id| {
-                socket_map.get(socket_id).map(|socket| {
+                snapshot.socket(*socket_id).map(|socket| {
                     SocketUIDefinition::new(
                         *socket_id,
                         socket.name.clone(),
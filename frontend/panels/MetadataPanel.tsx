@@ -3,10 +3,36 @@
 import { useDocumentStore } from "@/document/useDocumentStore";
 import { documentStore } from "@/document/documentStore";
 import { usePanelContext } from "@/context/PanelContext";
+import { useEffect, useState } from "react";
+import { getCompileStats, type CompileStats } from "@/runtime";
+
+// Refresh interval for the compile cost report
+const STATS_POLL_MS = 1000;
+
+function useCompileStats(): CompileStats | null {
+    const [stats, setStats] = useState<CompileStats | null>(() => getCompileStats());
+
+    useEffect(() => {
+        const interval = setInterval(() => setStats(getCompileStats()), STATS_POLL_MS);
+        return () => clearInterval(interval);
+    }, []);
+
+    return stats;
+}
+
+function StatRow({ label, value }: { label: string; value: number | string }) {
+    return (
+        <div className="flex items-center justify-between text-xs">
+            <span className="text-gray-500">{label}</span>
+            <span className="font-mono text-gray-300">{value}</span>
+        </div>
+    );
+}
 
 export function MetadataPanel() {
     const { panelId } = usePanelContext();
     const { title, description, isPublic } = useDocumentStore();
+    const stats = useCompileStats();
 
     return (
         <div className="h-full overflow-y-auto p-3 bg-zinc-950">
@@ -53,6 +79,35 @@ export function MetadataPanel() {
                         </div>
                     </label>
                 </div>
+
+                {/* Shader Cost */}
+                <div className="space-y-2">
+                    <label className="text-xs font-semibold text-gray-300">Shader Cost</label>
+                    {stats ? (
+                        <div className="p-3 rounded bg-white/2 border border-white/6 space-y-1">
+                            <StatRow label="Estimated cost" value={stats.estimated_cost} />
+                            <StatRow label="ALU" value={stats.counts.alu} />
+                            <StatRow label="Conversions" value={stats.counts.conversions} />
+                            <StatRow label="Texture samples" value={stats.counts.texture_samples} />
+                            <StatRow label="Helper calls" value={stats.counts.helper_calls} />
+                            <StatRow label="Live values" value={stats.max_live_values} />
+                            {stats.top_nodes.length > 0 && (
+                                <div className="pt-2 space-y-1">
+                                    <span className="text-[10px] text-gray-600">Most expensive nodes</span>
+                                    {stats.top_nodes.map((entry) => (
+                                        <StatRow
+                                            key={entry.node}
+                                            label={`Node ${entry.node}`}
+                                            value={entry.cost}
+                                        />
+                                    ))}
+                                </div>
+                            )}
+                        </div>
+                    ) : (
+                        <span className="text-[10px] text-gray-600">Not compiled yet</span>
+                    )}
+                </div>
             </div>
         </div>
     );
@@ -43,3 +43,23 @@ export function getWasmModule(): any {
 export function isRuntimeReady(): boolean {
     return isInitialized;
 }
+
+export interface CompileStats {
+    counts: {
+        alu: number;
+        conversions: number;
+        texture_samples: number;
+        helper_calls: number;
+    };
+    estimated_cost: number;
+    max_live_values: number;
+    top_nodes: { node: number; cost: number; instructions: number }[];
+}
+
+// Cost report of the latest compilation, null before the first one.
+export function getCompileStats(): CompileStats | null {
+    if (!isInitialized || typeof wasmModule?.get_compile_stats !== "function") {
+        return null;
+    }
+    return JSON.parse(wasmModule.get_compile_stats()) as CompileStats | null;
+}
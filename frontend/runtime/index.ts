@@ -1,6 +1,7 @@
 // Public runtime API - UI code must not import WASM bindings directly.
 
-export { ensureEditorRuntimeReady } from "./editorRuntime";
+export { ensureEditorRuntimeReady, getCompileStats } from "./editorRuntime";
+export type { CompileStats } from "./editorRuntime";
 
 export {
     createEditorView,